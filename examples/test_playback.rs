@@ -1,8 +1,9 @@
 use remu_audio::events;
+use remu_audio::loader::LoaderOptions;
 use remu_audio::player;
 
-use anyhow::Result;
 use player::Player;
+use remu_audio::Result;
 use std::thread;
 use std::time::Duration;
 
@@ -53,6 +54,9 @@ async fn main() -> Result<()> {
                 drop(cl);
                 println!("[@VolumeChange] 音量: {:.0}%", volume * 100.0);
             }
+            PlayerEvent::RateChange => {
+                println!("[@RateChange] 播放速率变化");
+            }
             PlayerEvent::Seeking => {
                 println!("[@Seeking] 正在跳转...");
             }
@@ -66,10 +70,28 @@ async fn main() -> Result<()> {
                 println!("[@LoadedData] 已加载首帧数据");
             }
             PlayerEvent::LoadedMetadata => {
+                let cl = control.read().unwrap();
+                let title = cl.metadata().and_then(|m| m.title.clone());
+                drop(cl);
                 println!("[@LoadedMetadata] 元数据加载完成，准备播放");
+                if let Some(title) = title {
+                    println!("[@LoadedMetadata] 标题: {}", title);
+                }
+            }
+            PlayerEvent::TrackChange { index } => {
+                println!("[@TrackChange] 当前队列项: {}", index);
             }
-            PlayerEvent::Error { message } => {
-                println!("[@Error] 错误: {}", message);
+            PlayerEvent::MetadataChange { metadata } => {
+                println!(
+                    "[@MetadataChange] 元数据更新: {}",
+                    metadata.title.unwrap_or_default()
+                );
+            }
+            PlayerEvent::DeviceChange { name } => {
+                println!("[@DeviceChange] 输出设备: {}", name.unwrap_or_default());
+            }
+            PlayerEvent::Error { error } => {
+                println!("[@Error] 错误: {}", error);
             }
         }
     });
@@ -78,8 +100,17 @@ async fn main() -> Result<()> {
         remu_audio::loader::LoaderEvent::Completed => {
             println!("[@Loader:Completed] 内容加载完成")
         }
-        remu_audio::loader::LoaderEvent::Aborted => {
-            println!("[@Loader:Aborted] 内容加载中止")
+        remu_audio::loader::LoaderEvent::Aborted { error } => {
+            println!("[@Loader:Aborted] 内容加载中止: {}", error)
+        }
+        remu_audio::loader::LoaderEvent::Retrying { attempt } => {
+            println!("[@Loader:Retrying] 连接中断，第 {} 次重试", attempt)
+        }
+        remu_audio::loader::LoaderEvent::Resumed => {
+            println!("[@Loader:Resumed] 已恢复下载")
+        }
+        remu_audio::loader::LoaderEvent::Progress { downloaded, total } => {
+            println!("[@Loader:Progress] 已下载 {}/{} 字节", downloaded, total)
         }
     });
 
@@ -145,7 +176,7 @@ async fn main() -> Result<()> {
     let file_url = "https://download.samplelib.com/mp3/sample-15s.mp3";
 
     println!("加载文件开始: {}", file_url);
-    player.load_url(file_url).await?;
+    player.load_url(file_url, LoaderOptions::default()).await?;
     thread::sleep(Duration::from_secs(20));
 
     println!("测试完成！");
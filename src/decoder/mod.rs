@@ -60,6 +60,8 @@ use rodio::{
     ChannelCount, Sample, SampleRate,
 };
 
+use crate::metadata::AudioMetadata;
+
 pub mod builder;
 pub use builder::{DecoderBuilder, Settings};
 
@@ -168,6 +170,22 @@ impl<R: Read + Seek> DecoderImpl<R> {
             DecoderImpl::None(_, _) => unreachable!(),
         }
     }
+
+    #[inline]
+    fn metadata(&self) -> &AudioMetadata {
+        match self {
+            DecoderImpl::Symphonia(source, PhantomData) => source.metadata(),
+            DecoderImpl::None(_, _) => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn set_metadata_callback(&mut self, callback: Box<dyn Fn(AudioMetadata) + Send>) {
+        match self {
+            DecoderImpl::Symphonia(source, PhantomData) => source.set_metadata_callback(callback),
+            DecoderImpl::None(_, _) => unreachable!(),
+        }
+    }
 }
 
 /// Converts a `File` into a `Decoder` with automatic optimizations.
@@ -467,6 +485,27 @@ impl<R: Read + Seek + Send + Sync + 'static> Decoder<R> {
     }
 }
 
+impl<R: Read + Seek> Decoder<R> {
+    /// Returns the metadata (tags and embedded pictures) read while opening the stream.
+    ///
+    /// Tags found in the container take precedence over tags found during probing.
+    #[inline]
+    pub fn metadata(&self) -> &AudioMetadata {
+        self.0.metadata()
+    }
+
+    /// Sets a callback invoked with the new tags whenever the stream publishes a metadata
+    /// revision during playback, such as a chained Ogg stream or an in-band ID3 frame.
+    ///
+    /// Replaces any previously set callback.
+    pub fn set_metadata_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(AudioMetadata) + Send + 'static,
+    {
+        self.0.set_metadata_callback(Box::new(callback));
+    }
+}
+
 impl<R> Iterator for Decoder<R>
 where
     R: Read + Seek,
@@ -17,6 +17,7 @@ use symphonia::{
 };
 
 use super::Settings;
+use crate::metadata::AudioMetadata;
 use rodio::{decoder::DecoderError, source, ChannelCount, Sample, SampleRate, Source};
 
 pub struct SymphoniaDecoder {
@@ -27,6 +28,8 @@ pub struct SymphoniaDecoder {
     buffer: SampleBuffer<Sample>,
     spec: SignalSpec,
     seek_mode: SeekMode,
+    metadata: AudioMetadata,
+    metadata_callback: Option<Box<dyn Fn(AudioMetadata) + Send>>,
 }
 
 impl SymphoniaDecoder {
@@ -47,6 +50,21 @@ impl SymphoniaDecoder {
         }
     }
 
+    /// Returns the tags read from the container and during probing.
+    #[inline]
+    pub fn metadata(&self) -> &AudioMetadata {
+        &self.metadata
+    }
+
+    /// Sets a callback invoked with the new tags whenever a metadata revision is found
+    /// in the middle of the stream (e.g. chained Ogg streams or in-band ID3 frames).
+    pub fn set_metadata_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(AudioMetadata) + Send + 'static,
+    {
+        self.metadata_callback = Some(Box::new(callback));
+    }
+
     #[inline]
     pub(crate) fn into_inner(self) -> MediaSourceStream {
         self.format.into_inner()
@@ -77,8 +95,12 @@ impl SymphoniaDecoder {
 
         // Prefer metadata that's provided in the container format, over other tags found during the
         // probe operation.
+        let mut metadata = AudioMetadata::default();
         if let Some(metadata_rev) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
-            println!("Tags: {:?}", metadata_rev.tags());
+            metadata.merge(AudioMetadata::from_revision(metadata_rev));
+        }
+        if let Some(metadata_rev) = probed.format.metadata().skip_to_latest() {
+            metadata.merge(AudioMetadata::from_revision(metadata_rev));
         }
 
         let stream = match probed.format.default_track() {
@@ -150,9 +172,27 @@ impl SymphoniaDecoder {
             buffer,
             spec,
             seek_mode,
+            metadata,
+            metadata_callback: None,
         }))
     }
 
+    /// Checks the format reader for metadata revisions published since the last packet.
+    fn poll_metadata(&mut self) {
+        let mut log = self.format.metadata();
+        if log.is_latest() {
+            return;
+        }
+        let Some(revision) = log.skip_to_latest() else {
+            return;
+        };
+        let update = AudioMetadata::from_revision(revision);
+        self.metadata.merge(update.clone());
+        if let Some(callback) = &self.metadata_callback {
+            callback(update);
+        }
+    }
+
     #[inline]
     fn get_buffer(decoded: AudioBufferRef, spec: &SignalSpec) -> SampleBuffer<Sample> {
         let duration = units::Duration::from(decoded.capacity() as u64);
@@ -278,6 +318,7 @@ impl Iterator for SymphoniaDecoder {
         if self.current_span_offset >= self.buffer.len() {
             let decoded = loop {
                 let packet = self.format.next_packet().ok()?;
+                self.poll_metadata();
                 let decoded = match self.decoder.decode(&packet) {
                     Ok(decoded) => decoded,
                     Err(Error::DecodeError(_)) => {
@@ -0,0 +1,128 @@
+//! 错误类型
+
+use rodio::decoder::DecoderError;
+use rodio::source::SeekError;
+use rodio::StreamError;
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+
+/// 播放器、加载器及离线处理的错误
+///
+/// 播放过程中的错误同样通过 [`PlayerEvent::Error`](crate::PlayerEvent::Error) 发送，
+/// 可按种类分别处理（如提示“不支持的格式”或“网络不可用”）
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// 读写文件等 I/O 错误
+    Io(Arc<io::Error>),
+    /// 服务器返回了表示失败的 HTTP 状态码
+    HttpStatus(u16),
+    /// 网络连接失败、超时或中断
+    Network(String),
+    /// 不支持的音频格式或编码（包括无效的播放列表及加密的 HLS 分片）
+    UnsupportedFormat(String),
+    /// 音频数据损坏，解码失败
+    Decode(String),
+    /// 跳转失败（如音频源不支持跳转）
+    Seek(String),
+    /// 打开音频输出设备失败或设备已断开
+    Device(String),
+    /// 操作被取消（如加载期间中断了下载）
+    Cancelled,
+    /// 参数或状态无效（如队列中没有下一项）
+    InvalidInput(String),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    /// 音频输出设备的错误
+    pub(crate) fn device(error: impl fmt::Display) -> Self {
+        Error::Device(error.to_string())
+    }
+
+    pub(crate) fn invalid_input(message: impl Into<String>) -> Self {
+        Error::InvalidInput(message.into())
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::HttpStatus(status) => write!(f, "HTTP status {}", status),
+            Error::Network(message) => write!(f, "Network error: {}", message),
+            Error::UnsupportedFormat(message) => write!(f, "Unsupported format: {}", message),
+            Error::Decode(message) => write!(f, "Decode error: {}", message),
+            Error::Seek(message) => write!(f, "Seek error: {}", message),
+            Error::Device(message) => write!(f, "Audio device error: {}", message),
+            Error::Cancelled => write!(f, "Operation cancelled"),
+            Error::InvalidInput(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Error::Io(a), Error::Io(b)) => a.kind() == b.kind() && a.to_string() == b.to_string(),
+            (Error::HttpStatus(a), Error::HttpStatus(b)) => a == b,
+            (Error::Network(a), Error::Network(b))
+            | (Error::UnsupportedFormat(a), Error::UnsupportedFormat(b))
+            | (Error::Decode(a), Error::Decode(b))
+            | (Error::Seek(a), Error::Seek(b))
+            | (Error::Device(a), Error::Device(b))
+            | (Error::InvalidInput(a), Error::InvalidInput(b)) => a == b,
+            (Error::Cancelled, Error::Cancelled) => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(Arc::new(e))
+    }
+}
+
+impl From<DecoderError> for Error {
+    fn from(e: DecoderError) -> Self {
+        match e {
+            DecoderError::UnrecognizedFormat | DecoderError::NoStreams => {
+                Error::UnsupportedFormat(e.to_string())
+            }
+            DecoderError::IoError(message) => Error::Io(Arc::new(io::Error::other(message))),
+            _ => Error::Decode(e.to_string()),
+        }
+    }
+}
+
+impl From<SeekError> for Error {
+    fn from(e: SeekError) -> Self {
+        Error::Seek(e.to_string())
+    }
+}
+
+impl From<StreamError> for Error {
+    fn from(e: StreamError) -> Self {
+        Error::device(e)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => Error::HttpStatus(status.as_u16()),
+            None => Error::Network(e.to_string()),
+        }
+    }
+}
@@ -1,3 +1,6 @@
+use crate::error::Error;
+use crate::metadata::AudioMetadata;
+
 /// 播放器事件类型
 #[derive(Debug, Clone, PartialEq)]
 pub enum PlayerEvent {
@@ -17,6 +20,8 @@ pub enum PlayerEvent {
     DurationChange,
     /// 音量变化（对应 volumechange 事件）
     VolumeChange,
+    /// 播放速率变化（对应 ratechange 事件）
+    RateChange,
     /// Seek 操作开始（对应 seeking 事件）
     Seeking,
     /// Seek 操作完成（对应 seeked 事件）
@@ -27,6 +32,12 @@ pub enum PlayerEvent {
     LoadedData,
     /// 元数据加载完成（对应 loadedmetadata 事件）
     LoadedMetadata,
-    /// 错误发生（对应 error 事件）
-    Error { message: String },
+    /// 播放队列中的当前项变化，携带当前项在队列中的索引
+    TrackChange { index: usize },
+    /// 播放过程中元数据变化（如网络电台切换曲目），携带更新后的元数据
+    MetadataChange { metadata: Box<AudioMetadata> },
+    /// 音频输出设备切换，携带新设备的名称（空输出或自定义输出流时为 `None`）
+    DeviceChange { name: Option<String> },
+    /// 错误发生（对应 error 事件），携带错误的种类及详情
+    Error { error: Error },
 }
@@ -1,9 +1,16 @@
 // Remu Playback Library
 
 pub mod decoder;
+pub mod error;
 pub mod events;
 pub mod loader;
+pub mod loudness;
+pub mod metadata;
 pub mod player;
 pub mod reader;
+pub mod render;
+pub mod waveform;
 
+pub use error::{Error, Result};
 pub use events::PlayerEvent;
+pub use metadata::AudioMetadata;
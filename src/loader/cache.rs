@@ -0,0 +1,363 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 磁盘缓存
+///
+/// 以 URL 为键缓存下载的音频数据，记录 ETag/Last-Modified 以便校验缓存是否过期。
+/// 跳转后下载的不连续数据同样写入缓存，并在元数据中记录已缓存的区间。
+/// 缓存总大小超过上限时，按最近访问时间淘汰最久未使用的条目。
+#[derive(Debug, Clone)]
+pub struct DiskCache {
+    inner: Arc<CacheInner>,
+}
+
+#[derive(Debug)]
+struct CacheInner {
+    /// 缓存目录
+    dir: PathBuf,
+    /// 缓存大小上限（字节）
+    max_bytes: u64,
+    /// 缓存目录的操作锁
+    lock: Mutex<()>,
+}
+
+/// 缓存条目
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheEntry {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_type: Option<String>,
+    /// 数据总长度
+    pub total_len: Option<u64>,
+    /// 从开头连续缓存的长度，继续下载时从此处开始
+    pub len: u64,
+    /// 已缓存的区间，按起始位置排列且互不相邻
+    pub ranges: Vec<Range<u64>>,
+    /// 是否已完整缓存
+    pub complete: bool,
+    /// 最近访问时间（UNIX 时间戳，毫秒）
+    pub accessed: u64,
+}
+
+impl CacheEntry {
+    /// 用于 `If-Range` 的校验值，优先使用 ETag
+    pub fn validator(&self) -> Option<&str> {
+        self.etag.as_deref().or(self.last_modified.as_deref())
+    }
+
+    fn to_meta(&self) -> String {
+        let mut meta = format!("url={}\n", self.url);
+        if let Some(ref etag) = self.etag {
+            meta += &format!("etag={}\n", etag);
+        }
+        if let Some(ref last_modified) = self.last_modified {
+            meta += &format!("last_modified={}\n", last_modified);
+        }
+        if let Some(ref content_type) = self.content_type {
+            meta += &format!("content_type={}\n", content_type);
+        }
+        if let Some(total_len) = self.total_len {
+            meta += &format!("total_len={}\n", total_len);
+        }
+        let ranges: Vec<String> = (self.ranges.iter())
+            .map(|range| format!("{}-{}", range.start, range.end))
+            .collect();
+        meta += &format!("ranges={}\n", ranges.join(","));
+        meta += &format!("complete={}\n", self.complete as u8);
+        meta += &format!("accessed={}\n", self.accessed);
+        meta
+    }
+
+    /// 解析元数据，`data_len` 为数据文件的长度
+    fn from_meta(text: &str, data_len: u64) -> Self {
+        let mut entry = Self::default();
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+            let value = value.to_string();
+            match key {
+                "url" => entry.url = value,
+                "etag" => entry.etag = Some(value),
+                "last_modified" => entry.last_modified = Some(value),
+                "content_type" => entry.content_type = Some(value),
+                "total_len" => entry.total_len = value.parse().ok(),
+                "ranges" => {
+                    ranges = (value.split(','))
+                        .filter_map(|range| {
+                            let (start, end) = range.split_once('-')?;
+                            Some(start.parse().ok()?..end.parse().ok()?)
+                        })
+                        .collect()
+                }
+                "complete" => entry.complete = value == "1",
+                "accessed" => entry.accessed = value.parse().unwrap_or(0),
+                _ => {}
+            }
+        }
+        for range in ranges {
+            insert_range(&mut entry.ranges, range.start..range.end.min(data_len));
+        }
+        entry.len = prefix_len(&entry.ranges);
+        entry
+    }
+}
+
+impl DiskCache {
+    /// 创建磁盘缓存
+    ///
+    /// # 参数
+    /// * `dir` - 缓存目录，不存在时自动创建
+    /// * `max_bytes` - 缓存大小上限（字节）
+    pub fn new(dir: impl Into<PathBuf>, max_bytes: u64) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            inner: Arc::new(CacheInner {
+                dir,
+                max_bytes,
+                lock: Mutex::new(()),
+            }),
+        })
+    }
+
+    /// 获取缓存目录
+    pub fn dir(&self) -> &Path {
+        &self.inner.dir
+    }
+
+    /// 查找缓存条目，并更新其访问时间
+    pub fn lookup(&self, url: &str) -> Option<CacheEntry> {
+        let _lock = self.inner.lock.lock().unwrap();
+        let key = cache_key(url);
+        let mut entry = self.read_entry(&key)?;
+        if entry.url != url {
+            return None;
+        }
+        entry.accessed = now();
+        let _ = fs::write(self.meta_path(&key), entry.to_meta());
+        Some(entry)
+    }
+
+    /// 读取已缓存的数据
+    pub fn read(&self, url: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.data_path(&cache_key(url)))
+    }
+
+    /// 打开已缓存的数据文件，用于分块读取
+    pub fn open(&self, url: &str) -> io::Result<File> {
+        File::open(self.data_path(&cache_key(url)))
+    }
+
+    /// 删除缓存条目
+    pub fn remove(&self, url: &str) {
+        let _lock = self.inner.lock.lock().unwrap();
+        self.remove_key(&cache_key(url));
+    }
+
+    /// 清空缓存
+    pub fn clear(&self) {
+        let _lock = self.inner.lock.lock().unwrap();
+        for key in self.keys() {
+            self.remove_key(&key);
+        }
+    }
+
+    /// 获取缓存的总大小
+    pub fn size(&self) -> u64 {
+        let _lock = self.inner.lock.lock().unwrap();
+        self.keys()
+            .iter()
+            .map(|key| file_len(&self.data_path(key)))
+            .sum()
+    }
+
+    /// 创建缓存写入器
+    ///
+    /// `resume` 为 `true` 时保留 `entry` 中已缓存的区间，否则覆盖原有数据
+    pub fn writer(&self, mut entry: CacheEntry, resume: bool) -> io::Result<CacheWriter> {
+        let _lock = self.inner.lock.lock().unwrap();
+        let key = cache_key(&entry.url);
+        let data_path = self.data_path(&key);
+        let file = if resume {
+            OpenOptions::new().write(true).open(&data_path)?
+        } else {
+            entry.ranges.clear();
+            File::create(&data_path)?
+        };
+        let data_len = file_len(&data_path);
+        let ranges = std::mem::take(&mut entry.ranges);
+        for range in ranges {
+            insert_range(&mut entry.ranges, range.start..range.end.min(data_len));
+        }
+        entry.len = prefix_len(&entry.ranges);
+        entry.complete = false;
+        entry.accessed = now();
+        fs::write(self.meta_path(&key), entry.to_meta())?;
+        self.evict(&key);
+
+        Ok(CacheWriter {
+            cache: self.clone(),
+            key,
+            file: Some(file),
+            entry,
+        })
+    }
+
+    /// 淘汰最久未使用的条目，直到缓存大小不超过上限
+    fn evict(&self, keep: &str) {
+        let mut entries: Vec<(String, u64, u64)> = self
+            .keys()
+            .into_iter()
+            .map(|key| {
+                let accessed = self.read_entry(&key).map_or(0, |e| e.accessed);
+                let len = file_len(&self.data_path(&key));
+                (key, accessed, len)
+            })
+            .collect();
+        let mut total: u64 = entries.iter().map(|(_, _, len)| len).sum();
+        entries.sort_by_key(|(_, accessed, _)| *accessed);
+
+        for (key, _, len) in entries {
+            if total <= self.inner.max_bytes {
+                break;
+            }
+            if key == keep {
+                continue;
+            }
+            self.remove_key(&key);
+            total -= len;
+        }
+    }
+
+    fn read_entry(&self, key: &str) -> Option<CacheEntry> {
+        let text = fs::read_to_string(self.meta_path(key)).ok()?;
+        Some(CacheEntry::from_meta(&text, file_len(&self.data_path(key))))
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let Ok(dir) = fs::read_dir(&self.inner.dir) else {
+            return Vec::new();
+        };
+        dir.filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().into_string().ok()?;
+                name.strip_suffix(".meta").map(str::to_string)
+            })
+            .collect()
+    }
+
+    fn remove_key(&self, key: &str) {
+        let _ = fs::remove_file(self.data_path(key));
+        let _ = fs::remove_file(self.meta_path(key));
+    }
+
+    fn data_path(&self, key: &str) -> PathBuf {
+        self.inner.dir.join(format!("{}.data", key))
+    }
+
+    fn meta_path(&self, key: &str) -> PathBuf {
+        self.inner.dir.join(format!("{}.meta", key))
+    }
+}
+
+/// 缓存写入器，随下载写入数据
+///
+/// 释放时保存已缓存的区间，中断的下载可在下次加载时继续
+pub struct CacheWriter {
+    cache: DiskCache,
+    key: String,
+    /// 写入失败或已保存后为 `None`，不再写入
+    file: Option<File>,
+    entry: CacheEntry,
+}
+
+impl CacheWriter {
+    /// 写入从 `offset` 开始的数据
+    pub fn write_at(&mut self, offset: u64, data: &[u8]) {
+        let Some(ref mut file) = self.file else {
+            return;
+        };
+        if data.is_empty() {
+            return;
+        }
+        let result = file
+            .seek(SeekFrom::Start(offset))
+            .and_then(|_| file.write_all(data));
+        if result.is_err() {
+            self.file = None;
+            return;
+        }
+        insert_range(&mut self.entry.ranges, offset..offset + data.len() as u64);
+        self.entry.len = prefix_len(&self.entry.ranges);
+    }
+
+    /// 完成写入，数据完整时标记为已完整缓存
+    pub fn finish(mut self) {
+        self.save();
+    }
+
+    fn save(&mut self) {
+        let _lock = self.cache.inner.lock.lock().unwrap();
+        if self.file.take().is_none() {
+            return;
+        }
+        self.entry.complete = self
+            .entry
+            .total_len
+            .is_some_and(|total_len| self.entry.len >= total_len);
+        let _ = fs::write(self.cache.meta_path(&self.key), self.entry.to_meta());
+        self.cache.evict(&self.key);
+    }
+}
+
+impl Drop for CacheWriter {
+    fn drop(&mut self) {
+        self.save();
+    }
+}
+
+/// 将区间并入按起始位置排列的区间列表，合并重叠及相邻的区间
+fn insert_range(ranges: &mut Vec<Range<u64>>, mut range: Range<u64>) {
+    if range.start >= range.end {
+        return;
+    }
+    let first = ranges.partition_point(|r| r.end < range.start);
+    let last = ranges.partition_point(|r| r.start <= range.end);
+    if first < last {
+        range.start = range.start.min(ranges[first].start);
+        range.end = range.end.max(ranges[last - 1].end);
+    }
+    ranges.splice(first..last, [range]);
+}
+
+/// 从开头连续的区间长度
+fn prefix_len(ranges: &[Range<u64>]) -> u64 {
+    ranges.first().filter(|r| r.start == 0).map_or(0, |r| r.end)
+}
+
+/// 计算 URL 对应的缓存键（FNV-1a 64位哈希）
+fn cache_key(url: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in url.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn file_len(path: &Path) -> u64 {
+    fs::metadata(path).map_or(0, |m| m.len())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_millis() as u64)
+}
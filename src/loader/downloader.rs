@@ -1,12 +1,30 @@
+use std::io;
+use std::ops::Range;
 use std::sync::Condvar;
 use std::sync::{
     atomic::{AtomicBool, AtomicU64, Ordering},
     Arc, Mutex,
 };
-
-use crate::loader::LoaderEvent;
+use std::time::{Duration, Instant};
+
+use crate::error::{Error, Result};
+use crate::loader::cache::{CacheEntry, DiskCache};
+use crate::loader::hls;
+use crate::loader::icy::IcyStripper;
+use crate::loader::{
+    DataWrapper, DownloadHandle, LoaderCallback, LoaderEvent, LoaderOptions, RetryPolicy,
+};
+use crate::metadata::AudioMetadata;
 use crate::reader::AppendableDataWrapper;
 
+/// 下载进度事件的最小触发间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 每次从缓存文件读取的字节数
+const CACHE_READ_SIZE: usize = 64 * 1024;
+
+type MetadataCallback = Arc<Mutex<Option<Box<dyn Fn(AudioMetadata) + Send + 'static>>>>;
+
 /// 下载状态枚举
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DownloadStatus {
@@ -20,10 +38,28 @@ pub enum DownloadStatus {
     Aborted,
 }
 
+/// 地址路径中文件名的扩展名，可作为解码器的格式提示
+pub fn url_extension(url: &str) -> Option<String> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let name = url.path_segments()?.next_back()?;
+    let (_, extension) = name.rsplit_once('.')?;
+    (!extension.is_empty()).then(|| extension.to_ascii_lowercase())
+}
+
+/// 当前请求结束后的下一步
+enum NextRequest {
+    /// 下载完成
+    Finished,
+    /// 从指定位置发起Range请求
+    Seek(u64),
+    /// 连接中断，从中断的位置重新连接
+    Reconnect(Error),
+}
+
 /// 下载器结构体
 pub struct Downloader {
     /// 下载的数据
-    data: Arc<Mutex<Box<dyn AppendableDataWrapper + Send + 'static>>>,
+    data: DataWrapper,
     /// 条件变量(每获取一次数据触发一次)
     condvar: Arc<Condvar>,
     /// 下载状态
@@ -38,10 +74,22 @@ pub struct Downloader {
     should_abort: Arc<AtomicBool>,
     /// 下载是否已完成（用于通知Reader停止等待）
     download_completed: Arc<AtomicBool>,
+    /// 服务器是否支持Range请求
+    seekable: Arc<AtomicBool>,
+    /// 响应的 Content-Type
+    content_type: Arc<Mutex<Option<String>>>,
+    /// 磁盘缓存
+    cache: Arc<Mutex<Option<DiskCache>>>,
+    /// 连接中断时的重试策略
+    retry_policy: Arc<Mutex<RetryPolicy>>,
+    /// 请求选项
+    options: Arc<Mutex<LoaderOptions>>,
     /// 下载线程句柄
-    thread_handle: Arc<Mutex<Option<tokio::task::JoinHandle<Result<(), ()>>>>>,
+    thread_handle: DownloadHandle,
     /// 回调函数
-    callback: Arc<Mutex<Option<Box<dyn Fn(LoaderEvent) + Send + 'static>>>>,
+    callback: LoaderCallback,
+    /// 流内元数据回调函数
+    metadata_callback: MetadataCallback,
 }
 
 impl Downloader {
@@ -56,8 +104,14 @@ impl Downloader {
             download_called: Arc::new(AtomicBool::new(false)),
             should_abort: Arc::new(AtomicBool::new(false)),
             download_completed: Arc::new(AtomicBool::new(false)),
+            seekable: Arc::new(AtomicBool::new(false)),
+            content_type: Arc::new(Mutex::new(None)),
+            cache: Arc::new(Mutex::new(None)),
+            retry_policy: Arc::new(Mutex::new(RetryPolicy::default())),
+            options: Arc::new(Mutex::new(LoaderOptions::default())),
             thread_handle: Arc::new(Mutex::new(None)),
             callback: Arc::new(Mutex::new(None)),
+            metadata_callback: Arc::new(Mutex::new(None)),
         }
     }
 
@@ -76,8 +130,13 @@ impl Downloader {
         self.downloaded_bytes.load(Ordering::Relaxed)
     }
 
+    /// 获取可供读取的字节范围
+    pub fn buffered(&self) -> Vec<Range<u64>> {
+        self.data.lock().unwrap().buffered()
+    }
+
     /// 获取下载数据的引用
-    pub fn data(&self) -> Arc<Mutex<Box<dyn AppendableDataWrapper + Send + 'static>>> {
+    pub fn data(&self) -> DataWrapper {
         Arc::clone(&self.data)
     }
 
@@ -91,6 +150,41 @@ impl Downloader {
         Arc::clone(&self.download_completed)
     }
 
+    /// 服务器是否支持Range请求（支持时可跳转到未下载的位置）
+    pub fn seekable(&self) -> bool {
+        self.seekable.load(Ordering::Relaxed)
+    }
+
+    /// 获取响应的 Content-Type
+    pub fn content_type(&self) -> Option<String> {
+        self.content_type.lock().unwrap().clone()
+    }
+
+    /// 获取响应的 MIME 类型（去除 Content-Type 的参数部分），可作为解码器的格式提示
+    pub fn mime_type(&self) -> Option<String> {
+        let content_type = self.content_type()?;
+        let mime = content_type.split(';').next().unwrap_or("").trim();
+        (!mime.is_empty()).then(|| mime.to_ascii_lowercase())
+    }
+
+    /// 设置磁盘缓存
+    ///
+    /// 设置后，已完整缓存的音频直接从缓存读取（并在后台校验是否过期），
+    /// 部分缓存的音频通过Range请求继续下载，下载的数据同时写入缓存
+    pub fn set_cache(&self, cache: DiskCache) {
+        *self.cache.lock().unwrap() = Some(cache);
+    }
+
+    /// 设置连接中断时的重试策略，需在 `download` 前调用
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry_policy.lock().unwrap() = policy;
+    }
+
+    /// 设置请求选项（请求头、认证、代理、超时等），需在 `download` 前调用
+    pub fn set_options(&self, options: LoaderOptions) {
+        *self.options.lock().unwrap() = options;
+    }
+
     /// 设置消息回调函数
     ///
     /// # 参数
@@ -106,28 +200,43 @@ impl Downloader {
         *cb = Some(Box::new(callback));
     }
 
+    /// 设置流内元数据回调函数
+    ///
+    /// 当网络电台等音频流通过 ICY 协议更新 StreamTitle 时触发
+    ///
+    /// # 注意
+    /// 多次调用会替换之前设置的回调函数
+    pub fn set_metadata_callback<F>(&self, callback: F)
+    where
+        F: Fn(AudioMetadata) + Send + 'static,
+    {
+        let mut cb = self.metadata_callback.lock().unwrap();
+        *cb = Some(Box::new(callback));
+    }
+
     /// 开始下载
     ///
     /// # 参数
     /// * `url` - 下载地址
-    /// * `headers` - 可选的HTTP请求头
+    /// * `headers` - 可选的HTTP请求头，附加在 [`set_options`](Self::set_options) 设置的请求头之后
     ///
     /// # 返回
     /// * `Ok(())` - 下载请求成功，获取到数据
-    /// * `Err(())` - 下载请求失败
+    /// * `Err(Error)` - 下载请求失败，服务器返回非 2xx 状态码时为 [`Error::HttpStatus`]
     ///
     /// # Panics
     /// 如果多次调用此方法会触发panic
-    pub async fn download(
-        &self,
-        url: &str,
-        headers: Option<Vec<(String, String)>>,
-    ) -> Result<(), ()> {
+    pub async fn download(&self, url: &str, headers: Option<Vec<(String, String)>>) -> Result<()> {
         // 检查是否已经调用过download
         if self.download_called.swap(true, Ordering::SeqCst) {
             panic!("download() can only be called once");
         }
 
+        // 构建HTTP客户端，只限制连接及读取空闲的时间，不限制长音频的下载时长
+        let mut options = self.options.lock().unwrap().clone();
+        options.headers.extend(headers.unwrap_or_default());
+        let client = options.client()?;
+
         // 更新状态为下载中
         {
             let mut status = self.status.lock().unwrap();
@@ -143,35 +252,104 @@ impl Downloader {
         let should_abort = Arc::clone(&self.should_abort);
         let download_completed = Arc::clone(&self.download_completed);
         let callback = Arc::clone(&self.callback);
+        let metadata_callback = Arc::clone(&self.metadata_callback);
+
+        // 读取缓存
+        let cache = self.cache.lock().unwrap().clone();
+        let cached = cache.as_ref().and_then(|cache| cache.lookup(url));
+        if let (Some(cache), Some(entry)) = (cache.as_ref(), cached.as_ref()) {
+            if entry.complete {
+                if let Ok(file) = cache.open(url) {
+                    if let Err(e) = self.load_cached(entry, file).await {
+                        // 缓存数据损坏，删除后下次加载时重新下载
+                        cache.remove(url);
+                        let error = Error::from(e);
+                        *status.lock().unwrap() = DownloadStatus::Aborted;
+                        if let Some(ref cb) = *callback.lock().unwrap() {
+                            cb(LoaderEvent::Aborted {
+                                error: error.clone(),
+                            });
+                        }
+                        return Err(error);
+                    }
+
+                    // 在后台校验缓存是否过期，校验值变化时删除缓存，下次加载时重新下载
+                    if entry.validator().is_some() {
+                        let request_builder = options.apply(client.head(url));
+                        let cache = cache.clone();
+                        let url = url.to_string();
+                        let entry = entry.clone();
+                        tokio::task::spawn(async move {
+                            let Ok(resp) = request_builder.send().await else {
+                                return;
+                            };
+                            if !resp.status().is_success() {
+                                return;
+                            }
+                            let header = |name: reqwest::header::HeaderName| {
+                                resp.headers().get(name).and_then(|v| v.to_str().ok())
+                            };
+                            let changed = match (&entry.etag, header(reqwest::header::ETAG)) {
+                                (Some(old), Some(new)) => old != new,
+                                _ => matches!(
+                                    (&entry.last_modified, header(reqwest::header::LAST_MODIFIED)),
+                                    (Some(old), Some(new)) if old != new
+                                ),
+                            };
+                            if changed {
+                                cache.remove(&url);
+                            }
+                        });
+                    }
+                    return Ok(());
+                }
+            }
+        }
 
         use futures_util::StreamExt;
 
-        // 构建HTTP客户端和请求
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()
-            .unwrap();
+        // 构建请求，`start` 为Range请求的起始位置
+        let request_url = url.to_string();
+        let build_request = move |start: Option<u64>| {
+            let mut request_builder = client.get(&request_url);
+            request_builder = match start {
+                Some(start) => {
+                    request_builder.header(reqwest::header::RANGE, format!("bytes={}-", start))
+                }
+                // 请求 ICY 元数据，以便获取网络电台的曲目信息
+                None => request_builder.header("Icy-MetaData", "1"),
+            };
 
-        let mut request_builder = client.get(url);
+            // 添加自定义headers及认证
+            options.apply(request_builder)
+        };
 
-        // 添加自定义headers
-        if let Some(hdrs) = headers {
-            for (key, value) in hdrs {
-                request_builder = request_builder.header(key, value);
-            }
+        // 部分缓存的音频从已缓存的位置继续下载，内容已变化时服务器返回完整内容
+        let resume = cached.filter(|e| {
+            !e.complete && e.len > 0 && e.total_len.is_some() && e.validator().is_some()
+        });
+        let mut request_builder = build_request(resume.as_ref().map(|e| e.len));
+        if let Some(validator) = resume.as_ref().and_then(|e| e.validator()) {
+            request_builder = request_builder.header(reqwest::header::IF_RANGE, validator);
         }
 
         // 发送请求
+        // 错误页面（如 404 的 HTML）不是音频数据，不写入缓冲区
         let response = match request_builder.send().await {
-            Ok(resp) => resp,
-            Err(e) => {
-                eprintln!("Failed to send request: {}", e);
+            Ok(resp) if resp.status().is_success() => resp,
+            result => {
+                let error = match result {
+                    Ok(resp) => Error::HttpStatus(resp.status().as_u16()),
+                    Err(e) => e.into(),
+                };
                 let mut s = status.lock().unwrap();
                 *s = DownloadStatus::Aborted;
                 if let Some(ref cb) = *callback.lock().unwrap() {
-                    cb(LoaderEvent::Aborted);
+                    cb(LoaderEvent::Aborted {
+                        error: error.clone(),
+                    });
                 }
-                return Err(());
+                return Err(error);
             }
         };
 
@@ -183,52 +361,300 @@ impl Downloader {
             .and_then(|s| s.parse::<u64>().ok())
             .unwrap_or(0);
 
+        let resumed = resume.filter(|_| response.status() == reqwest::StatusCode::PARTIAL_CONTENT);
+        let start = resumed.as_ref().map_or(0, |e| e.len);
+        let content_length = match resumed {
+            Some(ref entry) => entry.total_len.unwrap_or(start + content_length),
+            None => content_length,
+        };
+
         total_bytes.store(content_length, Ordering::Relaxed);
 
+        let header = |name: reqwest::header::HeaderName| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let content_type = header(reqwest::header::CONTENT_TYPE)
+            .or_else(|| resumed.as_ref().and_then(|e| e.content_type.clone()));
+        *self.content_type.lock().unwrap() = content_type.clone();
+
+        // 若服务器返回了 icy-metaint，则需要从数据流中剥离元数据块
+        let mut icy = IcyStripper::from_headers(response.headers());
+
+        // 服务器支持Range请求时，Reader可跳转到尚未下载的位置
+        let accept_ranges = response
+            .headers()
+            .get(reqwest::header::ACCEPT_RANGES)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.trim().eq_ignore_ascii_case("bytes"))
+            || resumed.is_some();
+
         // 设置数据容量，以防内存重新分配导致卡顿
-        data.lock().unwrap().set_capacity(content_length as usize);
+        let mut range_request = None;
+        {
+            let mut data = data.lock().unwrap();
+            data.set_capacity(content_length as usize);
+            if content_length > 0 && icy.is_none() {
+                data.set_total_len(content_length);
+                if accept_ranges {
+                    range_request = data.enable_ranges();
+                }
+            }
+        }
+        self.seekable
+            .store(range_request.is_some(), Ordering::Relaxed);
+
+        // 写入已缓存的部分
+        if let (Some(cache), Some(_)) = (cache.as_ref(), resumed.as_ref()) {
+            let result = match cache.open(url) {
+                Ok(file) => self.append_cached(file, start).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                let error = Error::from(e);
+                let mut s = status.lock().unwrap();
+                *s = DownloadStatus::Aborted;
+                if let Some(ref cb) = *callback.lock().unwrap() {
+                    cb(LoaderEvent::Aborted {
+                        error: error.clone(),
+                    });
+                }
+                return Err(error);
+            }
+        }
+
+        // 长度已知的音频文件随下载写入缓存（不缓存直播流及播放列表）
+        let cacheable = content_length > 0
+            && icy.is_none()
+            && !content_type
+                .as_deref()
+                .is_some_and(hls::is_playlist_content_type);
+        let mut cache_writer = match cache {
+            Some(cache) if cacheable => {
+                let entry = match resumed {
+                    Some(entry) => entry,
+                    None => CacheEntry {
+                        url: url.to_string(),
+                        etag: header(reqwest::header::ETAG),
+                        last_modified: header(reqwest::header::LAST_MODIFIED),
+                        content_type,
+                        total_len: Some(content_length),
+                        ..Default::default()
+                    },
+                };
+                cache.writer(entry, start > 0).ok()
+            }
+            _ => None,
+        };
+
+        // 长度未知或带有ICY元数据的直播流无法从中断的位置继续，重新连接后从最新的数据开始
+        let live = content_length == 0 || icy.is_some();
+        let retry_policy = *self.retry_policy.lock().unwrap();
 
         // 创建流式下载线程
         let handle = tokio::task::spawn(async move {
-            // 使用真正的流式下载
-            let mut stream = response.bytes_stream();
-
-            while let Some(chunk_result) = stream.next().await {
-                // 检查是否需要中断
-                if should_abort.load(Ordering::Relaxed) {
-                    let mut s = status.lock().unwrap();
-                    *s = DownloadStatus::Aborted;
-                    if let Some(ref cb) = *callback.lock().unwrap() {
-                        cb(LoaderEvent::Aborted);
-                    }
-                    return Err(());
+            let mut response = response;
+            let mut icy_audio = Vec::new();
+            // 当前数据在文件中的位置
+            let mut write_offset = start;
+            // 服务器忽略Range请求时，需要跳过的已下载字节数
+            let mut skip: u64 = 0;
+            // 上次触发下载进度事件的时间
+            let mut last_progress = Instant::now();
+            let emit_progress = || {
+                let downloaded = downloaded_bytes.load(Ordering::Relaxed);
+                let total = total_bytes.load(Ordering::Relaxed);
+                // Range请求会重复下载部分数据，已下载字节数不超过总长度
+                let downloaded = if total > 0 {
+                    downloaded.min(total)
+                } else {
+                    downloaded
+                };
+                if let Some(ref cb) = *callback.lock().unwrap() {
+                    cb(LoaderEvent::Progress { downloaded, total });
                 }
+            };
+
+            loop {
+                // 使用真正的流式下载
+                let mut stream = response.bytes_stream();
+
+                let next = loop {
+                    let chunk_result = match range_request {
+                        Some(ref request) => tokio::select! {
+                            biased;
+                            pos = request.wait() => break NextRequest::Seek(pos),
+                            chunk_result = stream.next() => chunk_result,
+                        },
+                        None => stream.next().await,
+                    };
+                    let Some(chunk_result) = chunk_result else {
+                        // 请求结束，继续下载缺失的部分
+                        if range_request.is_some() {
+                            break match data.lock().unwrap().first_missing() {
+                                Some(pos) => NextRequest::Seek(pos),
+                                None => NextRequest::Finished,
+                            };
+                        }
+                        // 数据不完整时视为连接中断
+                        if !live && write_offset < content_length {
+                            break NextRequest::Reconnect(Error::Network(format!(
+                                "Stream ended at {} of {} bytes",
+                                write_offset, content_length
+                            )));
+                        }
+                        break NextRequest::Finished;
+                    };
 
-                match chunk_result {
-                    Ok(chunk) => {
-                        // 将数据追加到data中
-                        let mut data_lock = data.lock().unwrap();
-                        data_lock.append_data(&chunk);
-                        drop(data_lock);
-                        // 获取到数据后，解除Reader对缓冲区数据的等待
-                        condvar.notify_all();
-
-                        // 更新已下载字节数
-                        downloaded_bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
-                    }
-                    Err(e) => {
-                        eprintln!("Error reading chunk: {}", e);
+                    // 检查是否需要中断
+                    if should_abort.load(Ordering::Relaxed) {
                         let mut s = status.lock().unwrap();
                         *s = DownloadStatus::Aborted;
                         if let Some(ref cb) = *callback.lock().unwrap() {
-                            cb(LoaderEvent::Aborted);
+                            cb(LoaderEvent::Aborted {
+                                error: Error::Cancelled,
+                            });
+                        }
+                        return Err(Error::Cancelled);
+                    }
+
+                    match chunk_result {
+                        Ok(mut chunk) => {
+                            // 跳过已下载的部分
+                            if skip > 0 {
+                                let n = skip.min(chunk.len() as u64);
+                                chunk = chunk.slice(n as usize..);
+                                skip -= n;
+                            }
+
+                            // 剥离ICY元数据块，仅保留音频数据
+                            let chunk: &[u8] = match icy.as_mut() {
+                                Some(icy) => {
+                                    icy_audio.clear();
+                                    if let Some(metadata) = icy.process(&chunk, &mut icy_audio) {
+                                        if let Some(ref cb) = *metadata_callback.lock().unwrap() {
+                                            cb(metadata);
+                                        }
+                                    }
+                                    &icy_audio
+                                }
+                                None => &chunk,
+                            };
+
+                            // 将数据追加到data中
+                            let mut data_lock = data.lock().unwrap();
+                            data_lock.append_data(chunk);
+                            drop(data_lock);
+                            // 获取到数据后，解除Reader对缓冲区数据的等待
+                            condvar.notify_all();
+
+                            // 写入缓存
+                            if let Some(ref mut writer) = cache_writer {
+                                writer.write_at(write_offset, chunk);
+                            }
+                            write_offset += chunk.len() as u64;
+
+                            // 更新已下载字节数
+                            downloaded_bytes.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+                            if last_progress.elapsed() >= PROGRESS_INTERVAL {
+                                last_progress = Instant::now();
+                                emit_progress();
+                            }
+
+                            // 已下载到存在数据的位置，转而下载缺失的部分
+                            if range_request.is_some() {
+                                let data = data.lock().unwrap();
+                                if data.write_pos_filled() {
+                                    break match data.first_missing() {
+                                        Some(pos) => NextRequest::Seek(pos),
+                                        None => NextRequest::Finished,
+                                    };
+                                }
+                            }
+                        }
+                        Err(e) => break NextRequest::Reconnect(e.into()),
+                    }
+                };
+
+                let (pos, mut attempt, mut last_error) = match next {
+                    NextRequest::Finished => break,
+                    NextRequest::Seek(pos) => (pos, 0, None),
+                    NextRequest::Reconnect(error) => (write_offset, 1, Some(error)),
+                };
+                let reconnecting = attempt > 0;
+
+                // 从指定位置发起Range请求，直播流重新请求最新的数据
+                let start = if live {
+                    None
+                } else {
+                    let start = data.lock().unwrap().seek_write(pos);
+                    write_offset = start;
+                    Some(start)
+                };
+
+                response = loop {
+                    if attempt > 0 {
+                        if attempt > retry_policy.max_attempts {
+                            // 重试均失败，解除Reader的等待以结束播放
+                            data.lock().unwrap().complete();
+                            download_completed.store(true, Ordering::Release);
+                            condvar.notify_all();
+
+                            let error = last_error.unwrap_or_else(|| {
+                                Error::Network("Connection interrupted".to_string())
+                            });
+                            let mut s = status.lock().unwrap();
+                            *s = DownloadStatus::Aborted;
+                            if let Some(ref cb) = *callback.lock().unwrap() {
+                                cb(LoaderEvent::Aborted {
+                                    error: error.clone(),
+                                });
+                            }
+                            return Err(error);
+                        }
+                        if let Some(ref cb) = *callback.lock().unwrap() {
+                            cb(LoaderEvent::Retrying { attempt });
                         }
-                        return Err(());
+                        tokio::time::sleep(retry_policy.delay(attempt)).await;
+                    }
+                    match build_request(start).send().await {
+                        Ok(resp) if resp.status().is_success() => break resp,
+                        Ok(resp) => last_error = Some(Error::HttpStatus(resp.status().as_u16())),
+                        Err(e) => last_error = Some(e.into()),
+                    }
+                    attempt += 1;
+                };
+
+                if live {
+                    icy = IcyStripper::from_headers(response.headers());
+                } else if response.status() != reqwest::StatusCode::PARTIAL_CONTENT
+                    && write_offset > 0
+                {
+                    if range_request.is_some() {
+                        // 服务器忽略了Range请求，从头开始线性下载
+                        range_request = None;
+                        data.lock().unwrap().seek_write(0);
+                        write_offset = 0;
+                    } else {
+                        // 服务器忽略了Range请求，跳过已下载的部分
+                        skip = write_offset;
+                    }
+                }
+
+                if reconnecting {
+                    if let Some(ref cb) = *callback.lock().unwrap() {
+                        cb(LoaderEvent::Resumed);
                     }
                 }
             }
 
             data.lock().unwrap().complete();
+            if let Some(writer) = cache_writer {
+                writer.finish();
+            }
 
             // 下载完成
             let mut s = status.lock().unwrap();
@@ -238,10 +664,11 @@ impl Downloader {
             download_completed.store(true, Ordering::Release);
             condvar.notify_all();
 
+            emit_progress();
             if let Some(ref cb) = *callback.lock().unwrap() {
                 cb(LoaderEvent::Completed);
             }
-            return Ok(());
+            Ok(())
         });
 
         let mut th = self.thread_handle.lock().unwrap();
@@ -249,11 +676,66 @@ impl Downloader {
         Ok(())
     }
 
+    /// 从缓存中加载完整的数据
+    async fn load_cached(&self, entry: &CacheEntry, file: std::fs::File) -> io::Result<()> {
+        let len = entry.len;
+        self.total_bytes.store(len, Ordering::Relaxed);
+        *self.content_type.lock().unwrap() = entry.content_type.clone();
+        {
+            let mut data = self.data.lock().unwrap();
+            data.set_capacity(len as usize);
+            data.set_total_len(len);
+        }
+        self.append_cached(file, len).await?;
+        self.data.lock().unwrap().complete();
+        // 数据已完整，可任意跳转
+        self.seekable.store(true, Ordering::Relaxed);
+
+        *self.status.lock().unwrap() = DownloadStatus::Completed;
+        self.download_completed.store(true, Ordering::Release);
+        self.condvar.notify_all();
+
+        if let Some(ref cb) = *self.callback.lock().unwrap() {
+            cb(LoaderEvent::Progress {
+                downloaded: len,
+                total: len,
+            });
+            cb(LoaderEvent::Completed);
+        }
+        Ok(())
+    }
+
+    /// 分块读取缓存文件开头的 `len` 字节，追加到缓冲区
+    async fn append_cached(&self, file: std::fs::File, len: u64) -> io::Result<()> {
+        use tokio::io::AsyncReadExt;
+
+        let mut file = tokio::fs::File::from_std(file).take(len);
+        let mut buf = vec![0; CACHE_READ_SIZE];
+        let mut read = 0;
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            self.data.lock().unwrap().append_data(&buf[..n]);
+            self.condvar.notify_all();
+            read += n as u64;
+            self.downloaded_bytes.store(read, Ordering::Relaxed);
+        }
+        if read < len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Truncated cached data",
+            ));
+        }
+        Ok(())
+    }
+
     /// 中断当前下载
     pub fn abort(&self) -> Result<(), DownloadStatus> {
         let mut status = self.status.lock().unwrap();
         if *status != DownloadStatus::Downloading {
-            return Err(status.clone());
+            return Err(*status);
         }
         // 设置中断标志
         self.should_abort.store(true, Ordering::SeqCst);
@@ -261,7 +743,7 @@ impl Downloader {
         // 中断下载线程
         let mut th = self.thread_handle.lock().unwrap();
         if let Some(handle) = th.take() {
-            let _ = handle.abort();
+            handle.abort();
         }
         *status = DownloadStatus::Aborted;
         Ok(())
@@ -278,7 +760,7 @@ impl Drop for Downloader {
         // 中断下载线程
         let mut th = self.thread_handle.lock().unwrap();
         if let Some(handle) = th.take() {
-            let _ = handle.abort();
+            handle.abort();
         }
         *status = DownloadStatus::Aborted;
     }
@@ -0,0 +1,484 @@
+use reqwest::Url;
+use std::future::Future;
+use std::sync::Condvar;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::loader::downloader::DownloadStatus;
+use crate::loader::{
+    DataWrapper, DownloadHandle, LoaderCallback, LoaderEvent, LoaderOptions, RetryPolicy,
+};
+use crate::reader::AppendableDataWrapper;
+
+pub mod mpegts;
+pub mod playlist;
+
+use mpegts::TsDemuxer;
+use playlist::Playlist;
+
+/// 直播时从倒数第几个分片开始播放
+const LIVE_START_SEGMENTS: usize = 3;
+
+/// 地址是否指向 HLS 播放列表
+pub fn is_playlist_url(url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    path.to_ascii_lowercase().ends_with(".m3u8")
+}
+
+/// Content-Type 是否为 HLS 播放列表
+pub fn is_playlist_content_type(content_type: &str) -> bool {
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    [
+        "application/vnd.apple.mpegurl",
+        "application/x-mpegurl",
+        "audio/mpegurl",
+        "audio/x-mpegurl",
+    ]
+    .iter()
+    .any(|m| mime.eq_ignore_ascii_case(m))
+}
+
+/// HLS 加载器
+///
+/// 解析主播放列表及媒体播放列表，按码率选择变体流，依次下载分片并写入数据中。
+/// MPEG-TS 分片会被解复用为音频流，直播播放列表会定期刷新。
+pub struct HlsLoader {
+    /// 下载的数据
+    data: DataWrapper,
+    /// 条件变量(每获取一次数据触发一次)
+    condvar: Arc<Condvar>,
+    /// 下载状态
+    status: Arc<Mutex<DownloadStatus>>,
+    /// 已下载字节数
+    downloaded_bytes: Arc<AtomicU64>,
+    /// 是否已经调用过load方法
+    load_called: Arc<AtomicBool>,
+    /// 是否需要中断下载
+    should_abort: Arc<AtomicBool>,
+    /// 下载是否已完成（用于通知Reader停止等待）
+    download_completed: Arc<AtomicBool>,
+    /// 点播音频的总时长
+    duration: Arc<Mutex<Option<Duration>>>,
+    /// 已下载分片的总时长
+    buffered_duration: Arc<Mutex<Duration>>,
+    /// 选择变体流时的最大码率
+    max_bandwidth: Arc<Mutex<Option<u64>>>,
+    /// 请求失败时的重试策略
+    retry_policy: Arc<Mutex<RetryPolicy>>,
+    /// 请求选项
+    options: Arc<Mutex<LoaderOptions>>,
+    /// 下载线程句柄
+    thread_handle: DownloadHandle,
+    /// 回调函数
+    callback: LoaderCallback,
+}
+
+impl HlsLoader {
+    /// 创建新的HLS加载器实例
+    pub fn new<T: AppendableDataWrapper + Send + 'static>(data: T) -> Self {
+        Self {
+            data: Arc::new(Mutex::new(Box::new(data))),
+            condvar: Arc::new(Condvar::new()),
+            status: Arc::new(Mutex::new(DownloadStatus::NotStarted)),
+            downloaded_bytes: Arc::new(AtomicU64::new(0)),
+            load_called: Arc::new(AtomicBool::new(false)),
+            should_abort: Arc::new(AtomicBool::new(false)),
+            download_completed: Arc::new(AtomicBool::new(false)),
+            duration: Arc::new(Mutex::new(None)),
+            buffered_duration: Arc::new(Mutex::new(Duration::ZERO)),
+            max_bandwidth: Arc::new(Mutex::new(None)),
+            retry_policy: Arc::new(Mutex::new(RetryPolicy::default())),
+            options: Arc::new(Mutex::new(LoaderOptions::default())),
+            thread_handle: Arc::new(Mutex::new(None)),
+            callback: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// 获取当前下载状态
+    pub fn status(&self) -> DownloadStatus {
+        *self.status.lock().unwrap()
+    }
+
+    /// 获取已下载字节数
+    pub fn downloaded_bytes(&self) -> u64 {
+        self.downloaded_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 获取条件变量的引用
+    pub fn condvar(&self) -> Arc<Condvar> {
+        Arc::clone(&self.condvar)
+    }
+
+    /// 获取下载完成标志的引用
+    pub fn download_completed(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.download_completed)
+    }
+
+    /// 获取点播音频的总时长，直播时返回 `None`
+    pub fn duration(&self) -> Option<Duration> {
+        *self.duration.lock().unwrap()
+    }
+
+    /// 获取已下载分片的总时长（直播时从开始播放的分片算起）
+    pub fn buffered_duration(&self) -> Duration {
+        *self.buffered_duration.lock().unwrap()
+    }
+
+    /// 设置选择变体流时的最大码率（bit/s），默认选择码率最高的变体
+    pub fn set_max_bandwidth(&self, max_bandwidth: Option<u64>) {
+        *self.max_bandwidth.lock().unwrap() = max_bandwidth;
+    }
+
+    /// 设置播放列表及分片请求失败时的重试策略，需在 `load` 前调用
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry_policy.lock().unwrap() = policy;
+    }
+
+    /// 设置请求选项（请求头、认证、代理、超时等），需在 `load` 前调用
+    pub fn set_options(&self, options: LoaderOptions) {
+        *self.options.lock().unwrap() = options;
+    }
+
+    /// 设置消息回调函数
+    ///
+    /// # 注意
+    /// 多次调用会替换之前设置的回调函数
+    pub fn set_callback<F>(&self, callback: F)
+    where
+        F: Fn(LoaderEvent) + Send + 'static,
+    {
+        let mut cb = self.callback.lock().unwrap();
+        *cb = Some(Box::new(callback));
+    }
+
+    /// 开始加载
+    ///
+    /// # 参数
+    /// * `url` - 播放列表地址
+    /// * `headers` - 可选的HTTP请求头，附加在 [`set_options`](Self::set_options) 设置的请求头之后
+    ///
+    /// # 返回
+    /// * `Ok(())` - 播放列表加载成功，开始下载分片
+    /// * `Err(Error)` - 播放列表加载失败
+    ///
+    /// # Panics
+    /// 如果多次调用此方法会触发panic
+    pub async fn load(&self, url: &str, headers: Option<Vec<(String, String)>>) -> Result<()> {
+        // 检查是否已经调用过load
+        if self.load_called.swap(true, Ordering::SeqCst) {
+            panic!("load() can only be called once");
+        }
+
+        let mut options = self.options.lock().unwrap().clone();
+        options.headers.extend(headers.unwrap_or_default());
+        let client = options.client()?;
+        let retry_policy = *self.retry_policy.lock().unwrap();
+        let callback = Arc::clone(&self.callback);
+
+        // 更新状态为下载中
+        {
+            let mut status = self.status.lock().unwrap();
+            *status = DownloadStatus::Downloading;
+        }
+
+        // 获取媒体播放列表，主播放列表则按码率选择变体流
+        let playlist = match Url::parse(url) {
+            Ok(url) => {
+                let fetch = || fetch_playlist(&client, &url, &options);
+                retry(&retry_policy, &callback, fetch).await
+            }
+            Err(e) => Err(Error::invalid_input(format!("Invalid URL: {}", e))),
+        };
+        let playlist = match playlist {
+            Ok((_, Playlist::Master(master))) => {
+                let max_bandwidth = *self.max_bandwidth.lock().unwrap();
+                match master.select_variant(max_bandwidth) {
+                    Some(variant) => {
+                        let fetch = || fetch_playlist(&client, &variant.uri, &options);
+                        retry(&retry_policy, &callback, fetch).await
+                    }
+                    None => Err(Error::UnsupportedFormat(
+                        "No variant in master playlist".to_string(),
+                    )),
+                }
+            }
+            playlist => playlist,
+        };
+        let (media_url, media) = match playlist {
+            Ok((media_url, Playlist::Media(media))) => (media_url, media),
+            Ok(_) => {
+                let error = Error::UnsupportedFormat("Unexpected master playlist".to_string());
+                self.abort_with(error.clone());
+                return Err(error);
+            }
+            Err(e) => {
+                self.abort_with(e.clone());
+                return Err(e);
+            }
+        };
+
+        if media.ended {
+            *self.duration.lock().unwrap() = Some(Duration::from_secs_f64(media.duration()));
+        }
+
+        // 克隆需要在线程中使用的Arc引用
+        let data = Arc::clone(&self.data);
+        let condvar = Arc::clone(&self.condvar);
+        let status = Arc::clone(&self.status);
+        let downloaded_bytes = Arc::clone(&self.downloaded_bytes);
+        let should_abort = Arc::clone(&self.should_abort);
+        let download_completed = Arc::clone(&self.download_completed);
+        let buffered_duration = Arc::clone(&self.buffered_duration);
+
+        // 创建分片下载线程
+        let handle = tokio::task::spawn(async move {
+            let mut media = media;
+            let mut demuxer: Option<TsDemuxer> = None;
+            let mut loaded_map = None;
+            let mut audio = Vec::new();
+
+            // 将数据追加到data中，并解除Reader对缓冲区数据的等待
+            let write = |slice: &[u8]| {
+                data.lock().unwrap().append_data(slice);
+                condvar.notify_all();
+                downloaded_bytes.fetch_add(slice.len() as u64, Ordering::Relaxed);
+            };
+            let abort = |error: Error| {
+                *status.lock().unwrap() = DownloadStatus::Aborted;
+                if let Some(ref cb) = *callback.lock().unwrap() {
+                    cb(LoaderEvent::Aborted {
+                        error: error.clone(),
+                    });
+                }
+                Err(error)
+            };
+
+            // 点播从头开始，直播从接近最新的分片开始
+            let mut next_sequence = if media.ended {
+                media.media_sequence
+            } else {
+                let start = media.segments.len().saturating_sub(LIVE_START_SEGMENTS);
+                media
+                    .segments
+                    .get(start)
+                    .map_or(media.media_sequence, |s| s.sequence)
+            };
+
+            loop {
+                for segment in media.segments.iter() {
+                    if segment.sequence < next_sequence {
+                        continue;
+                    }
+                    // 检查是否需要中断
+                    if should_abort.load(Ordering::Relaxed) {
+                        return abort(Error::Cancelled);
+                    }
+                    if segment.encrypted {
+                        return abort(Error::UnsupportedFormat(
+                            "Encrypted HLS segments are not supported".to_string(),
+                        ));
+                    }
+
+                    // fMP4 分片需要先写入初始化分片
+                    if segment.map != loaded_map {
+                        if let Some(ref map) = segment.map {
+                            let fetch_map = || fetch(&client, map, &options);
+                            match retry(&retry_policy, &callback, fetch_map).await {
+                                Ok(bytes) => write(&bytes),
+                                Err(e) => return abort(e),
+                            }
+                        }
+                        loaded_map = segment.map.clone();
+                    }
+
+                    let fetch_segment = || fetch(&client, &segment.uri, &options);
+                    let bytes = match retry(&retry_policy, &callback, fetch_segment).await {
+                        Ok(bytes) => bytes,
+                        Err(e) => return abort(e),
+                    };
+                    if demuxer.is_none() && mpegts::is_mpegts(&bytes) {
+                        demuxer = Some(TsDemuxer::new());
+                    }
+                    match demuxer.as_mut() {
+                        Some(demuxer) => {
+                            audio.clear();
+                            demuxer.push(&bytes, &mut audio);
+                            write(&audio);
+                        }
+                        None => write(mpegts::strip_id3(&bytes)),
+                    }
+                    next_sequence = segment.sequence + 1;
+
+                    // 分片的总长度未知，每下载一个分片触发一次进度事件
+                    *buffered_duration.lock().unwrap() +=
+                        Duration::try_from_secs_f64(segment.duration).unwrap_or_default();
+                    if let Some(ref cb) = *callback.lock().unwrap() {
+                        cb(LoaderEvent::Progress {
+                            downloaded: downloaded_bytes.load(Ordering::Relaxed),
+                            total: 0,
+                        });
+                    }
+                }
+
+                if media.ended {
+                    break;
+                }
+
+                // 直播：等待新分片后刷新播放列表
+                let interval = (media.target_duration / 2.0).max(1.0);
+                tokio::time::sleep(Duration::from_secs_f64(interval)).await;
+                if should_abort.load(Ordering::Relaxed) {
+                    return abort(Error::Cancelled);
+                }
+                let refresh = || fetch_playlist(&client, &media_url, &options);
+                media = match retry(&retry_policy, &callback, refresh).await {
+                    Ok((_, Playlist::Media(media))) => media,
+                    Ok(_) => {
+                        return abort(Error::UnsupportedFormat(
+                            "Unexpected master playlist".to_string(),
+                        ))
+                    }
+                    Err(e) => return abort(e),
+                };
+            }
+
+            if let Some(ref mut demuxer) = demuxer {
+                audio.clear();
+                demuxer.flush(&mut audio);
+                write(&audio);
+            }
+            data.lock().unwrap().complete();
+
+            // 下载完成
+            *status.lock().unwrap() = DownloadStatus::Completed;
+
+            // 设置下载完成标志，并通知所有等待的Reader
+            download_completed.store(true, Ordering::Release);
+            condvar.notify_all();
+
+            if let Some(ref cb) = *callback.lock().unwrap() {
+                cb(LoaderEvent::Completed);
+            }
+            Ok(())
+        });
+
+        let mut th = self.thread_handle.lock().unwrap();
+        *th = Some(handle);
+        Ok(())
+    }
+
+    /// 中断当前下载
+    pub fn abort(&self) -> Result<(), DownloadStatus> {
+        let mut status = self.status.lock().unwrap();
+        if *status != DownloadStatus::Downloading {
+            return Err(*status);
+        }
+        // 设置中断标志
+        self.should_abort.store(true, Ordering::SeqCst);
+
+        // 中断下载线程
+        let mut th = self.thread_handle.lock().unwrap();
+        if let Some(handle) = th.take() {
+            handle.abort();
+        }
+        *status = DownloadStatus::Aborted;
+        Ok(())
+    }
+
+    /// 播放列表加载失败时更新状态并发送中断事件
+    fn abort_with(&self, error: Error) {
+        *self.status.lock().unwrap() = DownloadStatus::Aborted;
+        if let Some(ref cb) = *self.callback.lock().unwrap() {
+            cb(LoaderEvent::Aborted { error });
+        }
+    }
+}
+
+impl Drop for HlsLoader {
+    fn drop(&mut self) {
+        // 中断下载
+        let mut status = self.status.lock().unwrap();
+        // 设置中断标志
+        self.should_abort.store(true, Ordering::SeqCst);
+
+        // 中断下载线程
+        let mut th = self.thread_handle.lock().unwrap();
+        if let Some(handle) = th.take() {
+            handle.abort();
+        }
+        *status = DownloadStatus::Aborted;
+    }
+}
+
+/// 按重试策略发起请求，网络错误及失败的状态码在等待后重试
+///
+/// 重试前发送 [`LoaderEvent::Retrying`]，重试成功后发送 [`LoaderEvent::Resumed`]
+async fn retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    callback: &LoaderCallback,
+    mut request: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match request().await {
+            Ok(value) => {
+                if attempt > 0 {
+                    if let Some(ref cb) = *callback.lock().unwrap() {
+                        cb(LoaderEvent::Resumed);
+                    }
+                }
+                return Ok(value);
+            }
+            // 无效的播放列表等错误重试后仍会失败
+            Err(Error::Network(_) | Error::HttpStatus(_)) if attempt < policy.max_attempts => {}
+            Err(e) => return Err(e),
+        }
+        attempt += 1;
+        if let Some(ref cb) = *callback.lock().unwrap() {
+            cb(LoaderEvent::Retrying { attempt });
+        }
+        tokio::time::sleep(policy.delay(attempt)).await;
+    }
+}
+
+/// 下载完整内容
+async fn fetch(
+    client: &reqwest::Client,
+    url: &Url,
+    options: &LoaderOptions,
+) -> Result<bytes::Bytes> {
+    let bytes = options
+        .apply(client.get(url.clone()))
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+    Ok(bytes)
+}
+
+/// 下载并解析播放列表，返回重定向后的地址及播放列表
+async fn fetch_playlist(
+    client: &reqwest::Client,
+    url: &Url,
+    options: &LoaderOptions,
+) -> Result<(Url, Playlist)> {
+    let response = options
+        .apply(client.get(url.clone()))
+        .send()
+        .await?
+        .error_for_status()?;
+    let url = response.url().clone();
+    let text = response.text().await?;
+    let playlist = Playlist::parse(&text, &url)
+        .ok_or_else(|| Error::UnsupportedFormat("Invalid m3u8 playlist".to_string()))?;
+    Ok((url, playlist))
+}
@@ -0,0 +1,159 @@
+/// TS 包长度
+const PACKET_SIZE: usize = 188;
+/// TS 包同步字节
+const SYNC_BYTE: u8 = 0x47;
+
+/// 是否为 MPEG-TS 数据
+pub fn is_mpegts(data: &[u8]) -> bool {
+    data.len() >= PACKET_SIZE * 2 && data[0] == SYNC_BYTE && data[PACKET_SIZE] == SYNC_BYTE
+}
+
+/// MPEG-TS 解复用器
+///
+/// 从 TS 流中提取第一个音频流（ADTS AAC 或 MPEG 音频）的原始数据，
+/// 提取后的数据可由解码器直接探测格式
+#[derive(Debug, Default)]
+pub struct TsDemuxer {
+    /// PMT 的 PID
+    pmt_pid: Option<u16>,
+    /// 音频流的 PID
+    audio_pid: Option<u16>,
+    /// 正在组装的 PES 包
+    pes: Vec<u8>,
+    /// 不足一个 TS 包的剩余数据
+    remainder: Vec<u8>,
+}
+
+impl TsDemuxer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 处理一段 TS 数据，将提取出的音频数据追加到 `out` 中
+    pub fn push(&mut self, data: &[u8], out: &mut Vec<u8>) {
+        self.remainder.extend_from_slice(data);
+        let buf = std::mem::take(&mut self.remainder);
+
+        let mut offset = 0;
+        while offset + PACKET_SIZE <= buf.len() {
+            if buf[offset] != SYNC_BYTE {
+                // 失去同步，查找下一个同步字节
+                offset += 1;
+                continue;
+            }
+            self.packet(&buf[offset..offset + PACKET_SIZE], out);
+            offset += PACKET_SIZE;
+        }
+        self.remainder = buf[offset..].to_vec();
+    }
+
+    /// 输出尚未完成的 PES 包
+    pub fn flush(&mut self, out: &mut Vec<u8>) {
+        self.flush_pes(out);
+        self.remainder.clear();
+    }
+
+    fn packet(&mut self, packet: &[u8], out: &mut Vec<u8>) {
+        let payload_start = packet[1] & 0x40 != 0;
+        let pid = ((packet[1] as u16 & 0x1f) << 8) | packet[2] as u16;
+        let adaptation = (packet[3] >> 4) & 0x3;
+
+        let mut offset = 4;
+        if adaptation & 0x2 != 0 {
+            offset += 1 + packet[4] as usize;
+        }
+        if adaptation & 0x1 == 0 || offset >= PACKET_SIZE {
+            return;
+        }
+        let payload = &packet[offset..];
+
+        if pid == 0 {
+            self.parse_pat(payload, payload_start);
+        } else if Some(pid) == self.pmt_pid {
+            self.parse_pmt(payload, payload_start);
+        } else if Some(pid) == self.audio_pid {
+            if payload_start {
+                self.flush_pes(out);
+            }
+            self.pes.extend_from_slice(payload);
+        }
+    }
+
+    /// 获取 PSI 表的内容（跳过 pointer field）
+    fn section(payload: &[u8], payload_start: bool) -> Option<&[u8]> {
+        if !payload_start {
+            return None;
+        }
+        let pointer = *payload.first()? as usize;
+        let section = payload.get(1 + pointer..)?;
+        let length = ((section.get(1)? & 0x0f) as usize) << 8 | *section.get(2)? as usize;
+        // 不含末尾的 CRC32
+        section.get(..(3 + length).checked_sub(4)?)
+    }
+
+    fn parse_pat(&mut self, payload: &[u8], payload_start: bool) {
+        let Some(section) = Self::section(payload, payload_start) else {
+            return;
+        };
+        for program in section.get(8..).unwrap_or(&[]).chunks_exact(4) {
+            let number = (program[0] as u16) << 8 | program[1] as u16;
+            if number != 0 {
+                self.pmt_pid = Some(((program[2] as u16 & 0x1f) << 8) | program[3] as u16);
+                return;
+            }
+        }
+    }
+
+    fn parse_pmt(&mut self, payload: &[u8], payload_start: bool) {
+        if self.audio_pid.is_some() {
+            return;
+        }
+        let Some(section) = Self::section(payload, payload_start) else {
+            return;
+        };
+        let Some(info_length) = section
+            .get(10..12)
+            .map(|b| ((b[0] as usize & 0x0f) << 8) | b[1] as usize)
+        else {
+            return;
+        };
+
+        let mut offset = 12 + info_length;
+        while offset + 5 <= section.len() {
+            let stream_type = section[offset];
+            let pid = ((section[offset + 1] as u16 & 0x1f) << 8) | section[offset + 2] as u16;
+            let es_info_length =
+                ((section[offset + 3] as usize & 0x0f) << 8) | section[offset + 4] as usize;
+            // 0x0f: ADTS AAC, 0x03/0x04: MPEG-1/2 音频
+            if matches!(stream_type, 0x0f | 0x03 | 0x04) {
+                self.audio_pid = Some(pid);
+                return;
+            }
+            offset += 5 + es_info_length;
+        }
+    }
+
+    /// 去掉 PES 头，输出其中的音频数据
+    fn flush_pes(&mut self, out: &mut Vec<u8>) {
+        let pes = std::mem::take(&mut self.pes);
+        if pes.len() < 9 || pes[..3] != [0, 0, 1] {
+            return;
+        }
+        let header_length = 9 + pes[8] as usize;
+        if let Some(data) = pes.get(header_length..) {
+            out.extend_from_slice(data);
+        }
+    }
+}
+
+/// 去掉分片开头的 ID3 标签（HLS 打包音频会在每个分片前附加时间戳标签）
+pub fn strip_id3(mut data: &[u8]) -> &[u8] {
+    while data.len() >= 10 && &data[..3] == b"ID3" {
+        let size = data[6..10]
+            .iter()
+            .fold(0usize, |size, b| (size << 7) | (*b as usize & 0x7f));
+        let footer = if data[5] & 0x10 != 0 { 10 } else { 0 };
+        data = data.get(10 + size + footer..).unwrap_or(&[]);
+    }
+    data
+}
@@ -0,0 +1,182 @@
+use reqwest::Url;
+
+/// HLS 播放列表
+#[derive(Debug, Clone, PartialEq)]
+pub enum Playlist {
+    /// 主播放列表，包含多个码率的变体流
+    Master(MasterPlaylist),
+    /// 媒体播放列表，包含分片列表
+    Media(MediaPlaylist),
+}
+
+/// 主播放列表
+#[derive(Debug, Clone, PartialEq)]
+pub struct MasterPlaylist {
+    pub variants: Vec<Variant>,
+}
+
+/// 变体流
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variant {
+    /// 媒体播放列表地址
+    pub uri: Url,
+    /// 峰值码率（bit/s）
+    pub bandwidth: u64,
+    /// 编码格式，如 `mp4a.40.2`
+    pub codecs: Option<String>,
+}
+
+/// 媒体播放列表
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    /// 分片的最大时长（秒）
+    pub target_duration: f64,
+    /// 第一个分片的序号
+    pub media_sequence: u64,
+    pub segments: Vec<Segment>,
+    /// 是否包含 `#EXT-X-ENDLIST`（点播），否则为直播
+    pub ended: bool,
+}
+
+/// 媒体分片
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub uri: Url,
+    /// 时长（秒）
+    pub duration: f64,
+    /// 序号
+    pub sequence: u64,
+    /// 初始化分片地址（`#EXT-X-MAP`，用于 fMP4）
+    pub map: Option<Url>,
+    /// 是否已加密（暂不支持解密）
+    pub encrypted: bool,
+}
+
+impl Playlist {
+    /// 解析播放列表，`base` 为播放列表自身的地址，用于解析相对地址
+    ///
+    /// 内容不是 m3u8 时返回 `None`
+    pub fn parse(text: &str, base: &Url) -> Option<Self> {
+        let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next()? != "#EXTM3U" {
+            return None;
+        }
+
+        let mut variants = Vec::new();
+        let mut pending_variant: Option<(u64, Option<String>)> = None;
+
+        let mut target_duration = 0.0;
+        let mut media_sequence = 0;
+        let mut segments = Vec::new();
+        let mut ended = false;
+        let mut is_media = false;
+        let mut pending_duration: Option<f64> = None;
+        let mut map = None;
+        let mut encrypted = false;
+
+        for line in lines {
+            if let Some(attrs) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+                let attrs = parse_attributes(attrs);
+                let bandwidth = attribute(&attrs, "BANDWIDTH")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                let codecs = attribute(&attrs, "CODECS").map(str::to_string);
+                pending_variant = Some((bandwidth, codecs));
+            } else if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+                is_media = true;
+                target_duration = value.parse().unwrap_or(0.0);
+            } else if let Some(value) = line.strip_prefix("#EXT-X-MEDIA-SEQUENCE:") {
+                media_sequence = value.parse().unwrap_or(0);
+            } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+                is_media = true;
+                let value = value.split(',').next().unwrap_or("");
+                pending_duration = Some(value.trim().parse().unwrap_or(0.0));
+            } else if let Some(attrs) = line.strip_prefix("#EXT-X-MAP:") {
+                let attrs = parse_attributes(attrs);
+                map = attribute(&attrs, "URI").and_then(|uri| base.join(uri).ok());
+            } else if let Some(attrs) = line.strip_prefix("#EXT-X-KEY:") {
+                let attrs = parse_attributes(attrs);
+                encrypted = attribute(&attrs, "METHOD").is_some_and(|m| m != "NONE");
+            } else if line == "#EXT-X-ENDLIST" {
+                ended = true;
+            } else if line.starts_with('#') {
+                continue;
+            } else if let Some((bandwidth, codecs)) = pending_variant.take() {
+                if let Ok(uri) = base.join(line) {
+                    variants.push(Variant {
+                        uri,
+                        bandwidth,
+                        codecs,
+                    });
+                }
+            } else if let Some(duration) = pending_duration.take() {
+                if let Ok(uri) = base.join(line) {
+                    segments.push(Segment {
+                        uri,
+                        duration,
+                        sequence: media_sequence + segments.len() as u64,
+                        map: map.clone(),
+                        encrypted,
+                    });
+                }
+            }
+        }
+
+        if is_media || variants.is_empty() {
+            Some(Playlist::Media(MediaPlaylist {
+                target_duration,
+                media_sequence,
+                segments,
+                ended,
+            }))
+        } else {
+            Some(Playlist::Master(MasterPlaylist { variants }))
+        }
+    }
+}
+
+impl MasterPlaylist {
+    /// 选择码率不超过 `max_bandwidth` 的最高码率变体，均超过时选择码率最低的变体
+    pub fn select_variant(&self, max_bandwidth: Option<u64>) -> Option<&Variant> {
+        let max_bandwidth = max_bandwidth.unwrap_or(u64::MAX);
+        self.variants
+            .iter()
+            .filter(|v| v.bandwidth <= max_bandwidth)
+            .max_by_key(|v| v.bandwidth)
+            .or_else(|| self.variants.iter().min_by_key(|v| v.bandwidth))
+    }
+}
+
+impl MediaPlaylist {
+    /// 所有分片的总时长（秒）
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().map(|s| s.duration).sum()
+    }
+}
+
+/// 解析形如 `KEY=VALUE,KEY="VALUE"` 的属性列表
+fn parse_attributes(text: &str) -> Vec<(&str, &str)> {
+    let mut attrs = Vec::new();
+    let mut rest = text;
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim();
+        rest = &rest[eq + 1..];
+        let value;
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            value = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or("");
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            value = &rest[..end];
+            rest = &rest[end..];
+        }
+        attrs.push((key, value));
+        rest = rest.trim_start_matches(',');
+    }
+    attrs
+}
+
+fn attribute<'a>(attrs: &[(&str, &'a str)], key: &str) -> Option<&'a str> {
+    attrs.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
@@ -0,0 +1,120 @@
+use crate::metadata::AudioMetadata;
+
+/// ICY 元数据解析器
+///
+/// 网络电台（SHOUTcast/Icecast）在请求头带有 `Icy-MetaData: 1` 时，会在音频数据中
+/// 每隔 `icy-metaint` 字节插入一个元数据块。此解析器负责将元数据块从字节流中剥离，
+/// 并解析其中的 `StreamTitle` 等字段。
+#[derive(Debug)]
+pub struct IcyStripper {
+    /// 两个元数据块之间的音频字节数
+    metaint: usize,
+    /// 距离下一个元数据块的剩余音频字节数
+    audio_remaining: usize,
+    /// 当前元数据块的剩余字节数，`None` 表示下一个字节为元数据块长度
+    meta_remaining: Option<usize>,
+    /// 当前元数据块的内容
+    meta_buf: Vec<u8>,
+    /// 上一次的 StreamTitle，用于过滤重复的元数据
+    last_title: Option<String>,
+}
+
+impl IcyStripper {
+    /// 创建新的 ICY 元数据解析器
+    ///
+    /// # 参数
+    /// * `metaint` - 响应头 `icy-metaint` 的值
+    pub fn new(metaint: usize) -> Self {
+        Self {
+            metaint,
+            audio_remaining: metaint,
+            meta_remaining: None,
+            meta_buf: Vec::new(),
+            last_title: None,
+        }
+    }
+
+    /// 从响应头中读取 `icy-metaint`
+    pub fn from_headers(headers: &reqwest::header::HeaderMap) -> Option<Self> {
+        headers
+            .get("icy-metaint")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|s| s.trim().parse::<usize>().ok())
+            .filter(|metaint| *metaint > 0)
+            .map(Self::new)
+    }
+
+    /// 处理一段数据
+    ///
+    /// 将其中的音频数据追加到 `audio` 中，若解析到新的 StreamTitle 则返回对应的元数据（有多个时返回最后一个）
+    pub fn process(&mut self, mut chunk: &[u8], audio: &mut Vec<u8>) -> Option<AudioMetadata> {
+        let mut updated = None;
+
+        while !chunk.is_empty() {
+            match self.meta_remaining {
+                // 音频数据部分
+                None if self.audio_remaining > 0 => {
+                    let len = self.audio_remaining.min(chunk.len());
+                    audio.extend_from_slice(&chunk[..len]);
+                    self.audio_remaining -= len;
+                    chunk = &chunk[len..];
+                }
+                // 元数据块长度（以 16 字节为单位）
+                None => {
+                    let len = chunk[0] as usize * 16;
+                    chunk = &chunk[1..];
+                    if len == 0 {
+                        self.audio_remaining = self.metaint;
+                    } else {
+                        self.meta_remaining = Some(len);
+                    }
+                }
+                // 元数据块内容
+                Some(remaining) => {
+                    let len = remaining.min(chunk.len());
+                    self.meta_buf.extend_from_slice(&chunk[..len]);
+                    chunk = &chunk[len..];
+                    if remaining == len {
+                        if let Some(metadata) = self.parse_block() {
+                            updated = Some(metadata);
+                        }
+                        self.meta_buf.clear();
+                        self.meta_remaining = None;
+                        self.audio_remaining = self.metaint;
+                    } else {
+                        self.meta_remaining = Some(remaining - len);
+                    }
+                }
+            }
+        }
+
+        updated
+    }
+
+    /// 解析元数据块，StreamTitle 未变化时返回 `None`
+    fn parse_block(&mut self) -> Option<AudioMetadata> {
+        let block = String::from_utf8_lossy(&self.meta_buf);
+        let block = block.trim_end_matches('\0');
+
+        let mut metadata = AudioMetadata::default();
+        let mut rest = block;
+        while let Some(eq) = rest.find("='") {
+            let key = rest[..eq].trim();
+            rest = &rest[eq + 2..];
+            let end = rest.find("';").unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = rest.get(end + 2..).unwrap_or("");
+
+            if key == "StreamTitle" && !value.is_empty() {
+                metadata.title = Some(value.to_string());
+            }
+            metadata.tags.push((key.to_string(), value.to_string()));
+        }
+
+        if metadata.title.is_none() || metadata.title == self.last_title {
+            return None;
+        }
+        self.last_title = metadata.title.clone();
+        Some(metadata)
+    }
+}
@@ -1,10 +1,33 @@
+use std::sync::{Arc, Mutex};
+
+use crate::error::Error;
+use crate::reader::AppendableDataWrapper;
+
+pub mod cache;
 pub mod downloader;
+pub mod hls;
+pub mod icy;
+pub mod options;
+pub mod retry;
+
+pub use options::{Auth, LoaderOptions};
+pub use retry::RetryPolicy;
+
+type DataWrapper = Arc<Mutex<Box<dyn AppendableDataWrapper + Send + 'static>>>;
+type DownloadHandle = Arc<Mutex<Option<tokio::task::JoinHandle<crate::error::Result<()>>>>>;
+type LoaderCallback = Arc<Mutex<Option<Box<dyn Fn(LoaderEvent) + Send + 'static>>>>;
 
 /// 加载器事件
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum LoaderEvent {
     /// 下载完成
     Completed,
-    /// 下载中断
-    Aborted,
+    /// 下载中断，携带中断的原因
+    Aborted { error: Error },
+    /// 连接中断，正在第 `attempt` 次重新连接
+    Retrying { attempt: u32 },
+    /// 重新连接成功，继续下载
+    Resumed,
+    /// 下载进度（限制触发频率），`total` 为 0 表示总长度未知
+    Progress { downloaded: u64, total: u64 },
 }
@@ -0,0 +1,159 @@
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+
+/// 默认的连接超时时间
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 默认的读取空闲超时时间
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP 认证方式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Auth {
+    /// `Authorization: Bearer <token>`
+    Bearer(String),
+    /// HTTP Basic 认证
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+}
+
+/// 网络音频的请求选项
+///
+/// 请求头、认证及 User-Agent 附加在每个请求上；超时、代理及 TLS 设置用于创建 HTTP 客户端，
+/// 指定了共享的 `client` 时这些设置以该客户端为准
+///
+/// # Examples
+///
+/// ```no_run
+/// use remu_audio::loader::{Auth, LoaderOptions};
+/// use remu_audio::player::Player;
+///
+/// # async fn example() -> remu_audio::Result<()> {
+/// let mut player = Player::new()?;
+/// let options = LoaderOptions {
+///     auth: Some(Auth::Bearer("token".to_string())),
+///     user_agent: Some("my-app/1.0".to_string()),
+///     ..Default::default()
+/// };
+/// player
+///     .load_url("https://cdn.example.com/audio.mp3", options)
+///     .await?;
+/// // 只需认证时可直接传入认证方式
+/// player
+///     .enqueue_url("https://cdn.example.com/next.mp3", Auth::Bearer("token".to_string()))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoaderOptions {
+    /// 附加的请求头
+    pub headers: Vec<(String, String)>,
+    /// 认证方式
+    pub auth: Option<Auth>,
+    /// User-Agent 请求头
+    pub user_agent: Option<String>,
+    /// 代理服务器
+    pub proxy: Option<reqwest::Proxy>,
+    /// 建立连接的超时时间，为 `None` 时不限制
+    pub connect_timeout: Option<Duration>,
+    /// 两次读取数据之间的最长空闲时间，为 `None` 时不限制；不限制下载的总时长
+    pub read_timeout: Option<Duration>,
+    /// 额外信任的根证书
+    pub root_certificates: Vec<reqwest::Certificate>,
+    /// 是否接受无效的证书（仅用于测试环境）
+    pub accept_invalid_certs: bool,
+    /// 共享的 HTTP 客户端，用于复用连接
+    pub client: Option<reqwest::Client>,
+}
+
+impl Default for LoaderOptions {
+    fn default() -> Self {
+        Self {
+            headers: Vec::new(),
+            auth: None,
+            user_agent: None,
+            proxy: None,
+            connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+            read_timeout: Some(DEFAULT_READ_TIMEOUT),
+            root_certificates: Vec::new(),
+            accept_invalid_certs: false,
+            client: None,
+        }
+    }
+}
+
+/// 使用指定的认证方式，其余为默认选项
+impl From<Auth> for LoaderOptions {
+    fn from(auth: Auth) -> Self {
+        Self {
+            auth: Some(auth),
+            ..Default::default()
+        }
+    }
+}
+
+/// 附加指定的请求头，其余为默认选项
+impl From<Vec<(String, String)>> for LoaderOptions {
+    fn from(headers: Vec<(String, String)>) -> Self {
+        Self {
+            headers,
+            ..Default::default()
+        }
+    }
+}
+
+/// 附加可选的请求头，兼容以 `None` 或 `Some(headers)` 传入请求头的写法
+impl From<Option<Vec<(String, String)>>> for LoaderOptions {
+    fn from(headers: Option<Vec<(String, String)>>) -> Self {
+        headers.map(Self::from).unwrap_or_default()
+    }
+}
+
+impl LoaderOptions {
+    /// 获取共享的客户端，未指定时按选项创建
+    pub(crate) fn client(&self) -> Result<reqwest::Client> {
+        if let Some(ref client) = self.client {
+            return Ok(client.clone());
+        }
+        let mut builder =
+            reqwest::Client::builder().danger_accept_invalid_certs(self.accept_invalid_certs);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.read_timeout {
+            builder = builder.read_timeout(timeout);
+        }
+        if let Some(ref proxy) = self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        builder
+            .build()
+            .map_err(|e| Error::invalid_input(format!("Invalid HTTP client options: {}", e)))
+    }
+
+    /// 为请求附加请求头、认证及 User-Agent
+    pub(crate) fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(ref user_agent) = self.user_agent {
+            request = request.header(reqwest::header::USER_AGENT, user_agent);
+        }
+        match self.auth {
+            Some(Auth::Bearer(ref token)) => request = request.bearer_auth(token),
+            Some(Auth::Basic {
+                ref username,
+                ref password,
+            }) => request = request.basic_auth(username, password.as_ref()),
+            None => {}
+        }
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+        request
+    }
+}
@@ -0,0 +1,54 @@
+use std::time::Duration;
+
+/// 下载重试策略
+///
+/// 下载中断时按指数退避的间隔重新连接，并从中断的位置继续下载
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// 最大重试次数，为 0 时不重试
+    pub max_attempts: u32,
+    /// 第一次重试前的等待时间
+    pub initial_delay: Duration,
+    /// 等待时间的上限
+    pub max_delay: Duration,
+    /// 每次重试后等待时间的倍数
+    pub multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 创建重试策略，等待时间从 `initial_delay` 开始每次翻倍
+    pub fn new(max_attempts: u32, initial_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            initial_delay,
+            ..Default::default()
+        }
+    }
+
+    /// 不重试
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 0,
+            ..Default::default()
+        }
+    }
+
+    /// 第 `attempt` 次重试（从 1 开始）前的等待时间
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.saturating_sub(1) as i32);
+        Duration::try_from_secs_f64(self.initial_delay.as_secs_f64() * factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+}
@@ -0,0 +1,586 @@
+//! 响度分析（ITU-R BS.1770 / EBU R128）
+//!
+//! 不经过音频设备，以尽可能快的速度解码音频并测量综合响度、响度范围、瞬时及短期响度和真峰值
+
+use rodio::Source;
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+use std::fs::File;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::decoder::Decoder;
+use crate::error::Result;
+
+/// 测量块的步长（毫秒），瞬时及短期响度均按此间隔给出
+const STEP_MS: u32 = 100;
+/// 瞬时响度（400ms）包含的步数
+const MOMENTARY_STEPS: usize = 4;
+/// 短期响度（3s）包含的步数
+const SHORT_TERM_STEPS: usize = 30;
+/// 绝对门限（LUFS）
+const ABSOLUTE_GATE: f64 = -70.0;
+/// 综合响度的相对门限（LU）
+const RELATIVE_GATE: f64 = -10.0;
+/// 响度范围的相对门限（LU）
+const RANGE_RELATIVE_GATE: f64 = -20.0;
+/// 响度范围取短期响度分布的百分位
+const RANGE_PERCENTILES: (f64, f64) = (0.10, 0.95);
+/// 真峰值插值滤波器每相的系数个数
+const TRUE_PEAK_TAPS: usize = 12;
+/// 真峰值的4倍过采样插值滤波器（BS.1770 附件2），每行对应一个相位
+const TRUE_PEAK_FILTER: [[f64; TRUE_PEAK_TAPS]; 4] = [
+    [
+        0.001708984375,
+        0.010986328125,
+        -0.0196533203125,
+        0.033203125,
+        -0.0594482421875,
+        0.1373291015625,
+        0.97216796875,
+        -0.102294921875,
+        0.047607421875,
+        -0.026611328125,
+        0.014892578125,
+        -0.00830078125,
+    ],
+    [
+        -0.0291748046875,
+        0.029296875,
+        -0.0517578125,
+        0.089111328125,
+        -0.16650390625,
+        0.465087890625,
+        0.77978515625,
+        -0.2003173828125,
+        0.1015625,
+        -0.0582275390625,
+        0.0330810546875,
+        -0.0189208984375,
+    ],
+    [
+        -0.0189208984375,
+        0.0330810546875,
+        -0.0582275390625,
+        0.1015625,
+        -0.2003173828125,
+        0.77978515625,
+        0.465087890625,
+        -0.16650390625,
+        0.089111328125,
+        -0.0517578125,
+        0.029296875,
+        -0.0291748046875,
+    ],
+    [
+        -0.00830078125,
+        0.014892578125,
+        -0.026611328125,
+        0.047607421875,
+        -0.102294921875,
+        0.97216796875,
+        0.1373291015625,
+        -0.0594482421875,
+        0.033203125,
+        -0.0196533203125,
+        0.010986328125,
+        0.001708984375,
+    ],
+];
+/// 综合响度直方图的分辨率（LU）
+const HISTOGRAM_STEP: f64 = 0.1;
+/// 综合响度直方图的格数，覆盖绝对门限至 +30 LUFS，更响的测量块计入最后一格
+const HISTOGRAM_BINS: usize = 1000;
+/// 每次读取的采样数
+const BLOCK_SAMPLES: usize = 4096;
+/// 进度回调的间隔（按分析的音频时长计算）
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+type ProgressCallback = Arc<dyn Fn(AnalysisProgress) + Send + Sync + 'static>;
+
+/// 双二阶滤波器（直接II型转置）
+#[derive(Clone, Copy, Default)]
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    #[inline]
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+/// K 计权滤波器：高架预滤波器及 RLB 高通滤波器，系数按采样率计算
+fn k_weighting(sample_rate: u32) -> [Biquad; 2] {
+    let sample_rate = sample_rate as f64;
+
+    let f0 = 1681.974450955533;
+    let gain = 3.999843853973347;
+    let q = 0.7071752369554196;
+    let k = (PI * f0 / sample_rate).tan();
+    let vh = 10f64.powf(gain / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = Biquad {
+        b: [
+            (vh + vb * k / q + k * k) / a0,
+            2.0 * (k * k - vh) / a0,
+            (vh - vb * k / q + k * k) / a0,
+        ],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    let f0 = 38.13547087602444;
+    let q = 0.5003270373238773;
+    let k = (PI * f0 / sample_rate).tan();
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = Biquad {
+        b: [1.0, -2.0, 1.0],
+        a: [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+        z: [0.0; 2],
+    };
+
+    [shelf, high_pass]
+}
+
+/// 各声道的权重，5.1 声道时忽略 LFE 并提升环绕声道，其余布局均按 1.0 计算
+fn channel_weights(channels: usize) -> Vec<f64> {
+    if channels == 6 {
+        vec![1.0, 1.0, 1.0, 0.0, 1.41, 1.41]
+    } else {
+        vec![1.0; channels]
+    }
+}
+
+/// 按均方值计算响度（LUFS），静音时为负无穷
+fn loudness(mean_square: f64) -> f64 {
+    -0.691 + 10.0 * mean_square.log10()
+}
+
+/// 按响度计算均方值
+fn mean_square(loudness: f64) -> f64 {
+    10f64.powf((loudness + 0.691) / 10.0)
+}
+
+/// 均方值高于门限的测量块的平均值
+fn gated_mean(blocks: &[f64], gate: f64) -> Option<f64> {
+    let (sum, count) = blocks
+        .iter()
+        .filter(|&&block| block > gate)
+        .fold((0.0, 0), |(sum, count), block| (sum + block, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// 高于绝对门限的瞬时测量块，按响度分格累计
+///
+/// 每格记录测量块的个数及均方值之和，计算综合响度的耗时与音频长度无关；
+/// 相对门限所在的格整体计入或排除，误差不超过一格
+struct GatedBlocks {
+    count: Vec<u64>,
+    sum: Vec<f64>,
+    /// 所有格的均方值之和及测量块个数
+    total_sum: f64,
+    total_count: u64,
+}
+
+impl GatedBlocks {
+    fn new() -> Self {
+        Self {
+            count: vec![0; HISTOGRAM_BINS],
+            sum: vec![0.0; HISTOGRAM_BINS],
+            total_sum: 0.0,
+            total_count: 0,
+        }
+    }
+
+    /// 响度所在的格
+    fn bin(loudness: f64) -> usize {
+        let bin = ((loudness - ABSOLUTE_GATE) / HISTOGRAM_STEP).floor();
+        (bin.max(0.0) as usize).min(HISTOGRAM_BINS - 1)
+    }
+
+    fn push(&mut self, block: f64) {
+        if block <= mean_square(ABSOLUTE_GATE) {
+            return;
+        }
+        let bin = Self::bin(loudness(block));
+        self.count[bin] += 1;
+        self.sum[bin] += block;
+        self.total_sum += block;
+        self.total_count += 1;
+    }
+
+    /// 综合响度的均方值
+    fn integrated(&self) -> Option<f64> {
+        if self.total_count == 0 {
+            return None;
+        }
+        let relative = self.total_sum / self.total_count as f64 * 10f64.powf(RELATIVE_GATE / 10.0);
+        let start = Self::bin(loudness(relative));
+        let count: u64 = self.count[start..].iter().sum();
+        let sum: f64 = self.sum[start..].iter().sum();
+        (count > 0).then(|| sum / count as f64)
+    }
+}
+
+/// 响度测量
+///
+/// 依次输入交错排列的采样，按 BS.1770 计算带门限的综合响度，并记录瞬时及短期响度
+pub(crate) struct LoudnessMeter {
+    channels: usize,
+    filters: Vec<[Biquad; 2]>,
+    weights: Vec<f64>,
+    /// 下一个采样所属的声道
+    channel: usize,
+    /// 当前帧的加权能量
+    frame_energy: f64,
+    /// 当前步的能量之和
+    step_energy: f64,
+    /// 当前步已输入的帧数
+    step_frames: usize,
+    /// 每步的帧数
+    frames_per_step: usize,
+    /// 最近几步的能量
+    steps: VecDeque<f64>,
+    /// 高于绝对门限的瞬时测量块
+    gated: GatedBlocks,
+    /// 是否记录瞬时及短期响度序列
+    history: bool,
+    /// 瞬时响度测量块的均方值
+    momentary: Vec<f64>,
+    /// 短期响度测量块的均方值
+    short_term: Vec<f64>,
+    /// 已输入的帧数
+    frames: u64,
+}
+
+impl LoudnessMeter {
+    pub fn new(channels: u16, sample_rate: u32) -> Self {
+        Self::with_history(channels, sample_rate, true)
+    }
+
+    /// 只测量综合响度，不记录响度序列，内存占用不随音频长度增长，可在播放线程中使用
+    pub fn realtime(channels: u16, sample_rate: u32) -> Self {
+        Self::with_history(channels, sample_rate, false)
+    }
+
+    fn with_history(channels: u16, sample_rate: u32, history: bool) -> Self {
+        let channels = channels.max(1) as usize;
+        let sample_rate = sample_rate.max(1);
+        Self {
+            channels,
+            filters: vec![k_weighting(sample_rate); channels],
+            weights: channel_weights(channels),
+            channel: 0,
+            frame_energy: 0.0,
+            step_energy: 0.0,
+            step_frames: 0,
+            frames_per_step: (sample_rate * STEP_MS / 1000).max(1) as usize,
+            steps: VecDeque::with_capacity(SHORT_TERM_STEPS),
+            gated: GatedBlocks::new(),
+            history,
+            momentary: Vec::new(),
+            short_term: Vec::new(),
+            frames: 0,
+        }
+    }
+
+    #[inline]
+    pub fn push(&mut self, sample: f32) {
+        let channel = self.channel;
+        let [shelf, high_pass] = &mut self.filters[channel];
+        let y = high_pass.process(shelf.process(sample as f64));
+        self.frame_energy += self.weights[channel] * y * y;
+
+        self.channel += 1;
+        if self.channel < self.channels {
+            return;
+        }
+        self.channel = 0;
+        self.step_energy += self.frame_energy;
+        self.frame_energy = 0.0;
+        self.step_frames += 1;
+        self.frames += 1;
+        if self.step_frames < self.frames_per_step {
+            return;
+        }
+
+        if self.steps.len() == SHORT_TERM_STEPS {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(self.step_energy);
+        self.step_energy = 0.0;
+        self.step_frames = 0;
+        if self.steps.len() >= MOMENTARY_STEPS {
+            let energy: f64 = self.steps.iter().rev().take(MOMENTARY_STEPS).sum();
+            let frames = MOMENTARY_STEPS * self.frames_per_step;
+            let block = energy / frames as f64;
+            self.gated.push(block);
+            if self.history {
+                self.momentary.push(block);
+            }
+        }
+        if self.history && self.steps.len() == SHORT_TERM_STEPS {
+            let energy: f64 = self.steps.iter().sum();
+            let frames = SHORT_TERM_STEPS * self.frames_per_step;
+            self.short_term.push(energy / frames as f64);
+        }
+    }
+
+    /// 已测量的帧数（每声道的采样数）
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// 综合响度（LUFS），尚未测满一个测量块或音频过于安静时返回 `None`
+    pub fn integrated(&self) -> Option<f64> {
+        self.gated.integrated().map(loudness)
+    }
+
+    /// 响度范围（LU，EBU Tech 3342），尚未测满一个短期测量块、音频过于安静或未记录响度序列时返回 `None`
+    pub fn loudness_range(&self) -> Option<f64> {
+        let absolute = mean_square(ABSOLUTE_GATE);
+        let mean = gated_mean(&self.short_term, absolute)?;
+        let gate = absolute.max(mean * 10f64.powf(RANGE_RELATIVE_GATE / 10.0));
+        let mut values: Vec<f64> = (self.short_term.iter())
+            .filter(|&&block| block > gate)
+            .map(|&block| loudness(block))
+            .collect();
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| values[((values.len() - 1) as f64 * p).round() as usize];
+        Some(percentile(RANGE_PERCENTILES.1) - percentile(RANGE_PERCENTILES.0))
+    }
+
+    /// 瞬时响度序列（LUFS）
+    pub fn momentary(&self) -> Vec<f64> {
+        self.momentary
+            .iter()
+            .map(|&block| loudness(block))
+            .collect()
+    }
+
+    /// 短期响度序列（LUFS）
+    pub fn short_term(&self) -> Vec<f64> {
+        self.short_term
+            .iter()
+            .map(|&block| loudness(block))
+            .collect()
+    }
+}
+
+/// 真峰值测量
+///
+/// 采样率低于 96kHz 时按4倍、低于 192kHz 时按2倍过采样后取峰值，更高的采样率直接取采样峰值
+struct TruePeakMeter {
+    channels: usize,
+    /// 使用的插值相位
+    phases: &'static [usize],
+    /// 各声道最近的采样，`history[c][0]` 为最新的采样
+    history: Vec<[f32; TRUE_PEAK_TAPS]>,
+    channel: usize,
+    true_peak: f32,
+    sample_peak: f32,
+}
+
+impl TruePeakMeter {
+    fn new(channels: u16, sample_rate: u32) -> Self {
+        let channels = channels.max(1) as usize;
+        let phases: &'static [usize] = if sample_rate < 96_000 {
+            &[0, 1, 2, 3]
+        } else if sample_rate < 192_000 {
+            &[0, 2]
+        } else {
+            &[]
+        };
+        Self {
+            channels,
+            phases,
+            history: vec![[0.0; TRUE_PEAK_TAPS]; channels],
+            channel: 0,
+            true_peak: 0.0,
+            sample_peak: 0.0,
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, sample: f32) {
+        self.sample_peak = self.sample_peak.max(sample.abs());
+        self.true_peak = self.true_peak.max(sample.abs());
+
+        let history = &mut self.history[self.channel];
+        history.copy_within(..TRUE_PEAK_TAPS - 1, 1);
+        history[0] = sample;
+        for &phase in self.phases {
+            let value: f64 = (TRUE_PEAK_FILTER[phase].iter())
+                .zip(history.iter())
+                .map(|(&c, &x)| c * x as f64)
+                .sum();
+            self.true_peak = self.true_peak.max(value.abs() as f32);
+        }
+
+        self.channel += 1;
+        if self.channel == self.channels {
+            self.channel = 0;
+        }
+    }
+}
+
+/// 分析进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnalysisProgress {
+    /// 已分析的音频时长
+    pub analyzed: Duration,
+    /// 音频总时长，未知时为 `None`
+    pub total: Option<Duration>,
+}
+
+/// 响度分析结果
+///
+/// 音频过于安静（低于 -70 LUFS）或过短时，综合响度及响度范围为 `None`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoudnessAnalysis {
+    /// 综合响度（LUFS）
+    pub integrated: Option<f64>,
+    /// 响度范围（LU）
+    pub loudness_range: Option<f64>,
+    /// 瞬时响度（400ms 窗口，LUFS），每 100ms 一个值，静音时为负无穷
+    pub momentary: Vec<f64>,
+    /// 短期响度（3s 窗口，LUFS），每 100ms 一个值，静音时为负无穷
+    pub short_term: Vec<f64>,
+    /// 真峰值（1.0 为满幅）
+    pub true_peak: f32,
+    /// 采样峰值（1.0 为满幅）
+    pub sample_peak: f32,
+    /// 分析的音频时长
+    pub duration: Duration,
+}
+
+impl LoudnessAnalysis {
+    /// 真峰值（dBTP）
+    pub fn true_peak_db(&self) -> f32 {
+        20.0 * self.true_peak.log10()
+    }
+
+    /// 将综合响度调整到 ReplayGain 2.0 参考响度（-18 LUFS）所需的增益（分贝）
+    pub fn replay_gain(&self) -> Option<f32> {
+        self.integrated.map(|loudness| (-18.0 - loudness) as f32)
+    }
+}
+
+/// 响度分析器
+///
+/// # Example
+///
+/// ```no_run
+/// use remu_audio::loudness::LoudnessAnalyzer;
+///
+/// let mut analyzer = LoudnessAnalyzer::new();
+/// analyzer.set_progress_callback(|progress| println!("{:?}", progress.analyzed));
+/// let analysis = analyzer.analyze_file("input.flac").unwrap();
+/// println!(
+///     "{:?} LUFS, {:?} LU, {:.1} dBTP",
+///     analysis.integrated,
+///     analysis.loudness_range,
+///     analysis.true_peak_db()
+/// );
+/// ```
+#[derive(Clone, Default)]
+pub struct LoudnessAnalyzer {
+    progress_callback: Option<ProgressCallback>,
+}
+
+impl LoudnessAnalyzer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置进度回调，每分析约一秒音频及分析完成时调用
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(AnalysisProgress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Arc::new(callback));
+    }
+
+    /// 分析音频文件
+    pub fn analyze_file(&self, path: impl AsRef<Path>) -> Result<LoudnessAnalysis> {
+        let source = Decoder::try_from(File::open(path)?)?;
+        Ok(self.analyze(source))
+    }
+
+    /// 分析 Reader 中的音频
+    pub fn analyze_reader<R>(&self, reader: R) -> Result<LoudnessAnalysis>
+    where
+        R: Read + Seek + Send + Sync + 'static,
+    {
+        let source = Decoder::new(reader)?;
+        Ok(self.analyze(source))
+    }
+
+    /// 分析音频源，声道数及采样率以开始时为准
+    pub fn analyze<S: Source>(&self, mut source: S) -> LoudnessAnalysis {
+        let channels = source.channels().max(1);
+        let sample_rate = source.sample_rate().max(1);
+        let total = source.total_duration();
+        let mut meter = LoudnessMeter::new(channels, sample_rate);
+        let mut peak = TruePeakMeter::new(channels, sample_rate);
+
+        let samples_per_second = sample_rate as u64 * channels as u64;
+        let mut analyzed: u64 = 0;
+        let mut next_progress = PROGRESS_INTERVAL;
+        loop {
+            let mut count = 0;
+            for sample in source.by_ref().take(BLOCK_SAMPLES) {
+                meter.push(sample);
+                peak.push(sample);
+                count += 1;
+            }
+            if count == 0 {
+                break;
+            }
+            analyzed += count as u64;
+
+            let duration = Duration::from_secs_f64(analyzed as f64 / samples_per_second as f64);
+            if duration >= next_progress {
+                next_progress = duration + PROGRESS_INTERVAL;
+                self.emit_progress(AnalysisProgress {
+                    analyzed: duration,
+                    total,
+                });
+            }
+        }
+
+        let duration = Duration::from_secs_f64(analyzed as f64 / samples_per_second as f64);
+        self.emit_progress(AnalysisProgress {
+            analyzed: duration,
+            total,
+        });
+        LoudnessAnalysis {
+            integrated: meter.integrated(),
+            loudness_range: meter.loudness_range(),
+            momentary: meter.momentary(),
+            short_term: meter.short_term(),
+            true_peak: peak.true_peak,
+            sample_peak: peak.sample_peak,
+            duration,
+        }
+    }
+
+    fn emit_progress(&self, progress: AnalysisProgress) {
+        if let Some(ref cb) = self.progress_callback {
+            cb(progress);
+        }
+    }
+}
@@ -0,0 +1,258 @@
+use symphonia::core::meta::{MetadataRevision, StandardTagKey, Value};
+
+pub use symphonia::core::meta::StandardVisualKey;
+
+/// 音频元数据
+///
+/// 由解码器从容器及探测阶段读取到的标签中整理得到，未读取到的字段为 `None`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AudioMetadata {
+    /// 标题
+    pub title: Option<String>,
+    /// 艺术家
+    pub artist: Option<String>,
+    /// 专辑
+    pub album: Option<String>,
+    /// 专辑艺术家
+    pub album_artist: Option<String>,
+    /// 音轨号
+    pub track_number: Option<u32>,
+    /// 音轨总数
+    pub track_total: Option<u32>,
+    /// 碟片号
+    pub disc_number: Option<u32>,
+    /// 碟片总数
+    pub disc_total: Option<u32>,
+    /// 流派
+    pub genre: Option<String>,
+    /// 日期
+    pub date: Option<String>,
+    /// ISRC 编码
+    pub isrc: Option<String>,
+    /// 注释
+    pub comments: Vec<String>,
+    /// 内嵌图片（封面等）
+    pub pictures: Vec<Picture>,
+    /// 响度标准化信息
+    pub replay_gain: ReplayGain,
+    /// 原始标签（键, 值）
+    pub tags: Vec<(String, String)>,
+}
+
+/// 内嵌图片
+#[derive(Debug, Clone, PartialEq)]
+pub struct Picture {
+    /// 图片的 MIME 类型
+    pub mime_type: String,
+    /// 图片用途
+    pub usage: Option<StandardVisualKey>,
+    /// 图片数据
+    pub data: Vec<u8>,
+}
+
+/// 响度标准化信息，由 ReplayGain 或 R128 标签得到
+///
+/// 增益均换算为 ReplayGain 2.0 的参考响度（-18 LUFS），R128 标签以 -23 LUFS 为参考，读取时加上 5 dB
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ReplayGain {
+    /// 音轨增益（分贝）
+    pub track_gain: Option<f32>,
+    /// 音轨峰值（1.0 为满幅）
+    pub track_peak: Option<f32>,
+    /// 专辑增益（分贝）
+    pub album_gain: Option<f32>,
+    /// 专辑峰值（1.0 为满幅）
+    pub album_peak: Option<f32>,
+}
+
+impl ReplayGain {
+    /// 是否没有任何标签
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn merge(&mut self, other: ReplayGain) {
+        self.track_gain = other.track_gain.or(self.track_gain);
+        self.track_peak = other.track_peak.or(self.track_peak);
+        self.album_gain = other.album_gain.or(self.album_gain);
+        self.album_peak = other.album_peak.or(self.album_peak);
+    }
+
+    /// 按标签名读取未映射为标准标签的 ReplayGain 及 R128 标签
+    ///
+    /// 标签名不区分大小写，并忽略 `TXXX:`、`----:com.apple.iTunes:` 等前缀
+    fn read_tag(&mut self, key: &str, value: &str) {
+        let name = key.rsplit(':').next().unwrap_or(key).to_ascii_lowercase();
+        match name.as_str() {
+            "replaygain_track_gain" => self.track_gain = parse_gain(value),
+            "replaygain_track_peak" => self.track_peak = parse_peak(value),
+            "replaygain_album_gain" => self.album_gain = parse_gain(value),
+            "replaygain_album_peak" => self.album_peak = parse_peak(value),
+            "r128_track_gain" => self.track_gain = parse_r128_gain(value),
+            "r128_album_gain" => self.album_gain = parse_r128_gain(value),
+            _ => {}
+        }
+    }
+}
+
+impl AudioMetadata {
+    /// 从 Symphonia 的元数据修订中读取元数据
+    pub fn from_revision(revision: &MetadataRevision) -> Self {
+        let mut metadata = Self::default();
+
+        for tag in revision.tags() {
+            let value = tag.value.to_string();
+            metadata.tags.push((tag.key.clone(), value.clone()));
+
+            let Some(std_key) = tag.std_key else {
+                metadata.replay_gain.read_tag(&tag.key, &value);
+                continue;
+            };
+            match std_key {
+                StandardTagKey::TrackTitle => metadata.title = Some(value),
+                StandardTagKey::Artist => metadata.artist = Some(value),
+                StandardTagKey::Album => metadata.album = Some(value),
+                StandardTagKey::AlbumArtist => metadata.album_artist = Some(value),
+                StandardTagKey::Genre => metadata.genre = Some(value),
+                StandardTagKey::IdentIsrc => metadata.isrc = Some(value),
+                StandardTagKey::Comment => metadata.comments.push(value),
+                StandardTagKey::Date | StandardTagKey::ReleaseDate => metadata.date = Some(value),
+                StandardTagKey::OriginalDate => {
+                    metadata.date.get_or_insert(value);
+                }
+                StandardTagKey::TrackNumber => {
+                    let (number, total) = parse_number_pair(&tag.value);
+                    metadata.track_number = number.or(metadata.track_number);
+                    metadata.track_total = total.or(metadata.track_total);
+                }
+                StandardTagKey::TrackTotal => {
+                    metadata.track_total = parse_number_pair(&tag.value).0;
+                }
+                StandardTagKey::DiscNumber => {
+                    let (number, total) = parse_number_pair(&tag.value);
+                    metadata.disc_number = number.or(metadata.disc_number);
+                    metadata.disc_total = total.or(metadata.disc_total);
+                }
+                StandardTagKey::DiscTotal => {
+                    metadata.disc_total = parse_number_pair(&tag.value).0;
+                }
+                StandardTagKey::ReplayGainTrackGain => {
+                    metadata.replay_gain.track_gain = parse_gain(&value);
+                }
+                StandardTagKey::ReplayGainTrackPeak => {
+                    metadata.replay_gain.track_peak = parse_peak(&value);
+                }
+                StandardTagKey::ReplayGainAlbumGain => {
+                    metadata.replay_gain.album_gain = parse_gain(&value);
+                }
+                StandardTagKey::ReplayGainAlbumPeak => {
+                    metadata.replay_gain.album_peak = parse_peak(&value);
+                }
+                _ => {}
+            }
+        }
+
+        for visual in revision.visuals() {
+            metadata.pictures.push(Picture {
+                mime_type: visual.media_type.clone(),
+                usage: visual.usage,
+                data: visual.data.to_vec(),
+            });
+        }
+
+        metadata
+    }
+
+    /// 合并另一份元数据，`other` 中已有的字段会覆盖当前字段
+    pub fn merge(&mut self, other: AudioMetadata) {
+        macro_rules! overlay {
+            ($($field:ident),*) => {
+                $(if other.$field.is_some() {
+                    self.$field = other.$field;
+                })*
+            };
+        }
+        overlay!(
+            title,
+            artist,
+            album,
+            album_artist,
+            track_number,
+            track_total,
+            disc_number,
+            disc_total,
+            genre,
+            date,
+            isrc
+        );
+        if !other.comments.is_empty() {
+            self.comments = other.comments;
+        }
+        if !other.pictures.is_empty() {
+            self.pictures = other.pictures;
+        }
+        self.replay_gain.merge(other.replay_gain);
+        // 同名标签以新值为准，避免流内元数据反复更新时标签无限增长
+        self.tags
+            .retain(|(key, _)| !other.tags.iter().any(|(other_key, _)| other_key == key));
+        self.tags.extend(other.tags);
+    }
+
+    /// 是否没有任何元数据
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 获取封面图片，优先返回前封面
+    pub fn cover(&self) -> Option<&Picture> {
+        self.pictures
+            .iter()
+            .find(|p| p.usage == Some(StandardVisualKey::FrontCover))
+            .or_else(|| self.pictures.first())
+    }
+}
+
+/// 解析形如 `3` 或 `3/12` 的编号
+fn parse_number_pair(value: &Value) -> (Option<u32>, Option<u32>) {
+    match value {
+        Value::UnsignedInt(n) => (u32::try_from(*n).ok(), None),
+        Value::SignedInt(n) => (u32::try_from(*n).ok(), None),
+        Value::String(s) => {
+            let mut parts = s.splitn(2, '/');
+            let number = parts.next().and_then(|p| p.trim().parse().ok());
+            let total = parts.next().and_then(|p| p.trim().parse().ok());
+            (number, total)
+        }
+        _ => (None, None),
+    }
+}
+
+/// 解析形如 `-6.48 dB` 的增益
+fn parse_gain(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let value = match value.len().checked_sub(2) {
+        Some(end) if value.is_char_boundary(end) && value[end..].eq_ignore_ascii_case("db") => {
+            &value[..end]
+        }
+        _ => value,
+    };
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|gain: &f32| gain.is_finite())
+}
+
+fn parse_peak(value: &str) -> Option<f32> {
+    value
+        .trim()
+        .parse()
+        .ok()
+        .filter(|peak: &f32| peak.is_finite() && *peak > 0.0)
+}
+
+/// 解析 R128 增益（Q7.8 定点数，以 -23 LUFS 为参考），换算为 ReplayGain 增益
+fn parse_r128_gain(value: &str) -> Option<f32> {
+    let gain: i16 = value.trim().parse().ok()?;
+    Some(gain as f32 / 256.0 + 5.0)
+}
@@ -0,0 +1,302 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::f32::consts::FRAC_PI_2;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::looping::LoopSettings;
+
+/// 对数曲线的动态范围（分贝）
+const LOG_FADE_RANGE_DB: f32 = 60.0;
+
+/// 淡入淡出曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FadeCurve {
+    /// 线性
+    Linear,
+    /// 等功率，交叉淡化过程中总响度基本不变
+    #[default]
+    EqualPower,
+    /// 对数，音量按分贝均匀变化
+    Logarithmic,
+}
+
+impl FadeCurve {
+    /// 淡入进度为 `t`（0 ~ 1）时的增益，淡出时使用 `gain(1 - t)`
+    pub fn gain(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            FadeCurve::Linear => t,
+            FadeCurve::EqualPower => (t * FRAC_PI_2).sin(),
+            FadeCurve::Logarithmic => {
+                if t <= 0.0 {
+                    0.0
+                } else {
+                    10f32.powf(LOG_FADE_RANGE_DB * (t - 1.0) / 20.0)
+                }
+            }
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => FadeCurve::Linear,
+            2 => FadeCurve::Logarithmic,
+            _ => FadeCurve::EqualPower,
+        }
+    }
+
+    fn as_u8(&self) -> u8 {
+        match self {
+            FadeCurve::Linear => 0,
+            FadeCurve::EqualPower => 1,
+            FadeCurve::Logarithmic => 2,
+        }
+    }
+}
+
+/// 交叉淡化设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crossfade {
+    /// 淡化时长
+    pub duration: Duration,
+    /// 淡化曲线
+    pub curve: FadeCurve,
+}
+
+impl Crossfade {
+    pub fn new(duration: Duration, curve: FadeCurve) -> Self {
+        Self { duration, curve }
+    }
+}
+
+/// 播放线程中读取的交叉淡化设置
+#[derive(Default)]
+pub(crate) struct CrossfadeSettings {
+    /// 淡化时长（毫秒），0 表示关闭
+    duration_ms: AtomicU64,
+    curve: AtomicU8,
+}
+
+impl CrossfadeSettings {
+    pub fn get(&self) -> Option<Crossfade> {
+        let ms = self.duration_ms.load(Ordering::Relaxed);
+        if ms == 0 {
+            return None;
+        }
+        Some(Crossfade {
+            duration: Duration::from_millis(ms),
+            curve: FadeCurve::from_u8(self.curve.load(Ordering::Relaxed)),
+        })
+    }
+
+    pub fn set(&self, crossfade: Option<Crossfade>) {
+        match crossfade {
+            Some(crossfade) => {
+                self.curve.store(crossfade.curve.as_u8(), Ordering::Relaxed);
+                self.duration_ms
+                    .store(crossfade.duration.as_millis() as u64, Ordering::Relaxed);
+            }
+            None => self.duration_ms.store(0, Ordering::Relaxed),
+        }
+    }
+}
+
+/// 单个音轨的淡出控制
+#[derive(Default)]
+pub(crate) struct FadeControl {
+    /// 请求淡出的时长（毫秒），0 表示无请求
+    fade_out_ms: AtomicU64,
+    curve: AtomicU8,
+    /// 是否已经过自动淡出的位置
+    triggered: AtomicBool,
+    /// 下一音轨是否已就绪，到达自动淡出位置时可直接开始交叉淡化
+    armed: AtomicBool,
+}
+
+impl FadeControl {
+    /// 从当前位置开始淡出，淡出完毕后音轨结束
+    pub fn fade_out(&self, crossfade: Crossfade) {
+        self.triggered.store(true, Ordering::SeqCst);
+        self.curve.store(crossfade.curve.as_u8(), Ordering::Relaxed);
+        self.fade_out_ms.store(
+            crossfade.duration.as_millis().max(1) as u64,
+            Ordering::SeqCst,
+        );
+    }
+
+    pub fn triggered(&self) -> bool {
+        self.triggered.load(Ordering::SeqCst)
+    }
+
+    pub fn set_triggered(&self) {
+        self.triggered.store(true, Ordering::SeqCst);
+    }
+
+    /// 设置下一音轨是否已就绪
+    pub fn set_armed(&self, armed: bool) {
+        self.armed.store(armed, Ordering::SeqCst);
+    }
+
+    /// 取出下一音轨的就绪状态
+    pub fn take_armed(&self) -> bool {
+        self.armed.swap(false, Ordering::SeqCst)
+    }
+}
+
+/// 到达自动淡出位置时调用，返回是否已开始交叉淡化
+pub(crate) type FadeTrigger = Box<dyn Fn() -> bool + Send>;
+
+/// 淡入淡出音频源
+///
+/// 在音轨开头淡入；距离结尾不足交叉淡化时长时调用 `trigger`，
+/// 若已开始交叉淡化则淡出至结尾。也可通过 `FadeControl` 从当前位置淡出。
+pub(crate) struct FadeSource {
+    inner: Box<dyn Source + Send>,
+    control: Arc<FadeControl>,
+    settings: Arc<CrossfadeSettings>,
+    /// 循环期间音轨不会结束，不自动淡出
+    looping: Arc<LoopSettings>,
+    trigger: FadeTrigger,
+    /// 淡入的帧数及曲线
+    fade_in: Option<(u64, FadeCurve)>,
+    /// 淡出的起始帧、帧数及曲线
+    fade_out: Option<(u64, u64, FadeCurve)>,
+    /// 当前帧
+    frame: u64,
+    /// 当前帧中的声道
+    channel: ChannelCount,
+    /// 当前帧的增益
+    gain: f32,
+}
+
+impl FadeSource {
+    pub fn new(
+        inner: Box<dyn Source + Send>,
+        control: Arc<FadeControl>,
+        settings: Arc<CrossfadeSettings>,
+        looping: Arc<LoopSettings>,
+        fade_in: Option<Crossfade>,
+        trigger: FadeTrigger,
+    ) -> Self {
+        let fade_in = fade_in.map(|c| (to_frames(c.duration, inner.sample_rate()), c.curve));
+        Self {
+            inner,
+            control,
+            settings,
+            looping,
+            trigger,
+            fade_in,
+            fade_out: None,
+            frame: 0,
+            channel: 0,
+            gain: 1.0,
+        }
+    }
+
+    /// 在每帧开始时更新增益，淡出完毕时返回 `false`
+    fn update_gain(&mut self) -> bool {
+        let sample_rate = self.inner.sample_rate();
+
+        // 手动淡出
+        let fade_out_ms = self.control.fade_out_ms.swap(0, Ordering::SeqCst);
+        if fade_out_ms > 0 && self.fade_out.is_none() {
+            let curve = FadeCurve::from_u8(self.control.curve.load(Ordering::Relaxed));
+            let len = to_frames(Duration::from_millis(fade_out_ms), sample_rate);
+            self.fade_out = Some((self.frame, len, curve));
+        }
+
+        // 自动淡出
+        if self.fade_out.is_none() && !self.control.triggered() && !self.looping.active() {
+            if let (Some(crossfade), Some(total)) =
+                (self.settings.get(), self.inner.total_duration())
+            {
+                let total = to_frames(total, sample_rate);
+                let len = to_frames(crossfade.duration, sample_rate);
+                if self.frame + len >= total && (self.trigger)() {
+                    self.fade_out = Some((
+                        self.frame,
+                        total.saturating_sub(self.frame),
+                        crossfade.curve,
+                    ));
+                }
+            }
+        }
+
+        let mut gain = 1.0;
+        if let Some((len, curve)) = self.fade_in {
+            if self.frame < len {
+                gain *= curve.gain(self.frame as f32 / len as f32);
+            } else {
+                self.fade_in = None;
+            }
+        }
+        if let Some((start, len, curve)) = self.fade_out {
+            let progress = self.frame.saturating_sub(start);
+            if progress >= len {
+                return false;
+            }
+            gain *= curve.gain(1.0 - progress as f32 / len as f32);
+        }
+        self.gain = gain;
+        true
+    }
+}
+
+fn to_frames(duration: Duration, sample_rate: SampleRate) -> u64 {
+    (duration.as_secs_f64() * sample_rate as f64) as u64
+}
+
+impl Iterator for FadeSource {
+    type Item = rodio::Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 && !self.update_gain() {
+            return None;
+        }
+        let sample = self.inner.next()?;
+        self.channel += 1;
+        if self.channel >= self.inner.channels() {
+            self.channel = 0;
+            self.frame += 1;
+        }
+        Some(sample * self.gain)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl Source for FadeSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.frame = to_frames(pos, self.inner.sample_rate());
+        self.channel = 0;
+        self.fade_in = None;
+        Ok(())
+    }
+}
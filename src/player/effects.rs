@@ -0,0 +1,524 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// 每次处理的帧数
+const BLOCK_FRAMES: usize = 512;
+
+/// 音频效果节点
+///
+/// 每个音轨各自创建一组节点实例（交叉淡化时两个音轨同时处理），因此节点内部可以保存滤波器等状态；
+/// 节点实例在修改效果链的线程中创建，音频线程只切换到已创建好的效果链
+///
+/// # Example
+///
+/// ```no_run
+/// use remu_audio::player::{AudioEffect, Player};
+/// use rodio::{ChannelCount, SampleRate};
+///
+/// /// 交换左右声道
+/// struct SwapChannels;
+///
+/// impl AudioEffect for SwapChannels {
+///     fn process(&mut self, samples: &mut [f32], channels: ChannelCount, _: SampleRate) {
+///         if channels == 2 {
+///             samples.chunks_exact_mut(2).for_each(|frame| frame.swap(0, 1));
+///         }
+///     }
+/// }
+///
+/// let player = Player::new().unwrap();
+/// let effects = player.effects();
+/// let id = effects.push(|| SwapChannels);
+/// // 可在其他线程中修改
+/// std::thread::spawn(move || effects.set_bypassed(id, true));
+/// ```
+pub trait AudioEffect: Send {
+    /// 原地处理交错排列的采样，`samples` 的长度为 `channels` 的整数倍
+    ///
+    /// 在音频线程中调用，应避免阻塞
+    fn process(&mut self, samples: &mut [f32], channels: ChannelCount, sample_rate: SampleRate);
+
+    /// 跳转或取消旁路等导致音频不连续时调用，用于清除内部状态
+    fn reset(&mut self) {}
+}
+
+/// 效果节点的标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EffectId(u64);
+
+type EffectFactory = Arc<dyn Fn() -> Box<dyn AudioEffect> + Send + Sync + 'static>;
+
+#[derive(Clone)]
+struct EffectNode {
+    id: EffectId,
+    factory: EffectFactory,
+    bypassed: bool,
+}
+
+#[derive(Default)]
+struct ChainState {
+    nodes: Vec<EffectNode>,
+    next_id: u64,
+    /// 使用此效果链的音轨
+    tracks: Vec<Weak<Mutex<TrackSlot>>>,
+}
+
+impl ChainState {
+    /// 为每个音轨准备更新后的效果链
+    fn publish(&mut self) {
+        let nodes = &self.nodes;
+        self.tracks.retain(|track| match track.upgrade() {
+            Some(track) => {
+                track.lock().unwrap().prepare(nodes);
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+/// 控制线程为音轨准备好的节点
+struct PreparedNode {
+    id: EffectId,
+    bypassed: bool,
+    /// 音轨尚未拥有的节点的实例
+    effect: Option<Box<dyn AudioEffect>>,
+}
+
+/// 控制线程为音轨准备好的效果链
+#[derive(Default)]
+struct PreparedChain {
+    /// 按处理顺序排列的节点
+    nodes: Vec<PreparedNode>,
+    /// 预留了足够容量的节点实例列表，由音轨填充
+    effects: Vec<ActiveEffect>,
+}
+
+/// 控制线程与音轨之间交换效果链的位置
+#[derive(Default)]
+struct TrackSlot {
+    /// 已发送给音轨的节点
+    ids: Vec<EffectId>,
+    /// 尚未被音轨取走的效果链
+    pending: Option<PreparedChain>,
+    /// 音轨换下的效果链，在控制线程中释放
+    retired: Option<PreparedChain>,
+}
+
+impl TrackSlot {
+    /// 按效果链的最新设置准备节点，音轨尚未拥有的节点在此创建实例
+    fn prepare(&mut self, nodes: &[EffectNode]) {
+        self.retired = None;
+        // 音轨尚未取走的实例可继续使用
+        let mut prebuilt: Vec<_> = (self.pending.take())
+            .map(|chain| chain.nodes)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|node| Some((node.id, node.effect?)))
+            .collect();
+        let prepared = nodes
+            .iter()
+            .map(|node| {
+                let effect = match prebuilt.iter().position(|(id, _)| *id == node.id) {
+                    Some(index) => Some(prebuilt.swap_remove(index).1),
+                    None if self.ids.contains(&node.id) => None,
+                    None => Some((node.factory)()),
+                };
+                PreparedNode {
+                    id: node.id,
+                    bypassed: node.bypassed,
+                    effect,
+                }
+            })
+            .collect();
+        // 已移除的节点淡出期间仍在列表中
+        let capacity = nodes.len() + self.ids.len();
+        self.ids = nodes.iter().map(|node| node.id).collect();
+        self.pending = Some(PreparedChain {
+            nodes: prepared,
+            effects: Vec::with_capacity(capacity),
+        });
+    }
+}
+
+/// 效果链
+///
+/// 应用于播放器加载的所有音频（文件、网络音频、Reader 及 Source），位于均衡器之后；
+/// 克隆得到的是同一效果链的句柄，可在任意线程中添加、移除、重新排序及旁路节点，
+/// 修改对正在播放的音轨立即生效，添加、移除及旁路时短暂交叉淡化以免产生爆音
+#[derive(Clone, Default)]
+pub struct EffectChain {
+    state: Arc<Mutex<ChainState>>,
+}
+
+impl EffectChain {
+    /// 在末尾添加节点，`factory` 在调用线程中为每个音轨创建一个节点实例
+    pub fn push<F, E>(&self, factory: F) -> EffectId
+    where
+        F: Fn() -> E + Send + Sync + 'static,
+        E: AudioEffect + 'static,
+    {
+        self.insert(usize::MAX, factory)
+    }
+
+    /// 在指定位置插入节点，超出范围时添加到末尾
+    pub fn insert<F, E>(&self, index: usize, factory: F) -> EffectId
+    where
+        F: Fn() -> E + Send + Sync + 'static,
+        E: AudioEffect + 'static,
+    {
+        let factory: EffectFactory = Arc::new(move || Box::new(factory()));
+        self.modify(|state| {
+            let id = EffectId(state.next_id);
+            state.next_id += 1;
+            let index = index.min(state.nodes.len());
+            state.nodes.insert(
+                index,
+                EffectNode {
+                    id,
+                    factory,
+                    bypassed: false,
+                },
+            );
+            id
+        })
+    }
+
+    /// 移除节点，节点不存在时返回 `false`
+    pub fn remove(&self, id: EffectId) -> bool {
+        self.modify(|state| {
+            let len = state.nodes.len();
+            state.nodes.retain(|node| node.id != id);
+            state.nodes.len() != len
+        })
+    }
+
+    /// 将节点移动到指定位置，超出范围时移动到末尾；节点不存在时返回 `false`
+    pub fn move_to(&self, id: EffectId, index: usize) -> bool {
+        self.modify(|state| {
+            let Some(from) = state.nodes.iter().position(|node| node.id == id) else {
+                return false;
+            };
+            let node = state.nodes.remove(from);
+            let index = index.min(state.nodes.len());
+            state.nodes.insert(index, node);
+            true
+        })
+    }
+
+    /// 设置是否旁路节点，旁路的节点不处理音频；节点不存在时返回 `false`
+    pub fn set_bypassed(&self, id: EffectId, bypassed: bool) -> bool {
+        self.modify(|state| {
+            let Some(node) = state.nodes.iter_mut().find(|node| node.id == id) else {
+                return false;
+            };
+            node.bypassed = bypassed;
+            true
+        })
+    }
+
+    /// 节点是否被旁路，节点不存在时返回 `None`
+    pub fn is_bypassed(&self, id: EffectId) -> Option<bool> {
+        let state = self.state.lock().unwrap();
+        let node = state.nodes.iter().find(|node| node.id == id)?;
+        Some(node.bypassed)
+    }
+
+    /// 按处理顺序排列的节点
+    pub fn ids(&self) -> Vec<EffectId> {
+        let state = self.state.lock().unwrap();
+        state.nodes.iter().map(|node| node.id).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 移除所有节点
+    pub fn clear(&self) {
+        self.modify(|state| state.nodes.clear());
+    }
+
+    /// 当前效果链的副本，不再随原效果链的修改而变化
+    pub(crate) fn snapshot(&self) -> Self {
+        let state = self.state.lock().unwrap();
+        Self {
+            state: Arc::new(Mutex::new(ChainState {
+                nodes: state.nodes.clone(),
+                next_id: state.next_id,
+                tracks: Vec::new(),
+            })),
+        }
+    }
+
+    /// 添加使用此效果链的音轨
+    fn attach(&self) -> Arc<Mutex<TrackSlot>> {
+        let mut state = self.state.lock().unwrap();
+        let slot = Arc::new(Mutex::new(TrackSlot::default()));
+        slot.lock().unwrap().prepare(&state.nodes);
+        state.tracks.push(Arc::downgrade(&slot));
+        slot
+    }
+
+    fn modify<T>(&self, f: impl FnOnce(&mut ChainState) -> T) -> T {
+        let mut state = self.state.lock().unwrap();
+        let result = f(&mut state);
+        state.publish();
+        result
+    }
+}
+
+/// 音轨中的节点实例
+struct ActiveEffect {
+    id: EffectId,
+    effect: Box<dyn AudioEffect>,
+    bypassed: bool,
+    /// 已从效果链中移除，淡出后释放
+    removed: bool,
+    /// 处理后音频所占的比例，0 或 1 以外的值不会出现在两个块之间
+    mix: f32,
+}
+
+impl ActiveEffect {
+    fn target(&self) -> f32 {
+        if self.bypassed || self.removed {
+            0.0
+        } else {
+            1.0
+        }
+    }
+}
+
+/// 效果链音频源
+///
+/// 按块读取音频（不跨越音频源的 span）并依次交由各节点处理
+pub(crate) struct EffectSource {
+    inner: Box<dyn Source + Send>,
+    /// 接收控制线程准备好的效果链
+    slot: Arc<Mutex<TrackSlot>>,
+    effects: Vec<ActiveEffect>,
+    /// 已处理的采样
+    buffer: Vec<f32>,
+    /// 下一个输出的采样在 `buffer` 中的位置
+    pos: usize,
+    /// `buffer` 的末尾是否为音频源一个 span 的结尾
+    span_end: bool,
+    /// `buffer` 中音频的声道数及采样率
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    /// 交叉淡化时保存节点处理后的音频
+    scratch: Vec<f32>,
+}
+
+impl EffectSource {
+    pub fn new(inner: Box<dyn Source + Send>, chain: EffectChain) -> Self {
+        let channels = inner.channels();
+        let sample_rate = inner.sample_rate();
+        let mut source = Self {
+            inner,
+            slot: chain.attach(),
+            effects: Vec::new(),
+            buffer: Vec::new(),
+            pos: 0,
+            span_end: false,
+            channels,
+            sample_rate,
+            scratch: Vec::new(),
+        };
+        // 音轨开始时直接使用当前的效果链，无需淡入
+        source.sync();
+        for effect in source.effects.iter_mut() {
+            effect.mix = effect.target();
+        }
+        source
+    }
+
+    /// 切换到控制线程准备好的效果链
+    fn sync(&mut self) {
+        // 控制线程正在准备时留到下一块再切换，以免阻塞音频线程
+        let Ok(mut slot) = self.slot.try_lock() else {
+            return;
+        };
+        let Some(mut chain) = slot.pending.take() else {
+            return;
+        };
+
+        let mut previous = std::mem::take(&mut self.effects);
+        for node in chain.nodes.drain(..) {
+            let effect = match node.effect {
+                Some(effect) => ActiveEffect {
+                    id: node.id,
+                    effect,
+                    bypassed: node.bypassed,
+                    removed: false,
+                    mix: 0.0,
+                },
+                None => {
+                    let Some(index) = previous.iter().position(|e| e.id == node.id) else {
+                        continue;
+                    };
+                    let mut effect = previous.remove(index);
+                    effect.bypassed = node.bypassed;
+                    effect
+                }
+            };
+            chain.effects.push(effect);
+        }
+
+        // 已移除的节点淡出后再释放，其余的随换下的效果链在控制线程中释放
+        let mut index = 0;
+        while index < previous.len() {
+            if previous[index].mix > 0.0 {
+                let mut effect = previous.swap_remove(index);
+                effect.removed = true;
+                chain.effects.push(effect);
+            } else {
+                index += 1;
+            }
+        }
+        self.effects = std::mem::replace(&mut chain.effects, previous);
+        slot.retired = Some(chain);
+    }
+
+    /// 读取并处理下一块音频，音频源结束时返回 `false`
+    fn fill(&mut self) -> bool {
+        self.buffer.clear();
+        self.pos = 0;
+        self.channels = self.inner.channels().max(1);
+        self.sample_rate = self.inner.sample_rate();
+
+        let channels = self.channels as usize;
+        let mut len = BLOCK_FRAMES * channels;
+        self.span_end = false;
+        if let Some(span) = self.inner.current_span_len().filter(|&span| span > 0) {
+            if span <= len {
+                len = span;
+                self.span_end = true;
+            }
+        }
+        self.buffer.extend(self.inner.by_ref().take(len));
+        if self.buffer.is_empty() {
+            return false;
+        }
+
+        self.sync();
+        // 音频源结束时可能剩余不完整的帧，不做处理
+        let len = self.buffer.len() - self.buffer.len() % channels;
+        let samples = &mut self.buffer[..len];
+        let frames = len / channels;
+        for effect in self.effects.iter_mut() {
+            let target = effect.target();
+            if effect.mix == target {
+                if target > 0.0 && frames > 0 {
+                    effect
+                        .effect
+                        .process(samples, self.channels, self.sample_rate);
+                }
+                continue;
+            }
+
+            if effect.mix == 0.0 {
+                effect.effect.reset();
+            }
+            self.scratch.clear();
+            self.scratch.extend_from_slice(samples);
+            effect
+                .effect
+                .process(&mut self.scratch, self.channels, self.sample_rate);
+            for (frame, (dry, wet)) in samples
+                .chunks_exact_mut(channels)
+                .zip(self.scratch.chunks_exact(channels))
+                .enumerate()
+            {
+                let t = (frame + 1) as f32 / frames as f32;
+                let mix = effect.mix + (target - effect.mix) * t;
+                for (d, w) in dry.iter_mut().zip(wet) {
+                    *d += (w - *d) * mix;
+                }
+            }
+            effect.mix = target;
+        }
+        self.effects.retain(|effect| !effect.removed);
+        true
+    }
+}
+
+impl Iterator for EffectSource {
+    type Item = rodio::Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.buffer.len() && !self.fill() {
+            return None;
+        }
+        let sample = self.buffer[self.pos];
+        self.pos += 1;
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let buffered = self.buffer.len() - self.pos;
+        let (lower, upper) = self.inner.size_hint();
+        (
+            lower.saturating_add(buffered),
+            upper.and_then(|upper| upper.checked_add(buffered)),
+        )
+    }
+}
+
+impl Source for EffectSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        let buffered = self.buffer.len() - self.pos;
+        if buffered == 0 {
+            return self.inner.current_span_len();
+        }
+        if self.span_end {
+            return Some(buffered);
+        }
+        self.inner
+            .current_span_len()
+            .map(|remaining| remaining + buffered)
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        if self.pos < self.buffer.len() {
+            self.channels
+        } else {
+            self.inner.channels()
+        }
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        if self.pos < self.buffer.len() {
+            self.sample_rate
+        } else {
+            self.inner.sample_rate()
+        }
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        // 丢弃跳转前已处理的音频，并清除节点状态
+        self.buffer.clear();
+        self.pos = 0;
+        for effect in self.effects.iter_mut() {
+            effect.effect.reset();
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,611 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::f32::consts::PI;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+/// 10段图示均衡器的中心频率（ISO 标准倍频程）
+pub const GRAPHIC_FREQUENCIES: [f32; 10] = [
+    31.0, 62.0, 125.0, 250.0, 500.0, 1000.0, 2000.0, 4000.0, 8000.0, 16000.0,
+];
+/// 10段图示均衡器各频段的 Q 值（约一个倍频程的带宽）
+const GRAPHIC_Q: f32 = 1.41;
+/// 增益的范围（分贝）
+const MAX_GAIN_DB: f32 = 24.0;
+
+/// 每隔多少帧更新一次滤波器系数
+const UPDATE_FRAMES: u32 = 64;
+/// 每次更新时参数向目标值靠近的比例
+const SMOOTHING: f32 = 0.3;
+/// 开关均衡器及重建滤波器时交叉淡化的时长（毫秒）
+const MIX_RAMP_MS: f32 = 10.0;
+/// 滤波器最多处理的声道数，超出的声道不做处理
+const MAX_CHANNELS: usize = 8;
+
+/// 滤波器类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterType {
+    /// 峰值（钟形），提升或衰减中心频率附近
+    Peaking,
+    /// 低架，提升或衰减截止频率以下
+    LowShelf,
+    /// 高架，提升或衰减截止频率以上
+    HighShelf,
+    /// 低通
+    LowPass,
+    /// 高通
+    HighPass,
+    /// 陷波，滤除中心频率附近
+    Notch,
+}
+
+/// 均衡器的单个频段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqBand {
+    pub filter: FilterType,
+    /// 中心或截止频率（Hz）
+    pub frequency: f32,
+    /// 增益（分贝），仅对峰值及架式滤波器有效
+    pub gain: f32,
+    /// 品质因数，越大频带越窄
+    pub q: f32,
+}
+
+impl EqBand {
+    pub fn new(filter: FilterType, frequency: f32, gain: f32, q: f32) -> Self {
+        Self {
+            filter,
+            frequency,
+            gain,
+            q,
+        }
+    }
+
+    pub fn peaking(frequency: f32, gain: f32, q: f32) -> Self {
+        Self::new(FilterType::Peaking, frequency, gain, q)
+    }
+
+    pub fn low_shelf(frequency: f32, gain: f32) -> Self {
+        Self::new(FilterType::LowShelf, frequency, gain, 0.707)
+    }
+
+    pub fn high_shelf(frequency: f32, gain: f32) -> Self {
+        Self::new(FilterType::HighShelf, frequency, gain, 0.707)
+    }
+
+    pub fn low_pass(frequency: f32, q: f32) -> Self {
+        Self::new(FilterType::LowPass, frequency, 0.0, q)
+    }
+
+    pub fn high_pass(frequency: f32, q: f32) -> Self {
+        Self::new(FilterType::HighPass, frequency, 0.0, q)
+    }
+
+    pub fn notch(frequency: f32, q: f32) -> Self {
+        Self::new(FilterType::Notch, frequency, 0.0, q)
+    }
+
+    /// 按 RBJ Audio EQ Cookbook 计算双二阶滤波器的系数 `[b0, b1, b2, a1, a2]`（已归一化）
+    fn coefficients(&self, sample_rate: SampleRate) -> [f32; 5] {
+        let sample_rate = sample_rate as f32;
+        let frequency = self.frequency.clamp(10.0, sample_rate * 0.49);
+        let q = self.q.clamp(0.1, 20.0);
+        let gain = self.gain.clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+
+        let w0 = 2.0 * PI * frequency / sample_rate;
+        let (sin, cos) = w0.sin_cos();
+        let alpha = sin / (2.0 * q);
+        let a = 10f32.powf(gain / 40.0);
+        let sqrt_a = 2.0 * a.sqrt() * alpha;
+
+        let (b0, b1, b2, a0, a1, a2) = match self.filter {
+            FilterType::Peaking => (
+                1.0 + alpha * a,
+                -2.0 * cos,
+                1.0 - alpha * a,
+                1.0 + alpha / a,
+                -2.0 * cos,
+                1.0 - alpha / a,
+            ),
+            FilterType::LowShelf => (
+                a * ((a + 1.0) - (a - 1.0) * cos + sqrt_a),
+                2.0 * a * ((a - 1.0) - (a + 1.0) * cos),
+                a * ((a + 1.0) - (a - 1.0) * cos - sqrt_a),
+                (a + 1.0) + (a - 1.0) * cos + sqrt_a,
+                -2.0 * ((a - 1.0) + (a + 1.0) * cos),
+                (a + 1.0) + (a - 1.0) * cos - sqrt_a,
+            ),
+            FilterType::HighShelf => (
+                a * ((a + 1.0) + (a - 1.0) * cos + sqrt_a),
+                -2.0 * a * ((a - 1.0) + (a + 1.0) * cos),
+                a * ((a + 1.0) + (a - 1.0) * cos - sqrt_a),
+                (a + 1.0) - (a - 1.0) * cos + sqrt_a,
+                2.0 * ((a - 1.0) - (a + 1.0) * cos),
+                (a + 1.0) - (a - 1.0) * cos - sqrt_a,
+            ),
+            FilterType::LowPass => (
+                (1.0 - cos) / 2.0,
+                1.0 - cos,
+                (1.0 - cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            FilterType::HighPass => (
+                (1.0 + cos) / 2.0,
+                -(1.0 + cos),
+                (1.0 + cos) / 2.0,
+                1.0 + alpha,
+                -2.0 * cos,
+                1.0 - alpha,
+            ),
+            FilterType::Notch => (1.0, -2.0 * cos, 1.0, 1.0 + alpha, -2.0 * cos, 1.0 - alpha),
+        };
+        [b0 / a0, b1 / a0, b2 / a0, a1 / a0, a2 / a0]
+    }
+}
+
+/// 均衡器预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqPreset {
+    Flat,
+    Rock,
+    Pop,
+    Jazz,
+    Classical,
+    BassBoost,
+    TrebleBoost,
+    Vocal,
+}
+
+impl EqPreset {
+    /// 预设在10段图示均衡器上的增益（分贝）
+    pub fn gains(&self) -> [f32; 10] {
+        match self {
+            EqPreset::Flat => [0.0; 10],
+            EqPreset::Rock => [5.0, 4.0, 3.0, 1.0, -1.0, -1.0, 1.0, 3.0, 4.0, 5.0],
+            EqPreset::Pop => [-1.0, 2.0, 4.0, 5.0, 3.0, 0.0, -1.0, -1.0, -1.0, -1.0],
+            EqPreset::Jazz => [4.0, 3.0, 1.0, 2.0, -2.0, -2.0, 0.0, 2.0, 3.0, 4.0],
+            EqPreset::Classical => [5.0, 4.0, 3.0, 2.0, -1.0, -1.0, 0.0, 2.0, 3.0, 4.0],
+            EqPreset::BassBoost => [6.0, 5.0, 4.0, 2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
+            EqPreset::TrebleBoost => [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 4.0, 5.0, 6.0],
+            EqPreset::Vocal => [-2.0, -3.0, -3.0, 1.0, 4.0, 4.0, 3.0, 1.0, 0.0, -2.0],
+        }
+    }
+}
+
+/// 参数均衡器
+///
+/// # Example
+///
+/// ```no_run
+/// use remu_audio::player::{EqBand, EqPreset, Equalizer, Player};
+///
+/// let player = Player::new().unwrap();
+/// // 10段图示均衡器
+/// player.set_equalizer(Some(Equalizer::preset(EqPreset::Rock)));
+/// // 自定义频段
+/// player.set_equalizer(Some(Equalizer::new(vec![
+///     EqBand::high_pass(40.0, 0.707),
+///     EqBand::peaking(3000.0, -4.0, 2.0),
+///     EqBand::high_shelf(10000.0, 3.0),
+/// ])));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Equalizer {
+    pub bands: Vec<EqBand>,
+    /// 前置增益（分贝），用于避免提升频段后削波
+    pub preamp: f32,
+}
+
+impl Equalizer {
+    pub fn new(bands: Vec<EqBand>) -> Self {
+        Self { bands, preamp: 0.0 }
+    }
+
+    /// 10段图示均衡器，`gains` 依次为 [`GRAPHIC_FREQUENCIES`] 各频段的增益（分贝）
+    pub fn graphic(gains: [f32; 10]) -> Self {
+        let bands = GRAPHIC_FREQUENCIES
+            .iter()
+            .zip(gains)
+            .map(|(&frequency, gain)| EqBand::peaking(frequency, gain, GRAPHIC_Q))
+            .collect();
+        Self::new(bands)
+    }
+
+    /// 加载预设
+    pub fn preset(preset: EqPreset) -> Self {
+        Self::graphic(preset.gains())
+    }
+}
+
+/// 播放器及各音轨共享的均衡器设置
+///
+/// 修改时在调用线程中为各音轨准备好目标设置及新的滤波器，音频线程只切换到准备好的设置
+#[derive(Default)]
+pub(crate) struct EqualizerSettings {
+    state: Mutex<SettingsState>,
+}
+
+#[derive(Default)]
+struct SettingsState {
+    equalizer: Option<Equalizer>,
+    /// 使用此设置的音轨
+    tracks: Vec<Weak<Mutex<EqualizerSlot>>>,
+}
+
+impl SettingsState {
+    /// 为每个音轨准备更新后的设置
+    fn publish(&mut self) {
+        let equalizer = &self.equalizer;
+        self.tracks.retain(|track| match track.upgrade() {
+            Some(track) => {
+                track.lock().unwrap().prepare(equalizer);
+                true
+            }
+            None => false,
+        });
+    }
+}
+
+impl EqualizerSettings {
+    pub fn get(&self) -> Option<Equalizer> {
+        self.state.lock().unwrap().equalizer.clone()
+    }
+
+    pub fn set(&self, equalizer: Option<Equalizer>) {
+        let mut state = self.state.lock().unwrap();
+        state.equalizer = equalizer;
+        state.publish();
+    }
+
+    /// 修改均衡器，未启用均衡器时返回 `false`
+    pub fn update<F: FnOnce(&mut Equalizer)>(&self, f: F) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(equalizer) = state.equalizer.as_mut() else {
+            return false;
+        };
+        f(equalizer);
+        state.publish();
+        true
+    }
+
+    /// 当前设置的副本，不再随播放器的设置变化
+    pub fn snapshot(&self) -> Self {
+        Self {
+            state: Mutex::new(SettingsState {
+                equalizer: self.get(),
+                tracks: Vec::new(),
+            }),
+        }
+    }
+
+    /// 添加使用此设置的音轨
+    fn attach(&self) -> Arc<Mutex<EqualizerSlot>> {
+        let mut state = self.state.lock().unwrap();
+        let slot = Arc::new(Mutex::new(EqualizerSlot::default()));
+        slot.lock().unwrap().prepare(&state.equalizer);
+        state.tracks.push(Arc::downgrade(&slot));
+        slot
+    }
+}
+
+/// 控制线程为音轨准备好的设置
+struct PreparedEqualizer {
+    target: Option<Equalizer>,
+    /// 频段数量或类型变化时新建的滤波器
+    filters: Option<Vec<BandFilter>>,
+}
+
+/// 控制线程与音轨之间交换设置的位置
+#[derive(Default)]
+struct EqualizerSlot {
+    /// 已发送给音轨的滤波器的类型
+    layout: Vec<FilterType>,
+    /// 尚未被音轨取走的设置
+    pending: Option<PreparedEqualizer>,
+    /// 音轨换下的设置及滤波器，在控制线程中释放
+    retired: Option<PreparedEqualizer>,
+}
+
+impl EqualizerSlot {
+    fn prepare(&mut self, equalizer: &Option<Equalizer>) {
+        self.retired = None;
+        // 音轨尚未取走的滤波器可继续使用
+        let prebuilt = self.pending.take().and_then(|prepared| prepared.filters);
+        let filters = match equalizer {
+            Some(equalizer)
+                if !self
+                    .layout
+                    .iter()
+                    .copied()
+                    .eq(equalizer.bands.iter().map(|band| band.filter)) =>
+            {
+                self.layout = equalizer.bands.iter().map(|band| band.filter).collect();
+                Some(
+                    equalizer
+                        .bands
+                        .iter()
+                        .map(|&band| BandFilter::new(band))
+                        .collect(),
+                )
+            }
+            _ => prebuilt,
+        };
+        self.pending = Some(PreparedEqualizer {
+            target: equalizer.clone(),
+            filters,
+        });
+    }
+}
+
+/// 单个频段的滤波器
+struct BandFilter {
+    /// 当前（平滑过渡中）的参数
+    band: EqBand,
+    coefficients: [f32; 5],
+    /// 各声道的状态（直接II型转置）
+    state: [[f32; 2]; MAX_CHANNELS],
+}
+
+impl BandFilter {
+    /// 创建滤波器，系数在音轨切换到此滤波器时按音轨的采样率计算
+    fn new(band: EqBand) -> Self {
+        Self {
+            band,
+            coefficients: [1.0, 0.0, 0.0, 0.0, 0.0],
+            state: [[0.0; 2]; MAX_CHANNELS],
+        }
+    }
+
+    /// 向目标参数靠近一步，返回是否仍在过渡中
+    fn approach(&mut self, target: &EqBand, sample_rate: SampleRate) -> bool {
+        if self.band == *target {
+            return false;
+        }
+        let close = (self.band.gain - target.gain).abs() < 0.01
+            && (self.band.frequency / target.frequency).ln().abs() < 0.001
+            && (self.band.q / target.q).ln().abs() < 0.001;
+        if close {
+            self.band = *target;
+        } else {
+            // 频率及 Q 值在对数域中过渡
+            let log_step =
+                |from: f32, to: f32| (from.ln() + (to.ln() - from.ln()) * SMOOTHING).exp();
+            self.band.gain += (target.gain - self.band.gain) * SMOOTHING;
+            self.band.frequency = log_step(self.band.frequency, target.frequency);
+            self.band.q = log_step(self.band.q, target.q);
+        }
+        self.coefficients = self.band.coefficients(sample_rate);
+        true
+    }
+
+    #[inline]
+    fn process(&mut self, channel: usize, x: f32) -> f32 {
+        let [b0, b1, b2, a1, a2] = self.coefficients;
+        let Some(state) = self.state.get_mut(channel) else {
+            return x;
+        };
+        let y = b0 * x + state[0];
+        state[0] = b1 * x - a1 * y + state[1];
+        state[1] = b2 * x - a2 * y;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.state.iter_mut().for_each(|s| *s = [0.0; 2]);
+    }
+}
+
+/// 均衡器音频源
+///
+/// 参数变化时滤波器系数在数毫秒内平滑过渡，开关均衡器时在原始与处理后的音频之间交叉淡化，
+/// 频段数量或类型变化时在新旧滤波器之间交叉淡化，以免产生爆音
+pub(crate) struct EqualizerSource {
+    inner: Box<dyn Source + Send>,
+    /// 接收控制线程准备好的设置
+    slot: Arc<Mutex<EqualizerSlot>>,
+    /// 目标设置
+    target: Option<Equalizer>,
+    filters: Vec<BandFilter>,
+    /// 重建前的滤波器，淡出期间与新的滤波器同时处理
+    fading: Vec<BandFilter>,
+    /// 重建前的滤波器所占的比例
+    fade: f32,
+    /// 当前的前置增益（线性）
+    preamp: f32,
+    /// 处理后音频所占的比例，0 时跳过处理
+    mix: f32,
+    /// 每个采样 `mix` 的变化量
+    mix_step: f32,
+    /// 参数是否仍在过渡中
+    transitioning: bool,
+    channel: usize,
+    /// 距下次更新系数的帧数
+    countdown: u32,
+    sample_rate: SampleRate,
+}
+
+impl EqualizerSource {
+    pub fn new(inner: Box<dyn Source + Send>, settings: Arc<EqualizerSettings>) -> Self {
+        let sample_rate = inner.sample_rate().max(1);
+        let mut source = Self {
+            inner,
+            slot: settings.attach(),
+            target: None,
+            filters: Vec::new(),
+            fading: Vec::new(),
+            fade: 0.0,
+            preamp: 1.0,
+            mix: 0.0,
+            mix_step: 1.0 / (MIX_RAMP_MS / 1000.0 * sample_rate as f32).max(1.0),
+            transitioning: false,
+            channel: 0,
+            countdown: 0,
+            sample_rate,
+        };
+        // 音轨开始时直接使用当前设置，无需过渡
+        source.update();
+        if let Some(ref target) = source.target {
+            source.preamp = db_to_linear(target.preamp);
+            source.mix = 1.0;
+        }
+        source
+    }
+
+    /// 切换到控制线程准备好的设置
+    fn sync(&mut self) {
+        // 控制线程正在准备时留到下次更新再切换，以免阻塞音频线程
+        let Ok(mut slot) = self.slot.try_lock() else {
+            return;
+        };
+        let Some(mut prepared) = slot.pending.take() else {
+            return;
+        };
+
+        std::mem::swap(&mut self.target, &mut prepared.target);
+        if let Some(mut filters) = prepared.filters.take() {
+            for filter in filters.iter_mut() {
+                filter.coefficients = filter.band.coefficients(self.sample_rate);
+            }
+            if self.mix > 0.0 && !self.filters.is_empty() {
+                // 新旧滤波器交叉淡化
+                std::mem::swap(&mut self.fading, &mut self.filters);
+                self.fade = 1.0;
+            }
+            prepared.filters = Some(std::mem::replace(&mut self.filters, filters));
+        }
+        // 换下的设置及滤波器在控制线程中释放
+        slot.retired = Some(prepared);
+        self.transitioning = true;
+    }
+
+    /// 读取最新的设置并更新滤波器
+    fn update(&mut self) {
+        self.sync();
+        let sample_rate = self.inner.sample_rate().max(1);
+        if sample_rate != self.sample_rate {
+            self.sample_rate = sample_rate;
+            self.transitioning = true;
+            for filter in self.filters.iter_mut().chain(self.fading.iter_mut()) {
+                filter.coefficients = filter.band.coefficients(sample_rate);
+            }
+        }
+        if !self.transitioning {
+            return;
+        }
+
+        let Some(ref target) = self.target else {
+            self.transitioning = self.mix > 0.0;
+            return;
+        };
+
+        let mut transitioning = false;
+        for (filter, band) in self.filters.iter_mut().zip(&target.bands) {
+            if self.mix <= 0.0 {
+                // 未启用时直接使用目标参数
+                filter.band = *band;
+                filter.coefficients = band.coefficients(self.sample_rate);
+                filter.reset();
+            } else {
+                transitioning |= filter.approach(band, self.sample_rate);
+            }
+        }
+
+        let preamp = db_to_linear(target.preamp);
+        if (self.preamp - preamp).abs() > 1e-4 {
+            self.preamp += (preamp - self.preamp) * SMOOTHING;
+            transitioning = true;
+        } else {
+            self.preamp = preamp;
+        }
+        self.transitioning = transitioning || self.mix < 1.0;
+    }
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db.clamp(-MAX_GAIN_DB, MAX_GAIN_DB) / 20.0)
+}
+
+impl Iterator for EqualizerSource {
+    type Item = rodio::Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            if self.countdown == 0 {
+                self.countdown = UPDATE_FRAMES;
+                self.update();
+            }
+            self.countdown -= 1;
+            let target = if self.target.is_some() { 1.0 } else { 0.0 };
+            if self.mix < target {
+                self.mix = (self.mix + self.mix_step).min(1.0);
+            } else if self.mix > target {
+                self.mix = (self.mix - self.mix_step).max(0.0);
+            }
+            if self.fade > 0.0 {
+                self.fade = (self.fade - self.mix_step).max(0.0);
+            }
+        }
+
+        let sample = self.inner.next()?;
+        let channel = self.channel;
+        self.channel += 1;
+        if self.channel >= self.inner.channels().max(1) as usize {
+            self.channel = 0;
+        }
+
+        if self.mix <= 0.0 || (self.filters.is_empty() && self.fade <= 0.0) {
+            return Some(sample);
+        }
+        let mut wet = sample * self.preamp;
+        for filter in self.filters.iter_mut() {
+            wet = filter.process(channel, wet);
+        }
+        if self.fade > 0.0 {
+            let mut old = sample * self.preamp;
+            for filter in self.fading.iter_mut() {
+                old = filter.process(channel, old);
+            }
+            wet += (old - wet) * self.fade;
+        }
+        Some(sample + (wet - sample) * self.mix)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl Source for EqualizerSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        // 跳转后音频不连续，清除滤波器状态
+        self.filters.iter_mut().for_each(|f| f.reset());
+        self.fade = 0.0;
+        self.channel = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use super::rate::MediaClock;
+
+/// 循环方式
+#[derive(Debug, Clone, Default, PartialEq)]
+struct LoopState {
+    /// 循环区间（媒体时间）
+    region: Option<Range<Duration>>,
+    /// 是否单曲循环
+    repeat_track: bool,
+}
+
+/// 音轨尚未取走的循环设置
+type LoopSlot = Mutex<Option<LoopState>>;
+
+#[derive(Debug, Default)]
+struct SettingsState {
+    state: LoopState,
+    /// 使用此设置的音轨
+    tracks: Vec<Weak<LoopSlot>>,
+}
+
+/// 播放器及各音轨共享的循环设置
+///
+/// 修改时将新的设置发送给各音轨，音频线程不等待锁
+#[derive(Debug, Default)]
+pub(crate) struct LoopSettings {
+    state: Mutex<SettingsState>,
+    /// 是否设置了任意一种循环，供交叉淡化在每帧读取
+    active: AtomicBool,
+    /// 设置循环或切换音轨后已循环的次数
+    count: AtomicU64,
+}
+
+impl LoopSettings {
+    fn update(&self, f: impl FnOnce(&mut LoopState)) {
+        let mut settings = self.state.lock().unwrap();
+        let SettingsState { state, tracks } = &mut *settings;
+        f(state);
+        self.active.store(
+            state.region.is_some() || state.repeat_track,
+            Ordering::SeqCst,
+        );
+        tracks.retain(|track| match track.upgrade() {
+            Some(track) => {
+                *track.lock().unwrap() = Some(state.clone());
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// 添加使用此设置的音轨
+    fn attach(&self) -> Arc<LoopSlot> {
+        let mut settings = self.state.lock().unwrap();
+        let slot = Arc::new(Mutex::new(Some(settings.state.clone())));
+        settings.tracks.push(Arc::downgrade(&slot));
+        slot
+    }
+
+    /// 设置或清除循环区间，同时重置循环次数
+    pub fn set_region(&self, region: Option<Range<Duration>>) {
+        self.update(|state| state.region = region);
+        self.count.store(0, Ordering::SeqCst);
+    }
+
+    pub fn region(&self) -> Option<Range<Duration>> {
+        self.state.lock().unwrap().state.region.clone()
+    }
+
+    pub fn set_repeat_track(&self, repeat_track: bool) {
+        self.update(|state| state.repeat_track = repeat_track);
+    }
+
+    pub fn repeat_track(&self) -> bool {
+        self.state.lock().unwrap().state.repeat_track
+    }
+
+    pub fn active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    /// 切换音轨时清除循环区间（区间只对设置时的音轨有意义），保留单曲循环
+    pub fn reset_track(&self) {
+        if self.region().is_some() {
+            self.set_region(None);
+        }
+        self.count.store(0, Ordering::SeqCst);
+    }
+}
+
+/// 循环音频源，位于变速源之后
+///
+/// 到达循环区间的结尾（或单曲循环时到达音轨结尾）时，将整条处理链跳转到循环的起点，
+/// 由解码器的精确跳转定位到采样，媒体时钟随之更新，因此 `position()` 始终为媒体时间；
+/// 音频源不支持跳转时不再循环
+pub(crate) struct LoopSource {
+    inner: Box<dyn Source + Send>,
+    settings: Arc<LoopSettings>,
+    clock: Arc<MediaClock>,
+    /// 接收新的设置
+    slot: Arc<LoopSlot>,
+    state: LoopState,
+    /// 下一个采样所属的声道
+    channel: usize,
+    /// 是否已跳转失败
+    unsupported: bool,
+}
+
+impl LoopSource {
+    pub fn new(
+        inner: Box<dyn Source + Send>,
+        settings: Arc<LoopSettings>,
+        clock: Arc<MediaClock>,
+    ) -> Self {
+        Self {
+            inner,
+            slot: settings.attach(),
+            settings,
+            clock,
+            state: LoopState::default(),
+            channel: 0,
+            unsupported: false,
+        }
+    }
+
+    /// 跳转到循环的起点，失败时不再循环
+    fn jump(&mut self, position: Duration) -> bool {
+        if self.inner.try_seek(position).is_err() {
+            self.unsupported = true;
+            return false;
+        }
+        self.settings.count.fetch_add(1, Ordering::SeqCst);
+        true
+    }
+
+    /// 音轨结束时的循环起点
+    fn restart_position(&self) -> Option<Duration> {
+        match self.state.region {
+            Some(ref region) => Some(region.start),
+            None => self.state.repeat_track.then_some(Duration::ZERO),
+        }
+    }
+}
+
+impl Iterator for LoopSource {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            // 控制线程正在修改时留到下一帧再读取
+            if let Ok(mut slot) = self.slot.try_lock() {
+                if let Some(state) = slot.take() {
+                    self.state = state;
+                }
+            }
+        }
+
+        let mut sample = match self.inner.next() {
+            Some(sample) => sample,
+            None => {
+                if self.channel != 0 || self.unsupported {
+                    return None;
+                }
+                let position = self.restart_position()?;
+                if !self.jump(position) {
+                    return None;
+                }
+                // 循环起点位于音轨结尾之后时结束
+                self.inner.next()?
+            }
+        };
+
+        // 变速源在输出每帧的首个采样时更新媒体时钟
+        if self.channel == 0 && !self.unsupported {
+            if let Some(Range { start, end }) = self.state.region {
+                if self.clock.position() >= end && self.jump(start) {
+                    sample = self.inner.next()?;
+                }
+            }
+        }
+
+        self.channel += 1;
+        if self.channel >= self.inner.channels().max(1) as usize {
+            self.channel = 0;
+        }
+        Some(sample)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl Source for LoopSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.channel = 0;
+        Ok(())
+    }
+}
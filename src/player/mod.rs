@@ -0,0 +1,987 @@
+use rodio::mixer::Mixer;
+use rodio::Sink;
+use rodio::Source;
+use std::io::{Read, Seek};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+use crate::error::{Error, Result};
+use crate::events::PlayerEvent;
+use crate::loader::cache::DiskCache;
+use crate::loader::{LoaderEvent, LoaderOptions, RetryPolicy};
+use crate::render::Renderer;
+
+mod crossfade;
+mod effects;
+mod equalizer;
+mod looping;
+mod normalization;
+mod output;
+mod prefetch;
+mod queue;
+mod rate;
+mod track;
+mod visualizer;
+
+use crossfade::CrossfadeSettings;
+use effects::EffectSource;
+use equalizer::{EqualizerSettings, EqualizerSource};
+use looping::LoopSettings;
+use normalization::{NormalizationSettings, NormalizationSource};
+use output::OutputHandle;
+use queue::{EventSender, Queue, QueueContext, QueueEvent, QueueItem};
+use rate::{MediaClock, RateSettings, TimeStretch};
+use track::TrackSource;
+
+pub use crate::metadata::AudioMetadata;
+pub use crossfade::{Crossfade, FadeCurve};
+pub use effects::{AudioEffect, EffectChain, EffectId};
+pub use equalizer::{EqBand, EqPreset, Equalizer, FilterType, GRAPHIC_FREQUENCIES};
+pub use normalization::{Normalization, NormalizationMode};
+pub use output::{output_devices, NullOutput, Output, OutputDevice};
+pub use prefetch::PrefetchSource;
+pub use track::ReadSeek;
+pub use visualizer::{
+    ChannelLevel, Spectrum, SpectrumOptions, Visualizer, WindowFunction, MAX_FFT_SIZE,
+};
+
+/// 默认的缓冲阈值
+const DEFAULT_BUFFER_THRESHOLD: Duration = Duration::from_millis(500);
+
+type PlayerCallback = Arc<RwLock<Option<Box<dyn Fn(PlayerEvent) + Send + Sync + 'static>>>>;
+type LoaderCallback = Arc<RwLock<Option<Box<dyn Fn(LoaderEvent) + Send + Sync + 'static>>>>;
+
+pub trait PlaybackControl {
+    fn play(&self);
+    fn pause(&self);
+    fn seek(&self, position: Duration) -> Result<(), rodio::source::SeekError>;
+    fn set_volume(&self, volume: f32);
+    /// 设置播放速率（0.25 ~ 4.0），播放位置仍为媒体时间
+    fn set_playback_rate(&self, rate: f32);
+    /// 设置变速时是否保持音高（默认保持）
+    fn set_preserves_pitch(&self, preserves_pitch: bool);
+    /// 设置音高偏移（-24 ~ 24 个半音），不改变播放速率
+    fn set_pitch(&self, semitones: f32);
+
+    fn paused(&self) -> bool;
+    fn duration(&self) -> Option<Duration>;
+    fn position(&self) -> Duration;
+    fn volume(&self) -> f32;
+    fn playback_rate(&self) -> f32;
+    fn preserves_pitch(&self) -> bool;
+    fn pitch(&self) -> f32;
+}
+
+pub struct PlayerControl {
+    /// 跳转需等待播放线程执行，调用方应先复制Sink再释放锁
+    sink: Arc<Sink>,
+    // 时长及元数据可能在播放线程中随音轨切换而更新，因此单独加锁，
+    // 以免播放线程等待 PlayerControl 的写锁
+    duration: Mutex<Option<Duration>>,
+    metadata: Mutex<Option<AudioMetadata>>,
+    /// 交叉淡化时正在淡出的上一音轨所在的Sink
+    fading: Mutex<Option<Arc<Sink>>>,
+    /// 播放速率设置
+    rate: Arc<RateSettings>,
+    /// 均衡器设置
+    equalizer: Arc<EqualizerSettings>,
+    /// 循环设置
+    looping: Arc<LoopSettings>,
+    /// 当前Sink的媒体时间
+    clock: Arc<MediaClock>,
+}
+
+impl PlayerControl {
+    fn new(
+        sink: Sink,
+        rate: Arc<RateSettings>,
+        equalizer: Arc<EqualizerSettings>,
+        looping: Arc<LoopSettings>,
+    ) -> Self {
+        sink.set_speed(rate.rate());
+        Self {
+            sink: Arc::new(sink),
+            clock: Arc::new(MediaClock::default()),
+            duration: Mutex::new(None),
+            metadata: Mutex::new(None),
+            fading: Mutex::new(None),
+            rate,
+            equalizer,
+            looping,
+        }
+    }
+
+    fn stop(&self) {
+        self.sink.stop();
+        if let Some(fading) = self.fading.lock().unwrap().take() {
+            fading.stop();
+        }
+    }
+
+    /// 获取当前音频的元数据
+    pub fn metadata(&self) -> Option<AudioMetadata> {
+        self.metadata.lock().unwrap().clone()
+    }
+
+    /// 设置均衡器，为 `None` 时关闭均衡器
+    ///
+    /// 对正在播放的音轨立即生效，参数在数毫秒内平滑过渡
+    pub fn set_equalizer(&self, equalizer: Option<Equalizer>) {
+        self.equalizer.set(equalizer);
+    }
+
+    pub fn equalizer(&self) -> Option<Equalizer> {
+        self.equalizer.get()
+    }
+
+    /// 修改均衡器的单个频段，未启用均衡器或频段不存在时返回 `false`
+    pub fn set_equalizer_band(&self, index: usize, band: EqBand) -> bool {
+        let mut found = false;
+        self.equalizer.update(|equalizer| {
+            if let Some(b) = equalizer.bands.get_mut(index) {
+                *b = band;
+                found = true;
+            }
+        });
+        found
+    }
+
+    /// 设置 A-B 循环区间，播放到 `end` 时回到 `start`，按采样精确跳转
+    ///
+    /// 跳转到 `end` 之后时立即回到 `start`；`end` 超出音轨时长时在音轨结尾处循环。
+    /// 区间只对当前音轨有效，切换音轨时清除；循环期间不会结束播放，也不会交叉淡化到下一音轨
+    pub fn set_loop_region(&self, start: Duration, end: Duration) -> Result<()> {
+        if start >= end {
+            return Err(Error::invalid_input("Loop start must be before loop end"));
+        }
+        self.looping.set_region(Some(start..end));
+        Ok(())
+    }
+
+    /// 清除循环区间，继续播放到音轨结尾
+    pub fn clear_loop(&self) {
+        self.looping.set_region(None);
+    }
+
+    pub fn loop_region(&self) -> Option<Range<Duration>> {
+        self.looping.region()
+    }
+
+    /// 设置单曲循环，到达音轨结尾时从头播放；设置在切换音轨后保持不变
+    ///
+    /// 同时设置了循环区间时，以循环区间为准
+    pub fn set_repeat_track(&self, repeat_track: bool) {
+        self.looping.set_repeat_track(repeat_track);
+    }
+
+    pub fn repeat_track(&self) -> bool {
+        self.looping.repeat_track()
+    }
+
+    /// 设置循环区间或切换音轨后已循环的次数
+    pub fn loop_count(&self) -> u64 {
+        self.looping.count()
+    }
+}
+
+/// 跳转到指定的媒体时间，阻塞直到播放线程执行跳转
+fn seek_sink(sink: &Sink, position: Duration) -> Result<(), rodio::source::SeekError> {
+    // Sink 的变速会按速率缩放跳转位置，需预先还原为媒体时间
+    sink.try_seek(position.div_f32(sink.speed()))
+}
+
+impl PlaybackControl for PlayerControl {
+    fn play(&self) {
+        self.sink.play();
+        if let Some(ref fading) = *self.fading.lock().unwrap() {
+            fading.play();
+        }
+    }
+
+    fn pause(&self) {
+        self.sink.pause();
+        if let Some(ref fading) = *self.fading.lock().unwrap() {
+            fading.pause();
+        }
+    }
+
+    fn seek(&self, position: Duration) -> Result<(), rodio::source::SeekError> {
+        seek_sink(&self.sink, position)
+    }
+
+    fn set_volume(&self, volume: f32) {
+        self.sink.set_volume(volume);
+        if let Some(ref fading) = *self.fading.lock().unwrap() {
+            fading.set_volume(volume);
+        }
+    }
+
+    fn set_playback_rate(&self, rate: f32) {
+        // 速率由 Sink 以重采样的方式实现，音高由各音轨的变速源补偿
+        let rate = self.rate.set_rate(rate);
+        self.sink.set_speed(rate);
+        if let Some(ref fading) = *self.fading.lock().unwrap() {
+            fading.set_speed(rate);
+        }
+    }
+
+    fn set_preserves_pitch(&self, preserves_pitch: bool) {
+        self.rate.set_preserves_pitch(preserves_pitch);
+    }
+
+    fn set_pitch(&self, semitones: f32) {
+        self.rate.set_pitch(semitones);
+    }
+
+    fn paused(&self) -> bool {
+        self.sink.is_paused()
+    }
+
+    fn position(&self) -> Duration {
+        self.clock.position()
+    }
+
+    fn volume(&self) -> f32 {
+        self.sink.volume()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        *self.duration.lock().unwrap()
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.rate.rate()
+    }
+
+    fn preserves_pitch(&self) -> bool {
+        self.rate.preserves_pitch()
+    }
+
+    fn pitch(&self) -> f32 {
+        self.rate.pitch()
+    }
+}
+
+pub struct Player {
+    /// 音频输出
+    output: OutputHandle,
+    control: Arc<RwLock<PlayerControl>>,
+    /// 播放队列
+    queue: Arc<Mutex<Queue>>,
+    /// 回调函数
+    callback: PlayerCallback,
+    loader_callback: LoaderCallback,
+    empty: Arc<AtomicBool>,
+    ended: Arc<AtomicBool>,
+    autoplay: Arc<AtomicBool>,
+    /// 交叉淡化设置
+    crossfade: Arc<CrossfadeSettings>,
+    /// 网络音频的磁盘缓存
+    cache: Arc<RwLock<Option<DiskCache>>>,
+    /// 网络音频下载中断时的重试策略
+    retry_policy: Arc<RwLock<RetryPolicy>>,
+    /// 队列工作线程的事件发送端
+    events: EventSender,
+    /// 等待数据后恢复播放前需要缓冲的时长
+    buffer_threshold: Arc<RwLock<Duration>>,
+    /// 网络音频预解码的时长
+    prefetch: Arc<RwLock<Option<Duration>>>,
+    /// 当前音轨是否正在等待数据
+    waiting: Arc<AtomicBool>,
+    /// 播放速率设置
+    rate: Arc<RateSettings>,
+    /// 均衡器设置
+    equalizer: Arc<EqualizerSettings>,
+    /// 效果链
+    effects: EffectChain,
+    /// 响度标准化设置
+    normalization: Arc<NormalizationSettings>,
+    /// 循环设置
+    looping: Arc<LoopSettings>,
+}
+
+impl PlaybackControl for Player {
+    fn play(&self) {
+        self.control.read().unwrap().play();
+        self.autoplay.store(true, Ordering::SeqCst);
+        self.emit(PlayerEvent::Play);
+        // 数据不足时，在缓冲完成后发送 Playing
+        if !self.empty() && !self.waiting.load(Ordering::SeqCst) {
+            self.emit(PlayerEvent::Playing);
+        }
+    }
+
+    fn pause(&self) {
+        self.control.read().unwrap().pause();
+        self.autoplay.store(false, Ordering::SeqCst);
+        self.emit(PlayerEvent::Pause);
+    }
+
+    fn seek(&self, position: Duration) -> Result<(), rodio::source::SeekError> {
+        self.emit(PlayerEvent::Seeking);
+        // 等待跳转期间不持有控制器的锁
+        let sink = self.control.read().unwrap().sink.clone();
+        let seek_result = seek_sink(&sink, position);
+        if seek_result.is_ok() {
+            // 丢弃跳转前的可视化数据
+            self.output.tap().clear();
+            self.emit(PlayerEvent::Seeked);
+        }
+        seek_result
+    }
+
+    fn set_volume(&self, volume: f32) {
+        self.control.read().unwrap().set_volume(volume);
+        self.emit(PlayerEvent::VolumeChange);
+    }
+
+    fn set_playback_rate(&self, rate: f32) {
+        self.control.read().unwrap().set_playback_rate(rate);
+        self.emit(PlayerEvent::RateChange);
+    }
+
+    fn set_preserves_pitch(&self, preserves_pitch: bool) {
+        self.control
+            .read()
+            .unwrap()
+            .set_preserves_pitch(preserves_pitch);
+    }
+
+    fn set_pitch(&self, semitones: f32) {
+        self.control.read().unwrap().set_pitch(semitones);
+    }
+
+    fn paused(&self) -> bool {
+        self.control.read().unwrap().paused()
+    }
+
+    fn position(&self) -> Duration {
+        self.control.read().unwrap().position()
+    }
+
+    fn volume(&self) -> f32 {
+        self.control.read().unwrap().volume()
+    }
+
+    fn duration(&self) -> Option<Duration> {
+        self.control.read().unwrap().duration()
+    }
+
+    fn playback_rate(&self) -> f32 {
+        self.control.read().unwrap().playback_rate()
+    }
+
+    fn preserves_pitch(&self) -> bool {
+        self.control.read().unwrap().preserves_pitch()
+    }
+
+    fn pitch(&self) -> f32 {
+        self.control.read().unwrap().pitch()
+    }
+}
+
+impl Player {
+    /// 使用系统默认的音频设备创建播放器
+    pub fn new() -> Result<Self> {
+        Self::with_output(Output::Default)
+    }
+
+    /// 使用指定的音频输出创建播放器
+    ///
+    /// 没有音频设备时（如 CI 及容器中）可使用 [`Output::Null`]，播放、暂停、跳转及结束事件均正常触发
+    pub fn with_output(output: Output) -> Result<Self> {
+        // 创建输出流和sink
+        let callback: PlayerCallback = Arc::new(RwLock::new(None));
+        let output = OutputHandle::open(output, callback.clone())?;
+        let sink = Sink::connect_new(output.mixer());
+        sink.pause();
+
+        let queue = Arc::new(Mutex::new(Queue::default()));
+        let rate = Arc::new(RateSettings::default());
+        let equalizer = Arc::new(EqualizerSettings::default());
+        let looping = Arc::new(LoopSettings::default());
+        let waiting = Arc::new(AtomicBool::new(false));
+        let (events, receiver) = mpsc::channel();
+
+        let player = Self {
+            output,
+            control: Arc::new(RwLock::new(PlayerControl::new(
+                sink,
+                rate.clone(),
+                equalizer.clone(),
+                looping.clone(),
+            ))),
+            queue,
+            callback,
+            loader_callback: Arc::new(RwLock::new(None)),
+            empty: Arc::new(AtomicBool::new(true)),
+            ended: Arc::new(AtomicBool::new(false)),
+            autoplay: Arc::new(AtomicBool::new(false)),
+            crossfade: Arc::new(CrossfadeSettings::default()),
+            cache: Arc::new(RwLock::new(None)),
+            retry_policy: Arc::new(RwLock::new(RetryPolicy::default())),
+            events,
+            buffer_threshold: Arc::new(RwLock::new(DEFAULT_BUFFER_THRESHOLD)),
+            prefetch: Arc::new(RwLock::new(None)),
+            waiting,
+            rate,
+            equalizer,
+            effects: EffectChain::default(),
+            normalization: Arc::new(NormalizationSettings::default()),
+            looping,
+        };
+        queue::spawn_worker(receiver, player.context());
+        Ok(player)
+    }
+
+    /// 切换音频输出
+    ///
+    /// 当前音频、播放位置、暂停状态及音量均保持不变，切换成功后发送 [`PlayerEvent::DeviceChange`]；
+    /// 新的输出打开失败时仍使用原输出
+    pub fn set_output(&mut self, output: Output) -> Result<()> {
+        self.output.switch(output, self.callback.clone())?;
+        self.emit(PlayerEvent::DeviceChange {
+            name: self.output.name(),
+        });
+        Ok(())
+    }
+
+    /// 当前输出设备的名称，空输出或自定义输出流时返回 `None`
+    pub fn device_name(&self) -> Option<String> {
+        self.output.name()
+    }
+
+    fn context(&self) -> QueueContext {
+        QueueContext {
+            queue: self.queue.clone(),
+            control: self.control.clone(),
+            callback: self.callback.clone(),
+            loader_callback: self.loader_callback.clone(),
+            ended: self.ended.clone(),
+            crossfade: self.crossfade.clone(),
+            mixer: self.output.mixer().clone(),
+            cache: self.cache.clone(),
+            retry_policy: self.retry_policy.clone(),
+            events: self.events.clone(),
+            waiting: self.waiting.clone(),
+            buffer_threshold: self.buffer_threshold.clone(),
+            prefetch: self.prefetch.clone(),
+            rate: self.rate.clone(),
+            equalizer: self.equalizer.clone(),
+            effects: self.effects.clone(),
+            normalization: self.normalization.clone(),
+            looping: self.looping.clone(),
+        }
+    }
+
+    /// 当前是否应以交叉淡化的方式切换音轨
+    fn active_crossfade(&self) -> Option<Crossfade> {
+        if self.empty() || self.ended() || self.paused() {
+            return None;
+        }
+        self.crossfade.get()
+    }
+
+    /// 清空队列，并以指定音频作为唯一的队列项加载
+    async fn load(&mut self, item: QueueItem) -> Result<()> {
+        // 清空相关绑定
+        if !self.empty() {
+            self.clear();
+        }
+        self.queue.lock().unwrap().clear();
+        let index = self.queue.lock().unwrap().push(item);
+        self.load_index(index).await
+    }
+
+    /** 加载队列中的指定项 */
+    async fn load_index(&mut self, index: usize) -> Result<()> {
+        if let Some(crossfade) = self.active_crossfade() {
+            return self.crossfade_index(index, crossfade).await;
+        }
+
+        // 已预加载的音轨可直接使用
+        let ready = self.queue.lock().unwrap().take_ready(index);
+
+        // 清空相关绑定
+        if !self.empty() {
+            self.clear();
+        }
+
+        let context = self.context();
+        self.emit(PlayerEvent::LoadStart);
+        let (id, track) = match ready {
+            Some(ready) => self.queue.lock().unwrap().select_ready(ready),
+            None => {
+                let (id, source, handle) = self.queue.lock().unwrap().select(index)?;
+                let future = context.open(id, source);
+                // 网络音频需在加入队列时所在的运行时中加载
+                let result = match handle {
+                    Some(handle) if Handle::try_current().is_err() => handle.block_on(future),
+                    _ => future.await,
+                };
+                match result {
+                    Ok(track) => (id, track),
+                    Err(e) => {
+                        self.emit(PlayerEvent::Error { error: e.clone() });
+                        return Err(e);
+                    }
+                }
+            }
+        };
+        self.empty.store(false, Ordering::SeqCst);
+
+        // 更新时长及元数据
+        {
+            let control = self.control.read().unwrap();
+            *control.duration.lock().unwrap() = track.duration;
+            // 加载期间可能已收到流内元数据（如ICY），应以其为准
+            let mut current = control.metadata.lock().unwrap();
+            *current = match (track.metadata.clone(), current.take()) {
+                (Some(mut metadata), Some(stream_metadata)) => {
+                    metadata.merge(stream_metadata);
+                    Some(metadata)
+                }
+                (metadata, stream_metadata) => metadata.or(stream_metadata),
+            };
+        }
+        self.emit(PlayerEvent::TrackChange { index });
+        self.emit(PlayerEvent::DurationChange);
+        self.emit(PlayerEvent::LoadedMetadata);
+        self.emit(PlayerEvent::LoadedData);
+        if !self.paused() {
+            self.emit(PlayerEvent::Playing);
+        }
+
+        // 加载Source
+        {
+            let mut queue = self.queue.lock().unwrap();
+            context.append(&mut queue, id, index, track);
+        }
+
+        // 预加载下一项，以便无缝切换
+        context.preload_next();
+
+        Ok(())
+    }
+
+    /** 以交叉淡化的方式切换到队列中的指定项 */
+    async fn crossfade_index(&mut self, index: usize, crossfade: Crossfade) -> Result<()> {
+        let context = self.context();
+        let ready = self.queue.lock().unwrap().take_ready(index);
+        let ready = match ready {
+            Some(ready) => ready,
+            None => {
+                let (id, source, handle) = self.queue.lock().unwrap().prepare(index)?;
+
+                self.emit(PlayerEvent::LoadStart);
+                let future = context.open(id, source);
+                let result = match handle {
+                    Some(handle) if Handle::try_current().is_err() => handle.block_on(future),
+                    _ => future.await,
+                };
+                match result {
+                    Ok(track) => queue::ReadyTrack::new(id, index, track),
+                    Err(e) => {
+                        self.emit(PlayerEvent::Error { error: e.clone() });
+                        return Err(e);
+                    }
+                }
+            }
+        };
+
+        // 音轨切换事件在新音轨开始播放时发送
+        context.crossfade_to(ready, crossfade);
+        Ok(())
+    }
+
+    // 加载本地音频文件
+    pub async fn load_file(&mut self, file_path: &str) -> Result<()> {
+        self.load(QueueItem::File(file_path.into())).await
+    }
+
+    /// 使用指定的请求选项（请求头、认证、代理、超时等）从URL加载音频
+    pub async fn load_url(&mut self, url: &str, options: impl Into<LoaderOptions>) -> Result<()> {
+        let options = Arc::new(options.into());
+        let item = QueueItem::Url(url.into(), options, Handle::try_current().ok());
+        self.load(item).await
+    }
+
+    // 从Reader加载音频
+    pub fn load_reader<R>(&mut self, reader: R) -> Result<()>
+    where
+        R: Read + Seek + Send + Sync + 'static,
+    {
+        let item = QueueItem::Once(Some(TrackSource::Reader(Box::new(reader))));
+        futures::executor::block_on(self.load(item))
+    }
+
+    // 从Source加载音频
+    pub fn load_source(&mut self, source: impl Source + Send + 'static) -> Result<()> {
+        let item = QueueItem::Once(Some(TrackSource::Source(Box::new(source))));
+        futures::executor::block_on(self.load(item))
+    }
+
+    /// 将音频加入队列末尾，若当前没有加载音频则直接加载
+    async fn enqueue(&mut self, item: QueueItem) -> Result<()> {
+        let index = self.queue.lock().unwrap().push(item);
+        if self.empty() {
+            return self.load_index(index).await;
+        }
+        // 若新加入的是下一项，则立即开始预加载
+        self.context().preload_next();
+        Ok(())
+    }
+
+    /// 将本地音频文件加入播放队列
+    pub async fn enqueue_file(&mut self, file_path: &str) -> Result<()> {
+        self.enqueue(QueueItem::File(file_path.into())).await
+    }
+
+    /// 使用指定的请求选项将网络音频加入播放队列
+    pub async fn enqueue_url(
+        &mut self,
+        url: &str,
+        options: impl Into<LoaderOptions>,
+    ) -> Result<()> {
+        let options = Arc::new(options.into());
+        let item = QueueItem::Url(url.into(), options, Handle::try_current().ok());
+        self.enqueue(item).await
+    }
+
+    /// 将Reader加入播放队列，Reader只能播放一次，无法通过 `previous` 回到该项
+    pub fn enqueue_reader<R>(&mut self, reader: R) -> Result<()>
+    where
+        R: Read + Seek + Send + Sync + 'static,
+    {
+        let item = QueueItem::Once(Some(TrackSource::Reader(Box::new(reader))));
+        futures::executor::block_on(self.enqueue(item))
+    }
+
+    /// 播放队列中的下一项
+    pub async fn next(&mut self) -> Result<()> {
+        let (index, ready) = {
+            let queue = self.queue.lock().unwrap();
+            let index = queue.current().map_or(0, |current| current + 1);
+            if index >= queue.len() {
+                return Err(Error::invalid_input("No next item in queue"));
+            }
+            (index, queue.next_ready())
+        };
+
+        if ready {
+            // 下一项已预加载到Sink中，跳过当前音轨即可
+            self.control.read().unwrap().sink.skip_one();
+            return Ok(());
+        }
+        self.load_index(index).await
+    }
+
+    /// 播放队列中的上一项
+    pub async fn previous(&mut self) -> Result<()> {
+        let index = match self.queue.lock().unwrap().current() {
+            Some(current) if current > 0 => current - 1,
+            _ => return Err(Error::invalid_input("No previous item in queue")),
+        };
+        self.load_index(index).await
+    }
+
+    /// 清空播放队列，当前播放的音频不受影响
+    pub fn clear_queue(&mut self) {
+        self.queue.lock().unwrap().clear_except_current();
+    }
+
+    /// 获取播放队列的长度
+    pub fn queue_len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    /// 获取当前播放项在队列中的索引
+    pub fn current_index(&self) -> Option<usize> {
+        self.queue.lock().unwrap().current()
+    }
+
+    /// 设置切换音轨时的交叉淡化，`None` 表示关闭（默认）
+    ///
+    /// 开启后，自动切换至下一音轨及手动切换音轨时，当前音轨淡出的同时下一音轨淡入。
+    /// 自动切换需要当前音轨的时长已知，否则仍为无缝切换。
+    pub fn set_crossfade(&self, crossfade: Option<Crossfade>) {
+        let crossfade = crossfade.filter(|c| !c.duration.is_zero());
+        self.crossfade.set(crossfade);
+        if crossfade.is_none() {
+            // 等待交叉淡化的音轨改为无缝切换
+            self.context().flush_ready();
+        }
+    }
+
+    /// 获取交叉淡化设置
+    pub fn crossfade(&self) -> Option<Crossfade> {
+        self.crossfade.get()
+    }
+
+    /// 设置网络音频的磁盘缓存，`None` 表示不使用缓存（默认）
+    ///
+    /// 已缓存的音频无需联网即可直接打开，未缓存完整的音频会从已缓存的位置继续下载
+    pub fn set_cache(&self, cache: Option<DiskCache>) {
+        *self.cache.write().unwrap() = cache;
+    }
+
+    /// 设置网络音频下载中断时的重试策略，对之后加载的音频生效
+    ///
+    /// 连接中断后按策略重新连接并从中断的位置继续下载，重试均失败时才结束播放。
+    /// HLS 播放列表及分片的请求失败时同样按策略重试
+    pub fn set_retry_policy(&self, policy: RetryPolicy) {
+        *self.retry_policy.write().unwrap() = policy;
+    }
+
+    /// 获取网络音频下载中断时的重试策略
+    pub fn retry_policy(&self) -> RetryPolicy {
+        *self.retry_policy.read().unwrap()
+    }
+
+    /// 设置网络音频等待数据后恢复播放前需要缓冲的时长，对之后加载的音频生效
+    ///
+    /// 播放追上下载进度时发送 `Waiting` 事件，缓冲达到该时长后才恢复播放并发送 `Playing` 事件，
+    /// 以免在下载速度接近播放速度时频繁切换状态
+    pub fn set_buffer_threshold(&self, threshold: Duration) {
+        *self.buffer_threshold.write().unwrap() = threshold;
+    }
+
+    /// 获取网络音频等待数据后恢复播放前需要缓冲的时长
+    pub fn buffer_threshold(&self) -> Duration {
+        *self.buffer_threshold.read().unwrap()
+    }
+
+    /// 设置网络音频预解码的时长，为 `None` 时不预解码（默认），对之后加载的音频生效
+    ///
+    /// 启用后网络音频在 tokio 阻塞线程池的线程中解码并预先缓冲该时长的采样，播放线程不会因等待网络数据而阻塞；
+    /// 加载及跳转立即返回，缓冲区为空时输出静音，播放位置照常前进
+    pub fn set_prefetch(&self, buffer: Option<Duration>) {
+        *self.prefetch.write().unwrap() = buffer;
+    }
+
+    /// 获取网络音频预解码的时长
+    pub fn prefetch(&self) -> Option<Duration> {
+        *self.prefetch.read().unwrap()
+    }
+
+    pub fn mixer(&self) -> &Mixer {
+        self.output.mixer()
+    }
+    pub fn control(&self) -> Arc<RwLock<PlayerControl>> {
+        self.control.clone()
+    }
+
+    /// 获取当前音频已缓冲的时间范围，类似 HTML 媒体元素的 `buffered` 属性
+    ///
+    /// 网络音频按平均码率将已下载的字节范围换算为时间，本地音频视为完整缓冲；
+    /// 时长未知（如直播流）时返回空列表
+    pub fn buffered(&self) -> Vec<Range<Duration>> {
+        self.queue.lock().unwrap().buffered()
+    }
+
+    /// 设置均衡器，为 `None` 时关闭均衡器
+    ///
+    /// 对正在播放的音轨立即生效，参数在数毫秒内平滑过渡；设置在切换音轨后保持不变
+    pub fn set_equalizer(&self, equalizer: Option<Equalizer>) {
+        self.control.read().unwrap().set_equalizer(equalizer);
+    }
+
+    pub fn equalizer(&self) -> Option<Equalizer> {
+        self.control.read().unwrap().equalizer()
+    }
+
+    /// 修改均衡器的单个频段，未启用均衡器或频段不存在时返回 `false`
+    pub fn set_equalizer_band(&self, index: usize, band: EqBand) -> bool {
+        self.control.read().unwrap().set_equalizer_band(index, band)
+    }
+
+    /// 设置 A-B 循环区间，详见 [`PlayerControl::set_loop_region`]
+    pub fn set_loop_region(&self, start: Duration, end: Duration) -> Result<()> {
+        self.control.read().unwrap().set_loop_region(start, end)
+    }
+
+    pub fn clear_loop(&self) {
+        self.control.read().unwrap().clear_loop();
+    }
+
+    pub fn loop_region(&self) -> Option<Range<Duration>> {
+        self.control.read().unwrap().loop_region()
+    }
+
+    /// 设置单曲循环，设置在切换音轨后保持不变
+    pub fn set_repeat_track(&self, repeat_track: bool) {
+        self.control.read().unwrap().set_repeat_track(repeat_track);
+    }
+
+    pub fn repeat_track(&self) -> bool {
+        self.control.read().unwrap().repeat_track()
+    }
+
+    /// 设置循环区间或切换音轨后已循环的次数
+    pub fn loop_count(&self) -> u64 {
+        self.control.read().unwrap().loop_count()
+    }
+
+    /// 设置响度标准化
+    ///
+    /// 按音频的 ReplayGain 或 R128 标签调整音量，对正在播放的音轨立即生效，增益变化平滑过渡
+    pub fn set_normalization(&self, normalization: Normalization) {
+        self.normalization.set(normalization);
+    }
+
+    pub fn normalization(&self) -> Normalization {
+        self.normalization.get()
+    }
+
+    /// 获取效果链的句柄，可在其他线程中添加、移除、重新排序及旁路效果节点
+    ///
+    /// 效果链应用于所有加载的音频，位于均衡器之后，修改对正在播放的音轨立即生效
+    pub fn effects(&self) -> EffectChain {
+        self.effects.clone()
+    }
+
+    /// 获取输出音频的可视化数据，可在其他线程中读取电平、波形及频谱
+    ///
+    /// 切换输出设备后仍然有效
+    pub fn visualizer(&self) -> Visualizer {
+        Visualizer::new(self.output.tap().clone())
+    }
+
+    /// 将播放队列离线渲染到文件，应用当前响度标准化、均衡器、效果链、音量、播放速率及音高
+    ///
+    /// 以尽可能快的速度依次解码队列中的音频，不经过音频输出；
+    /// 通过 Reader 或 Source 加载的一次性音频无法再次读取，会被跳过
+    pub async fn render_to_file(&self, path: impl AsRef<Path>, renderer: Renderer) -> Result<()> {
+        let sources = self.queue.lock().unwrap().render_sources();
+        if sources.is_empty() {
+            return Err(Error::invalid_input("No audio to render"));
+        }
+
+        let mut context = self.context();
+        // 离线渲染快于实时，不预解码，以免缓冲区耗尽时将静音写入文件
+        context.prefetch = Arc::new(RwLock::new(None));
+        let volume = self.volume();
+        // 渲染过程中不受播放器设置变化的影响
+        let rate = Arc::new(self.rate.snapshot());
+        let equalizer = Arc::new(self.equalizer.snapshot());
+        let effects = self.effects.snapshot();
+        let normalization = Arc::new(self.normalization.snapshot());
+        let mut tracks: Vec<Box<dyn Source + Send>> = Vec::new();
+        let mut streaming = Vec::new();
+        for source in sources {
+            let track = context.open(queue::RENDER_ID, source).await?;
+            let clock = Arc::new(MediaClock::default());
+            let replay_gain = track.metadata.map(|m| m.replay_gain).unwrap_or_default();
+            let source = NormalizationSource::new(track.source, normalization.clone(), replay_gain);
+            let source = EqualizerSource::new(Box::new(source), equalizer.clone());
+            let source = EffectSource::new(Box::new(source), effects.clone());
+            let source = TimeStretch::new(Box::new(source), rate.clone(), clock);
+            tracks.push(Box::new(source.speed(rate.rate()).amplify(volume)));
+            streaming.extend(track.streaming);
+        }
+
+        let path = path.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            // 渲染完成前保留网络音频的下载资源
+            let _streaming = streaming;
+            renderer.render_all(tracks, path)
+        })
+        .await
+        .map_err(|_| Error::Cancelled)?
+    }
+
+    /// 获取当前音频的元数据，未加载音频或音频源不提供元数据时返回 `None`
+    pub fn metadata(&self) -> Option<AudioMetadata> {
+        self.control.read().unwrap().metadata()
+    }
+
+    pub fn set_callback<F>(&self, callback: F)
+    where
+        F: Fn(PlayerEvent) + Send + Sync + 'static,
+    {
+        let mut cb = self.callback.write().unwrap();
+        *cb = Some(Box::new(callback));
+    }
+
+    pub fn set_loader_callback<F>(&self, callback: F)
+    where
+        F: Fn(LoaderEvent) + Send + Sync + 'static,
+    {
+        let mut cb = self.loader_callback.write().unwrap();
+        *cb = Some(Box::new(callback));
+    }
+
+    fn emit(&self, event: PlayerEvent) {
+        if let Some(ref cb) = *self.callback.read().unwrap() {
+            cb(event);
+        }
+    }
+
+    /// 清空播放状态及播放队列
+    pub fn stop(&mut self) {
+        self.clear();
+        self.queue.lock().unwrap().clear();
+    }
+
+    /// 强制清除正在播放的资源
+    fn clear(&mut self) {
+        // 释放已加载的音轨（同时通知Reader取消读取，以免造成阻塞）
+        let mut queue = self.queue.lock().unwrap();
+        queue.reset();
+
+        // 停止播放
+        self.control.read().unwrap().stop();
+        self.output.tap().clear();
+
+        // 重置控制器，保留音量
+        let mut control = self.control.write().unwrap();
+        let previous_duration = control.duration.lock().unwrap().take();
+        let sink = Sink::connect_new(self.output.mixer());
+        sink.set_volume(control.sink.volume());
+        if !(self.autoplay.load(Ordering::SeqCst)) {
+            sink.pause();
+        }
+        *control = PlayerControl::new(
+            sink,
+            self.rate.clone(),
+            self.equalizer.clone(),
+            self.looping.clone(),
+        );
+        self.looping.reset_track();
+        drop(control);
+        drop(queue);
+
+        self.ended.store(false, Ordering::SeqCst);
+        self.waiting.store(false, Ordering::SeqCst);
+
+        if !self.empty() {
+            // 标记为已清空，发送清空事件
+            self.empty.store(true, Ordering::SeqCst);
+            self.emit(PlayerEvent::Emptied);
+        }
+
+        // 如果之前有播放时长，发送时长变化事件
+        if previous_duration.is_some() {
+            self.emit(PlayerEvent::DurationChange);
+        }
+    }
+
+    fn empty(&self) -> bool {
+        self.empty.load(Ordering::SeqCst)
+    }
+
+    pub fn ended(&self) -> bool {
+        self.ended.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for Player {
+    fn drop(&mut self) {
+        self.stop();
+        let _ = self.events.send(QueueEvent::Shutdown);
+    }
+}
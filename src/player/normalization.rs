@@ -0,0 +1,277 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::loudness::LoudnessMeter;
+use crate::metadata::ReplayGain;
+
+/// ReplayGain 2.0 的参考响度（LUFS）
+const REFERENCE_LOUDNESS: f64 = -18.0;
+/// 实时分析至少测量多长的音频后才开始调整增益
+const MIN_ANALYSIS: Duration = Duration::from_secs(3);
+/// 每隔多少帧重新计算一次增益
+const UPDATE_FRAMES: u32 = 2048;
+/// 增益变化的过渡时长（毫秒）
+const RAMP_MS: f32 = 200.0;
+/// 增益的上限（分贝）
+const MAX_GAIN_DB: f32 = 24.0;
+
+/// 响度标准化模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NormalizationMode {
+    /// 不调整音量
+    #[default]
+    Off,
+    /// 使用音轨增益
+    Track,
+    /// 使用专辑增益，保持专辑内音轨间的响度差异；没有专辑增益时使用音轨增益
+    Album,
+}
+
+/// 响度标准化设置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Normalization {
+    pub mode: NormalizationMode,
+    /// 前置增益（分贝），在标签给出的增益之上额外调整
+    pub preamp: f32,
+    /// 根据峰值限制增益，避免削波
+    pub prevent_clipping: bool,
+    /// 没有 ReplayGain 或 R128 标签时，在播放过程中按 EBU R128 测量响度并估算增益
+    ///
+    /// 测量满数秒后才开始调整，此后随测量结果平滑变化；专辑模式下按音轨估算
+    pub analyze: bool,
+}
+
+impl Default for Normalization {
+    fn default() -> Self {
+        Self {
+            mode: NormalizationMode::Off,
+            preamp: 0.0,
+            prevent_clipping: true,
+            analyze: false,
+        }
+    }
+}
+
+impl Normalization {
+    pub fn new(mode: NormalizationMode) -> Self {
+        Self {
+            mode,
+            ..Default::default()
+        }
+    }
+}
+
+/// 播放器及各音轨共享的响度标准化设置
+#[derive(Debug, Default)]
+pub(crate) struct NormalizationSettings {
+    normalization: Mutex<Normalization>,
+    /// 设置的版本，每次修改时递增
+    version: AtomicU64,
+}
+
+impl NormalizationSettings {
+    pub fn get(&self) -> Normalization {
+        *self.normalization.lock().unwrap()
+    }
+
+    pub fn set(&self, normalization: Normalization) {
+        *self.normalization.lock().unwrap() = normalization;
+        self.version.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// 当前设置的副本，不再随播放器的设置变化
+    pub fn snapshot(&self) -> Self {
+        Self {
+            normalization: Mutex::new(self.get()),
+            version: AtomicU64::new(0),
+        }
+    }
+}
+
+fn db_to_linear(db: f32) -> f32 {
+    10f32.powf(db.min(MAX_GAIN_DB) / 20.0)
+}
+
+/// 响度标准化音频源
+pub(crate) struct NormalizationSource {
+    inner: Box<dyn Source + Send>,
+    settings: Arc<NormalizationSettings>,
+    /// 已应用的设置版本
+    version: u64,
+    normalization: Normalization,
+    tags: ReplayGain,
+    /// 实时分析的响度测量，需要时创建
+    meter: Option<LoudnessMeter>,
+    /// 已播放部分的峰值
+    peak: f32,
+    /// 当前增益（线性）
+    gain: f32,
+    target: f32,
+    /// 每个采样增益的变化量
+    step: f32,
+    /// 下一个采样所属的声道
+    channel: usize,
+    /// 距下次计算增益的帧数
+    countdown: u32,
+}
+
+impl NormalizationSource {
+    pub fn new(
+        inner: Box<dyn Source + Send>,
+        settings: Arc<NormalizationSettings>,
+        tags: ReplayGain,
+    ) -> Self {
+        let mut source = Self {
+            inner,
+            settings,
+            version: u64::MAX,
+            normalization: Normalization::default(),
+            tags,
+            meter: None,
+            peak: 0.0,
+            gain: 1.0,
+            target: 1.0,
+            step: 0.0,
+            channel: 0,
+            countdown: 0,
+        };
+        // 音轨开始时直接使用标签给出的增益
+        source.update();
+        source.gain = source.target;
+        source.step = 0.0;
+        source
+    }
+
+    /// 当前模式下标签给出的增益及峰值
+    fn tagged(&self) -> Option<(f32, Option<f32>)> {
+        let tags = &self.tags;
+        match self.normalization.mode {
+            NormalizationMode::Off => None,
+            NormalizationMode::Track => tags.track_gain.map(|gain| (gain, tags.track_peak)),
+            NormalizationMode::Album => match tags.album_gain {
+                Some(gain) => Some((gain, tags.album_peak)),
+                None => tags.track_gain.map(|gain| (gain, tags.track_peak)),
+            },
+        }
+    }
+
+    /// 重新计算目标增益
+    fn update(&mut self) {
+        let version = self.settings.version.load(Ordering::SeqCst);
+        if version != self.version {
+            self.version = version;
+            self.normalization = self.settings.get();
+        }
+
+        let normalization = self.normalization;
+        let tagged = self.tagged();
+        let analyzing = normalization.mode != NormalizationMode::Off
+            && normalization.analyze
+            && tagged.is_none();
+        if analyzing && self.meter.is_none() {
+            self.meter = Some(LoudnessMeter::realtime(
+                self.inner.channels(),
+                self.inner.sample_rate(),
+            ));
+        }
+
+        let estimated = || {
+            let meter = self.meter.as_ref()?;
+            let measured = meter.frames() as f64 / self.inner.sample_rate().max(1) as f64;
+            if measured < MIN_ANALYSIS.as_secs_f64() {
+                return None;
+            }
+            let loudness = meter.integrated()?;
+            Some(((REFERENCE_LOUDNESS - loudness) as f32, Some(self.peak)))
+        };
+        let target = match tagged.or_else(|| analyzing.then(estimated).flatten()) {
+            Some((gain, peak)) => {
+                let mut target = db_to_linear(gain + normalization.preamp);
+                if normalization.prevent_clipping {
+                    if let Some(peak) = peak.filter(|&peak| peak > 0.0) {
+                        target = target.min(1.0 / peak);
+                    }
+                }
+                target
+            }
+            None => 1.0,
+        };
+
+        if target != self.target {
+            self.target = target;
+            let samples =
+                RAMP_MS / 1000.0 * self.inner.sample_rate() as f32 * self.inner.channels() as f32;
+            self.step = (target - self.gain) / samples.max(1.0);
+        }
+    }
+}
+
+impl Iterator for NormalizationSource {
+    type Item = rodio::Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.channel == 0 {
+            if self.countdown == 0 {
+                self.countdown = UPDATE_FRAMES;
+                self.update();
+            }
+            self.countdown -= 1;
+        }
+
+        let sample = self.inner.next()?;
+        self.channel += 1;
+        if self.channel >= self.inner.channels().max(1) as usize {
+            self.channel = 0;
+        }
+        if let Some(ref mut meter) = self.meter {
+            meter.push(sample);
+            self.peak = self.peak.max(sample.abs());
+        }
+        if self.gain != self.target {
+            self.gain += self.step;
+            if (self.step > 0.0 && self.gain > self.target)
+                || (self.step < 0.0 && self.gain < self.target)
+            {
+                self.gain = self.target;
+            }
+        }
+        Some(sample * self.gain)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl Source for NormalizationSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.channel = 0;
+        Ok(())
+    }
+}
@@ -0,0 +1,432 @@
+use rodio::cpal::traits::{DeviceTrait, HostTrait};
+use rodio::cpal::{self, SupportedStreamConfigRange};
+use rodio::mixer::{self, Mixer, MixerSource};
+use rodio::stream::StreamError;
+use rodio::{
+    ChannelCount, Device, OutputStream, OutputStreamBuilder, SampleRate, Source,
+    SupportedStreamConfig,
+};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::visualizer::SampleTap;
+use super::PlayerCallback;
+use crate::error::{Error, Result};
+use crate::events::PlayerEvent;
+
+/// 播放器的音频输出
+pub enum Output {
+    /// 系统默认的音频设备
+    Default,
+    /// 指定的音频设备，可通过 [`output_devices`] 获取
+    Device(OutputDevice),
+    /// 已打开的输出流
+    Stream(OutputStream),
+    /// 空输出，消耗音频数据但不发出声音，用于没有音频设备的环境（如 CI 及容器）
+    Null(NullOutput),
+}
+
+/// 音频输出设备
+#[derive(Clone)]
+pub struct OutputDevice {
+    device: Device,
+    name: String,
+    default: bool,
+}
+
+impl OutputDevice {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// 是否为系统默认的输出设备
+    pub fn is_default(&self) -> bool {
+        self.default
+    }
+
+    /// 设备支持的输出配置（声道数、采样率范围及采样格式）
+    pub fn supported_configs(&self) -> Result<Vec<SupportedStreamConfigRange>> {
+        Ok(self
+            .device
+            .supported_output_configs()
+            .map_err(Error::device)?
+            .collect())
+    }
+
+    /// 设备的默认输出配置
+    pub fn default_config(&self) -> Result<SupportedStreamConfig> {
+        self.device.default_output_config().map_err(Error::device)
+    }
+}
+
+impl fmt::Debug for OutputDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OutputDevice")
+            .field("name", &self.name)
+            .field("default", &self.default)
+            .finish()
+    }
+}
+
+/// 列出系统中的音频输出设备
+pub fn output_devices() -> Result<Vec<OutputDevice>> {
+    let host = cpal::default_host();
+    let default = host.default_output_device().and_then(|d| d.name().ok());
+    let devices = host
+        .output_devices()
+        .map_err(Error::device)?
+        .filter_map(|device| {
+            let name = device.name().ok()?;
+            Some(OutputDevice {
+                default: default.as_ref() == Some(&name),
+                device,
+                name,
+            })
+        })
+        .collect();
+    Ok(devices)
+}
+
+/// 空输出设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NullOutput {
+    pub sample_rate: SampleRate,
+    pub channels: ChannelCount,
+    /// 是否按实时速度消耗数据，为 `false` 时尽可能快地消耗
+    pub real_time: bool,
+}
+
+impl Default for NullOutput {
+    fn default() -> Self {
+        Self {
+            sample_rate: 44100,
+            channels: 2,
+            real_time: true,
+        }
+    }
+}
+
+impl NullOutput {
+    /// 按实时速度消耗数据的空输出，播放进度与真实设备一致
+    pub fn real_time() -> Self {
+        Self::default()
+    }
+
+    /// 尽可能快地消耗数据的空输出
+    ///
+    /// 输出静音时（如暂停或没有音频）会短暂休眠，以免空转占满 CPU
+    pub fn unlimited() -> Self {
+        Self {
+            real_time: false,
+            ..Default::default()
+        }
+    }
+}
+
+/// 已打开的音频输出
+///
+/// 播放器的音频均追加到内部混音器中，内部混音器的输出经转接源追加到音频设备的混音器；
+/// 切换输出时只需将转接源移到新的设备，Sink 中的音频、播放位置、暂停状态及音量均保持不变
+pub(crate) struct OutputHandle {
+    /// 内部混音器
+    mixer: Mixer,
+    bridge: Arc<Mutex<Bridge>>,
+    device: DeviceHandle,
+    /// 当前输出设备的名称
+    name: Option<String>,
+    /// 发送到音频设备的采样
+    tap: Arc<SampleTap>,
+}
+
+impl OutputHandle {
+    pub fn open(output: Output, callback: PlayerCallback) -> Result<Self> {
+        let (device, name) = DeviceHandle::open(output, callback)?;
+        let (channels, sample_rate) = device.config();
+        let (mixer, source) = mixer::mixer(channels, sample_rate);
+        let tap = Arc::new(SampleTap::new(channels, sample_rate));
+        let bridge = Arc::new(Mutex::new(Bridge {
+            source,
+            owner: 0,
+            tap: tap.clone(),
+        }));
+        device
+            .mixer()
+            .add(BridgeSource::new(bridge.clone(), 0, channels, sample_rate));
+        Ok(Self {
+            mixer,
+            bridge,
+            device,
+            name,
+            tap,
+        })
+    }
+
+    /// 切换到新的音频输出，打开失败时仍使用原输出
+    pub fn switch(&mut self, output: Output, callback: PlayerCallback) -> Result<()> {
+        let (device, name) = DeviceHandle::open(output, callback)?;
+        let (owner, channels, sample_rate) = {
+            let mut bridge = self.bridge.lock().unwrap();
+            // 原输出中的转接源在下次读取时结束
+            bridge.owner += 1;
+            let source = &bridge.source;
+            (bridge.owner, source.channels(), source.sample_rate())
+        };
+        device.mixer().add(BridgeSource::new(
+            self.bridge.clone(),
+            owner,
+            channels,
+            sample_rate,
+        ));
+
+        let mut previous = std::mem::replace(&mut self.device, device);
+        if let DeviceHandle::Stream(ref mut stream) = previous {
+            stream.log_on_drop(false);
+        }
+        self.name = name;
+        Ok(())
+    }
+
+    pub fn mixer(&self) -> &Mixer {
+        &self.mixer
+    }
+
+    pub fn name(&self) -> Option<String> {
+        self.name.clone()
+    }
+
+    pub fn tap(&self) -> &Arc<SampleTap> {
+        &self.tap
+    }
+}
+
+/// 音频设备的输出
+enum DeviceHandle {
+    Stream(OutputStream),
+    Null(NullSink),
+}
+
+impl DeviceHandle {
+    /// 打开音频输出，返回输出及设备名称
+    fn open(output: Output, callback: PlayerCallback) -> Result<(Self, Option<String>)> {
+        Ok(match output {
+            Output::Default => {
+                let (stream, name) = Self::open_default(callback)?;
+                (Self::Stream(stream), name)
+            }
+            Output::Device(device) => {
+                let stream = Self::open_device(device.device, callback)?;
+                (Self::Stream(stream), Some(device.name))
+            }
+            Output::Stream(stream) => (Self::Stream(stream), None),
+            Output::Null(null) => (Self::Null(NullSink::new(null)), None),
+        })
+    }
+
+    /// 打开默认设备的输出流，失败时回退到其他可用的设备，返回输出流及设备名称
+    fn open_default(
+        callback: PlayerCallback,
+    ) -> Result<(OutputStream, Option<String>), StreamError> {
+        let host = cpal::default_host();
+        let error = match host.default_output_device() {
+            Some(device) => {
+                let name = device.name().ok();
+                match Self::open_device(device, callback.clone()) {
+                    Ok(stream) => return Ok((stream, name)),
+                    Err(error) => error,
+                }
+            }
+            None => StreamError::NoDevice,
+        };
+        let Ok(mut devices) = host.output_devices() else {
+            return Err(error);
+        };
+        devices
+            .find_map(|device| {
+                let name = device.name().ok();
+                let stream = Self::open_device(device, callback.clone()).ok()?;
+                Some((stream, name))
+            })
+            .ok_or(error)
+    }
+
+    /// 打开设备的输出流，输出出错时发送错误事件
+    fn open_device(device: Device, callback: PlayerCallback) -> Result<OutputStream, StreamError> {
+        OutputStreamBuilder::from_device(device)?
+            .with_error_callback(move |error| {
+                if let Some(ref cb) = *callback.read().unwrap() {
+                    cb(PlayerEvent::Error {
+                        error: Error::device(error),
+                    });
+                }
+            })
+            .open_stream_or_fallback()
+    }
+
+    fn mixer(&self) -> &Mixer {
+        match self {
+            Self::Stream(stream) => stream.mixer(),
+            Self::Null(null) => &null.mixer,
+        }
+    }
+
+    /// 输出的声道数及采样率
+    fn config(&self) -> (ChannelCount, SampleRate) {
+        match self {
+            Self::Stream(stream) => (
+                stream.config().channel_count(),
+                stream.config().sample_rate(),
+            ),
+            Self::Null(null) => (null.output.channels, null.output.sample_rate),
+        }
+    }
+}
+
+/// 内部混音器的输出
+struct Bridge {
+    source: MixerSource,
+    /// 当前转接源的标识，其余转接源在下次读取时结束
+    owner: u64,
+    tap: Arc<SampleTap>,
+}
+
+/// 追加到音频设备中的转接源，按整帧从内部混音器读取数据
+struct BridgeSource {
+    bridge: Arc<Mutex<Bridge>>,
+    id: u64,
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    buffer: Vec<f32>,
+    pos: usize,
+}
+
+impl BridgeSource {
+    /// 每次读取的帧数
+    const BLOCK_FRAMES: usize = 128;
+
+    fn new(
+        bridge: Arc<Mutex<Bridge>>,
+        id: u64,
+        channels: ChannelCount,
+        sample_rate: SampleRate,
+    ) -> Self {
+        Self {
+            bridge,
+            id,
+            channels,
+            sample_rate,
+            buffer: Vec::with_capacity(Self::BLOCK_FRAMES * channels as usize),
+            pos: 0,
+        }
+    }
+}
+
+impl Iterator for BridgeSource {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.pos == self.buffer.len() {
+            self.buffer.clear();
+            let len = Self::BLOCK_FRAMES * self.channels as usize;
+            match self.bridge.try_lock() {
+                Ok(mut bridge) => {
+                    if bridge.owner != self.id {
+                        return None;
+                    }
+                    // 内部混音器没有音频时输出静音
+                    self.buffer
+                        .extend((0..len).map(|_| bridge.source.next().unwrap_or(0.0)));
+                    bridge.tap.write(&self.buffer);
+                }
+                // 不在音频线程上等待切换输出，本次输出静音
+                Err(TryLockError::WouldBlock) => self.buffer.resize(len, 0.0),
+                Err(TryLockError::Poisoned(_)) => return None,
+            }
+            self.pos = 0;
+        }
+        let sample = self.buffer[self.pos];
+        self.pos += 1;
+        Some(sample)
+    }
+}
+
+impl Source for BridgeSource {
+    fn current_span_len(&self) -> Option<usize> {
+        None
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        None
+    }
+}
+
+/// 空输出，在后台线程中消耗混音器的数据
+pub(crate) struct NullSink {
+    mixer: Mixer,
+    output: NullOutput,
+    /// 是否需要停止
+    stop: Arc<AtomicBool>,
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl NullSink {
+    /// 每次消耗的时长
+    const BLOCK: Duration = Duration::from_millis(10);
+
+    fn new(output: NullOutput) -> Self {
+        let (mixer, source) = mixer::mixer(output.channels, output.sample_rate);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread_handle = std::thread::spawn(move || Self::run(source, output, thread_stop));
+        Self {
+            mixer,
+            output,
+            stop,
+            thread_handle: Some(thread_handle),
+        }
+    }
+
+    fn run(mut source: MixerSource, output: NullOutput, stop: Arc<AtomicBool>) {
+        let frames = (output.sample_rate as u64 * Self::BLOCK.as_millis() as u64 / 1000).max(1);
+        let samples = frames * output.channels as u64;
+        let start = Instant::now();
+        let mut consumed: u64 = 0;
+
+        while !stop.load(Ordering::Relaxed) {
+            let mut silent = true;
+            for sample in source.by_ref().take(samples as usize) {
+                silent &= sample == 0.0;
+            }
+            consumed += frames;
+
+            if output.real_time {
+                // 按已消耗的帧数计算时间，避免误差累积
+                let elapsed = Duration::from_secs_f64(consumed as f64 / output.sample_rate as f64);
+                if let Some(wait) = elapsed.checked_sub(start.elapsed()) {
+                    std::thread::sleep(wait);
+                }
+            } else if silent {
+                std::thread::sleep(Duration::from_millis(1));
+            }
+        }
+    }
+}
+
+impl Drop for NullSink {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::Thread;
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+/// 每次解码的最大采样数
+const BLOCK_SAMPLES: usize = 4096;
+/// 播放线程每次从缓冲区取出的最大帧数
+const BATCH_FRAMES: usize = 512;
+/// 缓冲区中最多记录的格式变化数
+const SPAN_SLOTS: usize = 64;
+
+/// 格式相同的一段解码后的采样
+struct Block {
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    samples: Vec<f32>,
+}
+
+/// 缓冲区中格式变化的位置
+#[derive(Default)]
+struct SpanSlot {
+    /// 新格式的第一个采样的位置
+    start: AtomicU64,
+    channels: AtomicU32,
+    sample_rate: AtomicU32,
+}
+
+/// 解码线程与播放线程共享的状态
+///
+/// 采样及格式变化分别存放在单生产者单消费者的无锁环形缓冲区中，双方只进行原子操作。
+/// 读写位置均为累计的数量，写入位置只由解码线程修改，读取位置只由播放线程修改
+struct Shared {
+    /// 采样的位表示
+    samples: Box<[AtomicU32]>,
+    /// 已写入的采样数
+    tail: AtomicU64,
+    /// 已读取的采样数
+    head: AtomicU64,
+    spans: Box<[SpanSlot]>,
+    span_tail: AtomicU64,
+    span_head: AtomicU64,
+    /// 请求跳转的位置（纳秒）
+    seek_pos: AtomicU64,
+    /// 播放线程请求的跳转序号
+    seek_requested: AtomicU64,
+    /// 解码线程已执行的跳转序号
+    seek_applied: AtomicU64,
+    /// 最近一次跳转时的写入位置，此前的采样应丢弃
+    flush_pos: AtomicU64,
+    /// 已解码到音频源的结尾（跳转后重置）
+    ended: AtomicBool,
+    /// 解码线程已退出
+    stopped: AtomicBool,
+    /// 预解码源已释放
+    closed: AtomicBool,
+    /// 解码线程，缓冲区有空间、请求跳转或释放时唤醒
+    decoder: OnceLock<Thread>,
+}
+
+impl Shared {
+    fn new(capacity: usize) -> Self {
+        Self {
+            samples: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            tail: AtomicU64::new(0),
+            head: AtomicU64::new(0),
+            spans: (0..SPAN_SLOTS).map(|_| SpanSlot::default()).collect(),
+            span_tail: AtomicU64::new(0),
+            span_head: AtomicU64::new(0),
+            seek_pos: AtomicU64::new(0),
+            seek_requested: AtomicU64::new(0),
+            seek_applied: AtomicU64::new(0),
+            flush_pos: AtomicU64::new(0),
+            ended: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            closed: AtomicBool::new(false),
+            decoder: OnceLock::new(),
+        }
+    }
+
+    /// 唤醒休眠中的解码线程
+    fn wake(&self) {
+        if self.stopped.load(Ordering::Acquire) {
+            return;
+        }
+        if let Some(thread) = self.decoder.get() {
+            thread.unpark();
+        }
+    }
+
+    fn sample(&self, pos: u64) -> f32 {
+        let index = (pos % self.samples.len() as u64) as usize;
+        f32::from_bits(self.samples[index].load(Ordering::Relaxed))
+    }
+
+    fn span(&self, index: u64) -> &SpanSlot {
+        &self.spans[(index % SPAN_SLOTS as u64) as usize]
+    }
+
+    /// 是否可以写入一段采样及其格式
+    fn has_space(&self) -> bool {
+        let used = self.tail.load(Ordering::Relaxed) - self.head.load(Ordering::Acquire);
+        let spans = self.span_tail.load(Ordering::Relaxed) - self.span_head.load(Ordering::Acquire);
+        used as usize + BLOCK_SAMPLES <= self.samples.len() && (spans as usize) < SPAN_SLOTS
+    }
+
+    /// 写入一段采样，格式与上一段不同时记录格式变化，仅由解码线程调用
+    fn push(&self, block: &Block, new_span: bool) {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if new_span {
+            let span_tail = self.span_tail.load(Ordering::Relaxed);
+            let slot = self.span(span_tail);
+            slot.start.store(tail, Ordering::Relaxed);
+            slot.channels
+                .store(block.channels as u32, Ordering::Relaxed);
+            slot.sample_rate.store(block.sample_rate, Ordering::Relaxed);
+            self.span_tail.store(span_tail + 1, Ordering::Release);
+        }
+        let len = self.samples.len() as u64;
+        for (i, sample) in block.samples.iter().enumerate() {
+            let index = ((tail + i as u64) % len) as usize;
+            self.samples[index].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.tail
+            .store(tail + block.samples.len() as u64, Ordering::Release);
+    }
+}
+
+/// 预解码的音频源
+///
+/// 在 tokio 阻塞线程池的解码线程中解码（网络音频的读取可能阻塞），解码后的采样写入有界的无锁缓冲区，
+/// 播放线程只从缓冲区中读取，不会等待解码或加锁。缓冲区为空时输出静音，
+/// 跳转由解码线程执行，执行完成前同样输出静音
+pub struct PrefetchSource {
+    shared: Arc<Shared>,
+    /// 已取出、尚未输出的采样，音频源结束时为空
+    batch: Vec<f32>,
+    batch_pos: usize,
+    /// `batch` 的格式
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+    /// 读取位置
+    head: u64,
+    /// 已读取的格式变化数
+    span_head: u64,
+    /// 已请求的跳转序号
+    seek_requested: u64,
+    /// 已丢弃跳转前数据的跳转序号
+    seek_flushed: u64,
+    total_duration: Option<Duration>,
+}
+
+impl PrefetchSource {
+    /// 在 `handle` 的阻塞线程池中解码 `inner`，最多预先解码 `buffer` 时长的音频
+    ///
+    /// 立即返回，缓冲区填充前输出静音；可在运行时之外调用
+    pub fn new<S>(inner: S, buffer: Duration, handle: &Handle) -> Self
+    where
+        S: Source + Send + 'static,
+    {
+        let channels = inner.channels();
+        let sample_rate = inner.sample_rate();
+        let frames = (buffer.as_secs_f64() * sample_rate as f64) as usize;
+        // 预留一段的空间，以便缓冲区中的采样不足 `buffer` 时即可继续解码
+        let capacity = frames * channels as usize + BLOCK_SAMPLES;
+        let shared = Arc::new(Shared::new(capacity));
+        let total_duration = inner.total_duration();
+
+        let decoder = shared.clone();
+        handle.spawn_blocking(move || decode(Box::new(inner), decoder));
+
+        let mut source = Self {
+            shared,
+            batch: Vec::with_capacity(BATCH_FRAMES * channels.max(1) as usize),
+            batch_pos: 0,
+            channels,
+            sample_rate,
+            head: 0,
+            span_head: 0,
+            seek_requested: 0,
+            seek_flushed: 0,
+            total_duration,
+        };
+        source.refill();
+        source
+    }
+
+    /// 已解码、尚未播放的采样数
+    pub fn buffered_samples(&self) -> usize {
+        let tail = self.shared.tail.load(Ordering::Acquire);
+        let pending = self.batch.len() - self.batch_pos;
+        pending + tail.saturating_sub(self.head) as usize
+    }
+
+    /// 从缓冲区中取出接下来输出的采样，不跨越格式变化
+    fn refill(&mut self) {
+        self.batch.clear();
+        self.batch_pos = 0;
+        let shared = &*self.shared;
+
+        let applied = shared.seek_applied.load(Ordering::Acquire);
+        if applied != self.seek_requested {
+            // 等待解码线程执行跳转
+            if !shared.stopped.load(Ordering::Acquire) {
+                self.push_silence();
+            }
+            return;
+        }
+        if self.seek_flushed != applied {
+            // 丢弃跳转前解码的采样
+            self.seek_flushed = applied;
+            self.head = shared.flush_pos.load(Ordering::Relaxed);
+            shared.head.store(self.head, Ordering::Release);
+            shared.wake();
+        }
+
+        // 应用已到达的格式变化
+        let span_tail = shared.span_tail.load(Ordering::Acquire);
+        while self.span_head < span_tail {
+            let slot = shared.span(self.span_head);
+            if slot.start.load(Ordering::Relaxed) > self.head {
+                break;
+            }
+            self.channels = slot.channels.load(Ordering::Relaxed) as ChannelCount;
+            self.sample_rate = slot.sample_rate.load(Ordering::Relaxed);
+            self.span_head += 1;
+        }
+        shared.span_head.store(self.span_head, Ordering::Release);
+
+        // 结束标记需在写入位置之前读取，以免遗漏最后写入的采样
+        let ended = shared.ended.load(Ordering::Acquire) || shared.stopped.load(Ordering::Acquire);
+        let tail = shared.tail.load(Ordering::Acquire);
+        let next_span = (self.span_head < span_tail)
+            .then(|| shared.span(self.span_head).start.load(Ordering::Relaxed));
+        let limit = next_span.map_or(tail, |start| start.min(tail));
+        let frame = self.channels.max(1) as usize;
+        let len = ((limit - self.head) as usize).min(BATCH_FRAMES * frame);
+
+        if len == 0 {
+            if !(ended && next_span.is_none()) {
+                // 缓冲区为空时输出一帧静音，不等待解码
+                self.push_silence();
+            }
+            return;
+        }
+        self.batch
+            .extend((self.head..self.head + len as u64).map(|pos| shared.sample(pos)));
+        self.head += len as u64;
+        shared.head.store(self.head, Ordering::Release);
+        shared.wake();
+    }
+
+    fn push_silence(&mut self) {
+        self.batch.resize(self.channels.max(1) as usize, 0.0);
+    }
+}
+
+/// 解码线程，缓冲区满时休眠，直到播放线程读取、请求跳转或释放
+///
+/// 解码及跳转可能等待网络数据，整个循环在阻塞线程池的同一线程中执行
+fn decode(mut inner: Box<dyn Source + Send>, shared: Arc<Shared>) {
+    let _ = shared.decoder.set(std::thread::current());
+    let mut format = None;
+    let mut applied = 0;
+    let mut ended = false;
+    loop {
+        if shared.closed.load(Ordering::Acquire) {
+            break;
+        }
+
+        let requested = shared.seek_requested.load(Ordering::Acquire);
+        if requested != applied {
+            let pos = Duration::from_nanos(shared.seek_pos.load(Ordering::Relaxed));
+            // 跳转失败时从原位置继续解码
+            let _ = inner.try_seek(pos);
+            format = None;
+            ended = false;
+            shared.ended.store(false, Ordering::Relaxed);
+            shared
+                .flush_pos
+                .store(shared.tail.load(Ordering::Relaxed), Ordering::Relaxed);
+            shared.seek_applied.store(requested, Ordering::Release);
+            applied = requested;
+            continue;
+        }
+
+        if ended || !shared.has_space() {
+            // 检查之后的唤醒会保留到休眠时，不会错过
+            std::thread::park();
+            continue;
+        }
+
+        let block = next_block(inner.as_mut());
+        // 解码期间发起了跳转，丢弃跳转前的数据
+        if shared.seek_requested.load(Ordering::Acquire) != applied {
+            continue;
+        }
+        match block {
+            Some(block) => {
+                let block_format = Some((block.channels, block.sample_rate));
+                shared.push(&block, format != block_format);
+                format = block_format;
+            }
+            None => {
+                ended = true;
+                shared.ended.store(true, Ordering::Release);
+            }
+        }
+    }
+    shared.stopped.store(true, Ordering::Release);
+}
+
+/// 解码下一段，不跨越 span 以保证段内格式一致
+fn next_block(inner: &mut (dyn Source + Send)) -> Option<Block> {
+    let channels = inner.channels();
+    let sample_rate = inner.sample_rate();
+    let frame = channels.max(1) as usize;
+    let limit = match inner.current_span_len() {
+        Some(len) if len > 0 => len.min(BLOCK_SAMPLES),
+        _ => BLOCK_SAMPLES,
+    };
+    let limit = (limit - limit % frame).max(frame);
+
+    let mut samples: Vec<f32> = inner.take(limit).collect();
+    if samples.is_empty() {
+        return None;
+    }
+    // 音频源在帧中间结束时补齐最后一帧
+    samples.resize(samples.len().next_multiple_of(frame), 0.0);
+    Some(Block {
+        channels,
+        sample_rate,
+        samples,
+    })
+}
+
+impl Iterator for PrefetchSource {
+    type Item = rodio::Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let sample = *self.batch.get(self.batch_pos)?;
+        self.batch_pos += 1;
+        if self.batch_pos == self.batch.len() {
+            // 预先取出下一段，以便报告其格式及长度
+            self.refill();
+        }
+        Some(sample)
+    }
+}
+
+impl Source for PrefetchSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        Some(self.batch.len() - self.batch_pos)
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.sample_rate
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.total_duration
+    }
+
+    /// 请求解码线程跳转并立即返回，跳转完成前输出静音；跳转失败时从原位置继续播放
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.seek_requested += 1;
+        let shared = &*self.shared;
+        shared
+            .seek_pos
+            .store(pos.as_nanos() as u64, Ordering::Relaxed);
+        shared
+            .seek_requested
+            .store(self.seek_requested, Ordering::Release);
+        shared.wake();
+        self.refill();
+        Ok(())
+    }
+}
+
+impl Drop for PrefetchSource {
+    fn drop(&mut self) {
+        self.shared.closed.store(true, Ordering::Release);
+        self.shared.wake();
+    }
+}
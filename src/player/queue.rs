@@ -0,0 +1,955 @@
+use rodio::mixer::Mixer;
+use rodio::source::{EmptyCallback, SeekError};
+use rodio::{ChannelCount, SampleRate, Sink, Source};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, RwLock};
+use std::time::Duration;
+use tokio::runtime::Handle;
+
+use super::crossfade::{Crossfade, CrossfadeSettings, FadeControl, FadeSource};
+use super::effects::{EffectChain, EffectSource};
+use super::equalizer::{EqualizerSettings, EqualizerSource};
+use super::looping::{LoopSettings, LoopSource};
+use super::normalization::{NormalizationSettings, NormalizationSource};
+use super::prefetch::PrefetchSource;
+use super::rate::{MediaClock, RateSettings, TimeStretch};
+use super::track::{StreamReader, Streaming, Track, TrackSource};
+use super::{LoaderCallback, PlayerCallback, PlayerControl};
+use crate::decoder::{Decoder, DecoderBuilder};
+use crate::error::{Error, Result};
+use crate::events::PlayerEvent;
+use crate::loader::cache::DiskCache;
+use crate::loader::downloader::{self, Downloader};
+use crate::loader::hls::{self, HlsLoader};
+use crate::loader::{LoaderEvent, LoaderOptions, RetryPolicy};
+use crate::metadata::AudioMetadata;
+use crate::reader::{self, BufferMonitor, BufferState};
+
+/// 码率未知时用于换算缓冲阈值的字节率（按 128kbps 估算）
+const DEFAULT_BYTE_RATE: f64 = 16_000.0;
+
+/// 离线渲染时使用的音轨标识，不会成为当前音轨
+pub(crate) const RENDER_ID: u64 = 0;
+
+/// 发送给队列工作线程的事件
+///
+/// 播放线程中的回调只发送事件，不获取队列及控制器的锁，以免与等待播放线程的操作（如跳转）互相阻塞
+pub(crate) enum QueueEvent {
+    /// 音轨的缓冲状态变化
+    Buffer(u64, BufferState),
+    /// 音轨开始播放
+    TrackStart(u64),
+    /// 音轨播放完毕
+    TrackEnd(u64),
+    /// 音轨到达自动淡出的位置，且已开始淡出
+    FadePoint(u64),
+    /// 在工作线程中预加载队列项（不需要在运行时中加载的音频）
+    Preload(Preload, TrackSource),
+    /// 预加载完成
+    Preloaded(Preload, Box<Result<Track>>),
+    /// 音轨的流内元数据
+    Metadata(u64, Box<AudioMetadata>),
+    /// 播放器已释放，工作线程退出
+    Shutdown,
+}
+
+/// 正在预加载的音轨
+pub(crate) struct Preload {
+    id: u64,
+    index: usize,
+    /// 开始预加载时的队列版本
+    generation: u64,
+}
+
+/// 队列事件的发送端
+pub(crate) type EventSender = mpsc::Sender<QueueEvent>;
+
+/// 播放队列项
+pub(crate) enum QueueItem {
+    /// 本地文件
+    File(PathBuf),
+    /// 网络地址、请求选项，以及加入队列时所在的运行时（用于在后台预加载）
+    Url(String, Arc<LoaderOptions>, Option<Handle>),
+    /// 自定义Reader或Source，只能播放一次，播放后为 `None`
+    Once(Option<TrackSource>),
+}
+
+impl QueueItem {
+    /// 获取音轨的数据来源，一次性来源已被取出时返回 `None`
+    fn source(&mut self) -> Option<TrackSource> {
+        match self {
+            QueueItem::File(path) => Some(TrackSource::File(path.clone())),
+            QueueItem::Url(url, options, _) => Some(TrackSource::Url(url.clone(), options.clone())),
+            QueueItem::Once(source) => source.take(),
+        }
+    }
+
+    fn handle(&self) -> Option<Handle> {
+        match self {
+            QueueItem::Url(_, _, handle) => handle.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// 已追加到Sink中的音轨
+struct QueuedTrack {
+    /// 音轨标识（每次追加到Sink时分配）
+    id: u64,
+    /// 在队列中的索引
+    index: usize,
+    duration: Option<Duration>,
+    metadata: Option<AudioMetadata>,
+    /// 下载资源，随音轨一同释放
+    streaming: Option<Streaming>,
+    /// 音轨是否已被移出队列
+    cancelled: Arc<AtomicBool>,
+    /// 淡出控制
+    fade: Arc<FadeControl>,
+}
+
+impl QueuedTrack {
+    /// 已缓冲的时间范围，本地音频视为完整缓冲
+    fn buffered(&self) -> Vec<Range<Duration>> {
+        let Some(duration) = self.duration else {
+            return Vec::new();
+        };
+        match self.streaming {
+            Some(ref streaming) => streaming.buffered(duration),
+            None => vec![Duration::ZERO..duration],
+        }
+    }
+}
+
+/// 已预加载、等待交叉淡化的音轨
+pub(crate) struct ReadyTrack {
+    id: u64,
+    index: usize,
+    track: Track,
+}
+
+impl ReadyTrack {
+    pub fn new(id: u64, index: usize, track: Track) -> Self {
+        Self { id, index, track }
+    }
+}
+
+/// 播放队列
+#[derive(Default)]
+pub(crate) struct Queue {
+    /// 队列项
+    items: Vec<QueueItem>,
+    /// 当前播放项的索引
+    current: Option<usize>,
+    /// 当前播放音轨的标识
+    current_id: Option<u64>,
+    /// 当前音轨是否已播放完毕
+    current_ended: bool,
+    /// 已追加到Sink中的音轨，队首为当前音轨
+    tracks: VecDeque<QueuedTrack>,
+    /// 已切换走、正在淡出的音轨
+    fading: Vec<QueuedTrack>,
+    /// 等待交叉淡化的下一音轨
+    ready: Option<ReadyTrack>,
+    /// 正在预加载的队列项索引
+    preloading: Option<usize>,
+    /// 队列版本，Sink被重建或队列被清空时递增，用于丢弃过期的预加载结果
+    generation: u64,
+    /// 下一个音轨标识
+    next_id: u64,
+}
+
+impl Queue {
+    pub fn push(&mut self, item: QueueItem) -> usize {
+        self.items.push(item);
+        self.items.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// 当前音轨已缓冲的时间范围
+    pub fn buffered(&self) -> Vec<Range<Duration>> {
+        self.tracks
+            .iter()
+            .find(|t| Some(t.id) == self.current_id)
+            .map_or_else(Vec::new, QueuedTrack::buffered)
+    }
+
+    /// 可重复打开的队列项的数据来源，一次性来源被跳过
+    pub fn render_sources(&self) -> Vec<TrackSource> {
+        self.items
+            .iter()
+            .filter_map(|item| match item {
+                QueueItem::File(path) => Some(TrackSource::File(path.clone())),
+                QueueItem::Url(url, options, _) => {
+                    Some(TrackSource::Url(url.clone(), options.clone()))
+                }
+                QueueItem::Once(_) => None,
+            })
+            .collect()
+    }
+
+    /// 下一项是否已追加到Sink中
+    pub fn next_ready(&self) -> bool {
+        let Some(current) = self.current else {
+            return false;
+        };
+        self.tracks.iter().any(|t| t.index == current + 1)
+    }
+
+    /// 取出等待交叉淡化的指定项
+    pub fn take_ready(&mut self, index: usize) -> Option<ReadyTrack> {
+        if self.ready.as_ref().is_some_and(|r| r.index == index) {
+            return self.unset_ready();
+        }
+        None
+    }
+
+    /// 设置等待交叉淡化的音轨，当前音轨到达淡出位置时即可开始交叉淡化
+    fn set_ready(&mut self, ready: ReadyTrack) {
+        self.ready = Some(ready);
+        self.set_current_armed(true);
+    }
+
+    /// 取出等待交叉淡化的音轨
+    fn unset_ready(&mut self) -> Option<ReadyTrack> {
+        self.set_current_armed(false);
+        self.ready.take()
+    }
+
+    fn set_current_armed(&self, armed: bool) {
+        let current_id = self.current_id;
+        if let Some(track) = self.tracks.iter().find(|t| Some(t.id) == current_id) {
+            track.fade.set_armed(armed);
+        }
+    }
+
+    /// 获取指定项的数据来源及运行时，并为其分配音轨标识
+    pub fn prepare(&mut self, index: usize) -> Result<(u64, TrackSource, Option<Handle>)> {
+        let item = self
+            .items
+            .get_mut(index)
+            .ok_or_else(|| Error::invalid_input(format!("Queue index {} out of range", index)))?;
+        let source = item.source().ok_or_else(|| {
+            Error::invalid_input(format!("Queue item {} can only be played once", index))
+        })?;
+        let handle = item.handle();
+        let id = self.allocate_id();
+        Ok((id, source, handle))
+    }
+
+    /// 将指定项设为当前项，返回其数据来源及运行时
+    pub fn select(&mut self, index: usize) -> Result<(u64, TrackSource, Option<Handle>)> {
+        let (id, source, handle) = self.prepare(index)?;
+        self.current = Some(index);
+        self.current_id = Some(id);
+        self.current_ended = false;
+        Ok((id, source, handle))
+    }
+
+    /// 将已预加载的音轨设为当前项，返回其标识及音轨
+    pub fn select_ready(&mut self, ready: ReadyTrack) -> (u64, Track) {
+        self.current = Some(ready.index);
+        self.current_id = Some(ready.id);
+        self.current_ended = false;
+        (ready.id, ready.track)
+    }
+
+    /// 释放已追加到Sink中的音轨，并丢弃正在进行的预加载
+    pub fn reset(&mut self) {
+        for track in self.tracks.drain(..).chain(self.fading.drain(..)) {
+            track.cancelled.store(true, Ordering::SeqCst);
+        }
+        self.ready = None;
+        self.current_id = None;
+        self.current_ended = false;
+        self.preloading = None;
+        self.generation += 1;
+    }
+
+    /// 清空队列
+    pub fn clear(&mut self) {
+        self.reset();
+        self.items.clear();
+        self.current = None;
+    }
+
+    /// 清空队列，仅保留当前项
+    pub fn clear_except_current(&mut self) {
+        let current_id = self.current_id;
+        self.tracks.retain(|t| {
+            let keep = Some(t.id) == current_id;
+            if !keep {
+                t.cancelled.store(true, Ordering::SeqCst);
+            }
+            keep
+        });
+        self.unset_ready();
+        self.preloading = None;
+        self.generation += 1;
+
+        match self.current.take() {
+            Some(current) => {
+                let item = self.items.swap_remove(current);
+                self.items = vec![item];
+                self.current = Some(0);
+                for track in self.tracks.iter_mut() {
+                    track.index = 0;
+                }
+            }
+            None => self.items.clear(),
+        }
+    }
+
+    fn allocate_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+}
+
+/// 队列在回调及后台线程中所需的共享状态
+#[derive(Clone)]
+pub(crate) struct QueueContext {
+    pub queue: Arc<Mutex<Queue>>,
+    pub control: Arc<RwLock<PlayerControl>>,
+    pub callback: PlayerCallback,
+    pub loader_callback: LoaderCallback,
+    pub ended: Arc<AtomicBool>,
+    pub crossfade: Arc<CrossfadeSettings>,
+    pub mixer: Mixer,
+    pub cache: Arc<RwLock<Option<DiskCache>>>,
+    pub retry_policy: Arc<RwLock<RetryPolicy>>,
+    pub events: EventSender,
+    pub waiting: Arc<AtomicBool>,
+    pub buffer_threshold: Arc<RwLock<Duration>>,
+    pub prefetch: Arc<RwLock<Option<Duration>>>,
+    pub rate: Arc<RateSettings>,
+    pub equalizer: Arc<EqualizerSettings>,
+    pub effects: EffectChain,
+    pub normalization: Arc<NormalizationSettings>,
+    pub looping: Arc<LoopSettings>,
+}
+
+impl QueueContext {
+    fn emit(&self, event: PlayerEvent) {
+        if let Some(ref cb) = *self.callback.read().unwrap() {
+            cb(event);
+        }
+    }
+
+    /// 打开音轨
+    pub async fn open(&self, id: u64, source: TrackSource) -> Result<Track> {
+        let track = match source {
+            TrackSource::File(path) => {
+                // 打开音频文件（支持格式：wav, mp3, flac, ogg等）
+                let file = File::open(path)?;
+                let mut source = Decoder::try_from(file)?;
+                source.set_metadata_callback(self.metadata_handler(id));
+                let metadata = source.metadata().clone();
+                Track::new(Box::new(source), Some(metadata), None)
+            }
+            TrackSource::Url(url, options) => {
+                // HLS 播放列表可通过扩展名或 Content-Type 识别
+                if hls::is_playlist_url(&url) {
+                    self.open_hls(id, &url, &options).await?
+                } else {
+                    match self.open_download(id, &url, &options).await? {
+                        Some(track) => track,
+                        None => self.open_hls(id, &url, &options).await?,
+                    }
+                }
+            }
+            TrackSource::Reader(reader) => {
+                let mut source = Decoder::new(reader)?;
+                source.set_metadata_callback(self.metadata_handler(id));
+                let metadata = source.metadata().clone();
+                Track::new(Box::new(source), Some(metadata), None)
+            }
+            TrackSource::Source(source) => Track::new(source, None, None),
+        };
+        Ok(track)
+    }
+
+    /// 下载并打开网络音频，响应为 HLS 播放列表时返回 `None`
+    async fn open_download(
+        &self,
+        id: u64,
+        url: &str,
+        options: &LoaderOptions,
+    ) -> Result<Option<Track>> {
+        let wrapper = reader::MVecBytesWrapper::new(256 * 1024);
+        let loader = Downloader::new(wrapper.clone());
+
+        loader.set_callback(self.loader_event_handler());
+        loader.set_metadata_callback(self.metadata_handler(id));
+        if let Some(cache) = self.cache.read().unwrap().clone() {
+            loader.set_cache(cache);
+        }
+        loader.set_retry_policy(*self.retry_policy.read().unwrap());
+        loader.set_options(options.clone());
+        loader.download(url, None).await?;
+        if loader
+            .content_type()
+            .is_some_and(|t| hls::is_playlist_content_type(&t))
+        {
+            return Ok(None);
+        }
+        let mime_type = loader.mime_type();
+        let prefetch = *self.prefetch.read().unwrap();
+        let reader = StreamReader::new(wrapper, loader.condvar(), prefetch.is_some());
+        let buffer_monitor = reader.buffer_monitor();
+        let total_bytes = loader.total_bytes();
+
+        // 服务器支持Range请求时，可直接跳转到未下载的位置
+        let byte_len = Some(total_bytes).filter(|_| loader.seekable());
+
+        let cancellation_token = reader.cancellation_token();
+        let streaming = Streaming::new(loader.condvar(), cancellation_token, loader);
+
+        // 以 Content-Type 及扩展名作为格式提示，加快格式探测
+        let mut builder = Decoder::builder().with_data(reader);
+        if let Some(len) = byte_len {
+            builder = builder.with_byte_len(len).with_seekable(true);
+        }
+        if let Some(ref mime_type) = mime_type {
+            builder = builder.with_mime_type(mime_type);
+        }
+        if let Some(extension) = downloader::url_extension(url) {
+            builder = builder.with_hint(&extension);
+        }
+        let mut source = build_decoder(builder).await?;
+        source.set_metadata_callback(self.metadata_handler(id));
+        self.watch_buffer(id, &buffer_monitor, total_bytes, source.total_duration());
+        let metadata = source.metadata().clone();
+        Ok(Some(Track::new(
+            with_prefetch(source, prefetch),
+            Some(metadata),
+            Some(streaming),
+        )))
+    }
+
+    /// 通过 HLS 加载器打开网络音频
+    async fn open_hls(&self, id: u64, url: &str, options: &LoaderOptions) -> Result<Track> {
+        let wrapper = reader::MVecBytesWrapper::new(256 * 1024);
+        let loader = HlsLoader::new(wrapper.clone());
+
+        loader.set_callback(self.loader_event_handler());
+        loader.set_retry_policy(*self.retry_policy.read().unwrap());
+        loader.set_options(options.clone());
+        loader.load(url, None).await?;
+        let prefetch = *self.prefetch.read().unwrap();
+        let reader = StreamReader::new(wrapper, loader.condvar(), prefetch.is_some());
+        let buffer_monitor = reader.buffer_monitor();
+        let duration = loader.duration();
+
+        let cancellation_token = reader.cancellation_token();
+        let streaming = Streaming::new(loader.condvar(), cancellation_token, loader);
+
+        let mut source = build_decoder(Decoder::builder().with_data(reader)).await?;
+        source.set_metadata_callback(self.metadata_handler(id));
+        self.watch_buffer(id, &buffer_monitor, 0, None);
+        let metadata = source.metadata().clone();
+        let mut track = Track::new(
+            with_prefetch(source, prefetch),
+            Some(metadata),
+            Some(streaming),
+        );
+        // 分片格式通常不含时长信息，以播放列表中的时长为准
+        track.duration = track.duration.or(duration);
+        Ok(track)
+    }
+
+    /// 监视网络音频的缓冲状态，通过通道将状态变化发送给播放器
+    ///
+    /// 根据总字节数及时长估算码率，将缓冲阈值换算为字节数
+    fn watch_buffer(
+        &self,
+        id: u64,
+        monitor: &BufferMonitor,
+        total_bytes: u64,
+        duration: Option<Duration>,
+    ) {
+        let byte_rate = match duration {
+            Some(duration) if total_bytes > 0 && !duration.is_zero() => {
+                total_bytes as f64 / duration.as_secs_f64()
+            }
+            _ => DEFAULT_BYTE_RATE,
+        };
+        let threshold = *self.buffer_threshold.read().unwrap();
+        monitor.set_resume_bytes((byte_rate * threshold.as_secs_f64()) as u64);
+
+        let sender = self.events.clone();
+        monitor.set_callback(move |state| {
+            let _ = sender.send(QueueEvent::Buffer(id, state));
+        });
+    }
+
+    /// 将加载器事件转发给播放器的加载器回调
+    fn loader_event_handler(&self) -> impl Fn(LoaderEvent) + Send + 'static {
+        let loader_callback = self.loader_callback.clone();
+        move |event| {
+            if let Some(ref cb) = *loader_callback.read().unwrap() {
+                cb(event);
+            }
+        }
+    }
+
+    /// 将音轨追加到当前Sink中
+    pub fn append(&self, queue: &mut Queue, id: u64, index: usize, track: Track) {
+        let control = self.control.read().unwrap();
+        let ready = ReadyTrack::new(id, index, track);
+        self.attach(&control.sink, &control.clock, queue, ready, None);
+    }
+
+    /// 将音轨追加到指定Sink中
+    ///
+    /// 音轨前后分别附加开始及结束回调，用于在无缝切换时更新播放状态
+    fn attach(
+        &self,
+        sink: &Sink,
+        clock: &Arc<MediaClock>,
+        queue: &mut Queue,
+        ready: ReadyTrack,
+        fade_in: Option<Crossfade>,
+    ) {
+        let ReadyTrack { id, index, track } = ready;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let fade = Arc::new(FadeControl::default());
+
+        let replay_gain = (track.metadata.as_ref())
+            .map(|m| m.replay_gain)
+            .unwrap_or_default();
+        let source =
+            NormalizationSource::new(track.source, self.normalization.clone(), replay_gain);
+        let source = EqualizerSource::new(Box::new(source), self.equalizer.clone());
+        let source = EffectSource::new(Box::new(source), self.effects.clone());
+        let trigger = fade.clone();
+        let sender = self.events.clone();
+        let source = FadeSource::new(
+            Box::new(source),
+            fade.clone(),
+            self.crossfade.clone(),
+            self.looping.clone(),
+            fade_in,
+            Box::new(move || {
+                trigger.set_triggered();
+                // 下一音轨已就绪时开始淡出，由工作线程切换Sink
+                let armed = trigger.take_armed();
+                if armed {
+                    let _ = sender.send(QueueEvent::FadePoint(id));
+                }
+                armed
+            }),
+        );
+
+        let sender = self.events.clone();
+        sink.append(EmptyCallback::new(Box::new(move || {
+            let _ = sender.send(QueueEvent::TrackStart(id));
+        })));
+        let source = TimeStretch::new(Box::new(source), self.rate.clone(), clock.clone());
+        let source = LoopSource::new(Box::new(source), self.looping.clone(), clock.clone());
+        sink.append(QueuedSource::new(Box::new(source), cancelled.clone()));
+        let sender = self.events.clone();
+        sink.append(EmptyCallback::new(Box::new(move || {
+            let _ = sender.send(QueueEvent::TrackEnd(id));
+        })));
+
+        queue.tracks.push_back(QueuedTrack {
+            id,
+            index,
+            duration: track.duration,
+            metadata: track.metadata,
+            streaming: track.streaming,
+            cancelled,
+            fade,
+        });
+    }
+
+    /// 将当前音轨淡出，同时在新的Sink中淡入指定音轨
+    ///
+    /// 新音轨开始播放时由开始回调更新播放状态并发送音轨切换事件
+    pub fn crossfade_to(&self, ready: ReadyTrack, crossfade: Crossfade) {
+        let (sink, clock) = {
+            let mut queue = self.queue.lock().unwrap();
+            // 丢弃正在进行的预加载
+            queue.unset_ready();
+            queue.preloading = None;
+            queue.generation += 1;
+
+            let current_id = queue.current_id;
+            let tracks: Vec<_> = queue.tracks.drain(..).collect();
+            for track in tracks {
+                if Some(track.id) == current_id {
+                    track.fade.fade_out(crossfade);
+                    queue.fading.push(track);
+                } else {
+                    track.cancelled.store(true, Ordering::SeqCst);
+                }
+            }
+
+            let sink = Sink::connect_new(&self.mixer);
+            let clock = Arc::new(MediaClock::default());
+            {
+                let control = self.control.read().unwrap();
+                sink.set_volume(control.sink.volume());
+                sink.set_speed(control.sink.speed());
+                if control.sink.is_paused() {
+                    sink.pause();
+                }
+            }
+            self.attach(&sink, &clock, &mut queue, ready, Some(crossfade));
+            (sink, clock)
+        };
+
+        // 旧Sink继续播放淡出部分，上一次淡出的Sink随之释放
+        let mut control = self.control.write().unwrap();
+        let previous = std::mem::replace(&mut control.sink, Arc::new(sink));
+        control.clock = clock;
+        *control.fading.lock().unwrap() = Some(previous);
+    }
+
+    /// 将等待交叉淡化的音轨直接追加到当前Sink中
+    pub fn flush_ready(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(ReadyTrack { id, index, track }) = queue.unset_ready() {
+            self.append(&mut queue, id, index, track);
+        }
+    }
+
+    /// 音轨到达自动淡出的位置，在新的Sink中淡入下一音轨
+    fn on_fade_point(&self, id: u64) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.current_id != Some(id) {
+            return;
+        }
+        let Some(ready) = queue.ready.take() else {
+            return;
+        };
+        match self.crossfade.get() {
+            Some(crossfade) => {
+                drop(queue);
+                self.crossfade_to(ready, crossfade);
+            }
+            // 交叉淡化已关闭，淡出后直接播放下一音轨
+            None => {
+                let ReadyTrack { id, index, track } = ready;
+                self.append(&mut queue, id, index, track);
+            }
+        }
+    }
+
+    /// 音轨开始播放
+    fn on_track_start(&self, id: u64) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.current_id == Some(id) {
+            // 直接加载的音轨，播放状态已在加载时更新
+            queue.current_ended = false;
+            return;
+        }
+        if !queue.tracks.iter().any(|t| t.id == id) {
+            // 音轨已被移出队列
+            return;
+        }
+
+        // 释放已播放完毕的音轨
+        while queue.tracks.front().is_some_and(|t| t.id != id) {
+            queue.tracks.pop_front();
+        }
+        let track = queue.tracks.front_mut().unwrap();
+        let index = track.index;
+        let duration = track.duration;
+        let metadata = track.metadata.take();
+        queue.current = Some(index);
+        queue.current_id = Some(id);
+        queue.current_ended = false;
+        self.looping.reset_track();
+
+        {
+            let control = self.control.read().unwrap();
+            *control.duration.lock().unwrap() = duration;
+            *control.metadata.lock().unwrap() = metadata;
+        }
+        drop(queue);
+
+        self.ended.store(false, Ordering::SeqCst);
+        self.emit(PlayerEvent::TrackChange { index });
+        self.emit(PlayerEvent::DurationChange);
+        self.emit(PlayerEvent::LoadedMetadata);
+
+        self.preload_next();
+    }
+
+    /// 音轨播放完毕
+    fn on_track_end(&self, id: u64) {
+        let mut queue = self.queue.lock().unwrap();
+        if let Some(pos) = queue.fading.iter().position(|t| t.id == id) {
+            // 淡出完毕，释放音轨
+            queue.fading.remove(pos);
+            return;
+        }
+        if queue.current_id != Some(id) {
+            return;
+        }
+        if queue.ready.is_some() {
+            // 未能交叉淡化（如音轨时长未知），直接播放下一音轨
+            drop(queue);
+            self.flush_ready();
+            return;
+        }
+        let has_next = queue.preloading.is_some() || queue.tracks.iter().any(|t| t.id != id);
+        if has_next {
+            // 下一音轨已就绪或正在加载
+            return;
+        }
+        queue.current_ended = true;
+        drop(queue);
+
+        self.ended.store(true, Ordering::SeqCst);
+        self.emit(PlayerEvent::Ended);
+    }
+
+    /// 在后台预加载下一队列项，并在加载完成后追加到Sink中
+    ///
+    /// 开启交叉淡化时，音轨暂不追加，等待当前音轨到达淡出位置
+    pub fn preload_next(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.preloading.is_some() || queue.ready.is_some() {
+            return;
+        }
+        let Some(tail) = queue.tracks.back().map(|t| t.index).or(queue.current) else {
+            return;
+        };
+        let index = tail + 1;
+        let Some(item) = queue.items.get_mut(index) else {
+            return;
+        };
+        let Some(source) = item.source() else {
+            return;
+        };
+        let handle = item.handle();
+        let request = Preload {
+            id: queue.allocate_id(),
+            index,
+            generation: queue.generation,
+        };
+        queue.preloading = Some(index);
+        drop(queue);
+
+        match handle {
+            // 网络音频在加入队列时所在的运行时中加载
+            Some(handle) => {
+                let context = self.clone();
+                handle.spawn(async move {
+                    let result = context.open(request.id, source).await;
+                    let _ = context
+                        .events
+                        .send(QueueEvent::Preloaded(request, Box::new(result)));
+                });
+            }
+            None => {
+                let _ = self.events.send(QueueEvent::Preload(request, source));
+            }
+        }
+    }
+
+    /// 预加载完成，将音轨追加到Sink中或等待交叉淡化
+    fn on_preloaded(&self, request: Preload, result: Result<Track>) {
+        let Preload {
+            id,
+            index,
+            generation,
+        } = request;
+        let mut queue = self.queue.lock().unwrap();
+        if queue.generation != generation {
+            // 队列已变化，丢弃预加载结果
+            return;
+        }
+        queue.preloading = None;
+        match result {
+            Ok(track) => {
+                // 当前音轨可交叉淡化且尚未到达淡出位置时，等待交叉淡化
+                let can_crossfade = self.crossfade.get().is_some()
+                    && queue.tracks.len() == 1
+                    && queue.tracks.front().is_some_and(|t| {
+                        Some(t.id) == queue.current_id
+                            && t.duration.is_some()
+                            && !t.fade.triggered()
+                    });
+                if can_crossfade {
+                    queue.set_ready(ReadyTrack { id, index, track });
+                } else {
+                    self.append(&mut queue, id, index, track);
+                }
+            }
+            Err(e) => {
+                let current_ended = queue.current_ended;
+                drop(queue);
+                self.emit(PlayerEvent::Error { error: e });
+                if current_ended {
+                    self.ended.store(true, Ordering::SeqCst);
+                    self.emit(PlayerEvent::Ended);
+                }
+            }
+        }
+    }
+
+    /// 创建流内元数据的处理函数，元数据由工作线程处理
+    pub fn metadata_handler(&self, id: u64) -> impl Fn(AudioMetadata) + Send + Sync + 'static {
+        let sender = self.events.clone();
+        move |update| {
+            let _ = sender.send(QueueEvent::Metadata(id, Box::new(update)));
+        }
+    }
+
+    /// 收到流内元数据
+    ///
+    /// 音轨为当前音轨时合并元数据并发送元数据变化事件，否则暂存到预加载的音轨中
+    fn on_metadata(&self, id: u64, update: Box<AudioMetadata>) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.current_id != Some(id) {
+            let metadata = match queue.tracks.iter_mut().find(|t| t.id == id) {
+                Some(track) => &mut track.metadata,
+                None => match queue.ready.as_mut().filter(|r| r.id == id) {
+                    Some(ready) => &mut ready.track.metadata,
+                    None => return,
+                },
+            };
+            metadata
+                .get_or_insert_with(AudioMetadata::default)
+                .merge(*update);
+            return;
+        }
+        let metadata = {
+            let control = self.control.read().unwrap();
+            let mut metadata = control.metadata.lock().unwrap();
+            let metadata = metadata.get_or_insert_with(AudioMetadata::default);
+            metadata.merge(*update);
+            metadata.clone()
+        };
+        drop(queue);
+        self.emit(PlayerEvent::MetadataChange {
+            metadata: Box::new(metadata),
+        });
+    }
+
+    /// 音轨的缓冲状态变化，当前音轨的状态变化以 `Waiting`/`Playing` 事件发送
+    fn on_buffer_state(&self, id: u64, state: BufferState) {
+        if self.queue.lock().unwrap().current_id != Some(id) {
+            return;
+        }
+        self.waiting
+            .store(state == BufferState::Waiting, Ordering::SeqCst);
+        self.emit(match state {
+            BufferState::Waiting => PlayerEvent::Waiting,
+            BufferState::Playing => PlayerEvent::Playing,
+        });
+    }
+}
+
+/// 在阻塞线程池中探测格式并创建网络音频的解码器，读取数据时可能等待下载
+async fn build_decoder(builder: DecoderBuilder<StreamReader>) -> Result<Decoder<StreamReader>> {
+    let decoder = tokio::task::spawn_blocking(move || builder.build())
+        .await
+        .map_err(|_| Error::Cancelled)??;
+    Ok(decoder)
+}
+
+/// 启用预解码时在解码线程中解码网络音频，播放线程不再等待网络数据
+fn with_prefetch(
+    source: impl Source + Send + 'static,
+    prefetch: Option<Duration>,
+) -> Box<dyn Source + Send> {
+    match (prefetch, Handle::try_current()) {
+        (Some(buffer), Ok(handle)) => Box::new(PrefetchSource::new(source, buffer, &handle)),
+        _ => Box::new(source),
+    }
+}
+
+/// 可被移出队列的音频源，移出后立即结束
+struct QueuedSource {
+    inner: Box<dyn Source + Send>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl QueuedSource {
+    fn new(inner: Box<dyn Source + Send>, cancelled: Arc<AtomicBool>) -> Self {
+        Self { inner, cancelled }
+    }
+}
+
+impl Iterator for QueuedSource {
+    type Item = rodio::Sample;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+        self.inner.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl Source for QueuedSource {
+    #[inline]
+    fn current_span_len(&self) -> Option<usize> {
+        self.inner.current_span_len()
+    }
+
+    #[inline]
+    fn channels(&self) -> ChannelCount {
+        self.inner.channels()
+    }
+
+    #[inline]
+    fn sample_rate(&self) -> SampleRate {
+        self.inner.sample_rate()
+    }
+
+    #[inline]
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    #[inline]
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)
+    }
+}
+
+/// 在后台线程中处理队列事件：音轨切换、交叉淡化、预加载、流内元数据及缓冲状态变化
+///
+/// 收到 `Shutdown` 或所有发送端释放后线程退出
+pub(crate) fn spawn_worker(receiver: mpsc::Receiver<QueueEvent>, context: QueueContext) {
+    std::thread::spawn(move || {
+        for event in receiver {
+            match event {
+                QueueEvent::Buffer(id, state) => context.on_buffer_state(id, state),
+                QueueEvent::TrackStart(id) => context.on_track_start(id),
+                QueueEvent::TrackEnd(id) => context.on_track_end(id),
+                QueueEvent::FadePoint(id) => context.on_fade_point(id),
+                QueueEvent::Preload(request, source) => {
+                    let result = futures::executor::block_on(context.open(request.id, source));
+                    context.on_preloaded(request, result);
+                }
+                QueueEvent::Preloaded(request, result) => context.on_preloaded(request, *result),
+                QueueEvent::Metadata(id, metadata) => context.on_metadata(id, metadata),
+                QueueEvent::Shutdown => break,
+            }
+        }
+    });
+}
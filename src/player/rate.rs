@@ -0,0 +1,510 @@
+use rodio::source::SeekError;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 播放速率的范围
+const MIN_RATE: f32 = 0.25;
+const MAX_RATE: f32 = 4.0;
+/// 音高偏移的范围（半音）
+const MAX_PITCH: f32 = 24.0;
+
+/// 每段的时长（毫秒）
+const SEQUENCE_MS: u64 = 40;
+/// 寻找最佳拼接位置的范围（毫秒）
+const SEEK_MS: u64 = 15;
+/// 相邻两段交叉淡化的时长（毫秒）
+const OVERLAP_MS: u64 = 8;
+
+/// 播放速率设置，由播放器及各音轨的变速源共享
+#[derive(Debug)]
+pub(crate) struct RateSettings {
+    /// 播放速率（f32 的位表示）
+    rate: AtomicU32,
+    /// 音高偏移，单位为半音（f32 的位表示）
+    pitch: AtomicU32,
+    /// 变速时是否保持音高
+    preserves_pitch: AtomicBool,
+}
+
+impl Default for RateSettings {
+    fn default() -> Self {
+        Self {
+            rate: AtomicU32::new(1f32.to_bits()),
+            pitch: AtomicU32::new(0f32.to_bits()),
+            preserves_pitch: AtomicBool::new(true),
+        }
+    }
+}
+
+impl RateSettings {
+    pub fn rate(&self) -> f32 {
+        f32::from_bits(self.rate.load(Ordering::Relaxed))
+    }
+
+    /// 设置播放速率，超出范围时取边界值，返回实际的速率
+    pub fn set_rate(&self, rate: f32) -> f32 {
+        let rate = if rate.is_finite() {
+            rate.clamp(MIN_RATE, MAX_RATE)
+        } else {
+            1.0
+        };
+        self.rate.store(rate.to_bits(), Ordering::Relaxed);
+        rate
+    }
+
+    pub fn pitch(&self) -> f32 {
+        f32::from_bits(self.pitch.load(Ordering::Relaxed))
+    }
+
+    pub fn set_pitch(&self, semitones: f32) {
+        let semitones = if semitones.is_finite() {
+            semitones.clamp(-MAX_PITCH, MAX_PITCH)
+        } else {
+            0.0
+        };
+        self.pitch.store(semitones.to_bits(), Ordering::Relaxed);
+    }
+
+    pub fn preserves_pitch(&self) -> bool {
+        self.preserves_pitch.load(Ordering::Relaxed)
+    }
+
+    pub fn set_preserves_pitch(&self, preserves_pitch: bool) {
+        self.preserves_pitch
+            .store(preserves_pitch, Ordering::Relaxed);
+    }
+
+    /// 当前设置的副本，不再随播放器的设置变化
+    pub fn snapshot(&self) -> Self {
+        Self {
+            rate: AtomicU32::new(self.rate.load(Ordering::Relaxed)),
+            pitch: AtomicU32::new(self.pitch.load(Ordering::Relaxed)),
+            preserves_pitch: AtomicBool::new(self.preserves_pitch()),
+        }
+    }
+
+    /// 时间伸缩的比例（输出帧数与输入帧数之比）
+    ///
+    /// 速率由 Sink 以重采样的方式实现，会同时改变音高；
+    /// 变速源先按此比例伸缩时长，使重采样后的音高为目标音高
+    fn stretch_ratio(&self) -> f64 {
+        let rate = self.rate() as f64;
+        let mut pitch = 2f64.powf(self.pitch() as f64 / 12.0);
+        if !self.preserves_pitch() {
+            pitch *= rate;
+        }
+        pitch / rate
+    }
+}
+
+/// 媒体时间时钟，由 Sink 中正在播放的变速源更新
+///
+/// Sink 的播放位置按输出的采样计算，变速时与媒体时间不一致
+#[derive(Debug, Default)]
+pub(crate) struct MediaClock {
+    /// 当前位置（纳秒），精确到帧以便按采样循环
+    nanos: AtomicU64,
+}
+
+impl MediaClock {
+    pub fn position(&self) -> Duration {
+        Duration::from_nanos(self.nanos.load(Ordering::Relaxed))
+    }
+
+    fn set(&self, position: Duration) {
+        self.nanos
+            .store(position.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+/// 变速源，以 WSOLA 的方式伸缩音频时长而不改变音高
+///
+/// 输出按段组成独立的 span，其采样率为原采样率乘以伸缩比例，
+/// 再由 Sink 的变速重采样为目标速率及音高。无需伸缩时直接输出输入的采样。
+/// 输入的声道数或采样率在 span 边界处变化时，先输出缓冲的输入，再按新的格式继续
+pub(crate) struct TimeStretch {
+    inner: Box<dyn Source + Send>,
+    settings: Arc<RateSettings>,
+    clock: Arc<MediaClock>,
+    /// 最近一次跳转或格式变化时的位置（秒），之后的输入位置（帧）均相对于此
+    offset: f64,
+    channels: ChannelCount,
+    input_rate: SampleRate,
+    /// 输入的当前 span 中剩余的采样数，`None` 表示直到结束
+    span_remaining: Option<usize>,
+    /// 输入已到达格式变化的位置
+    format_pending: bool,
+    /// 输入缓冲（交错排列），首帧的位置为 `input_start`
+    input: Vec<f32>,
+    input_start: u64,
+    /// 输入是否已读完（或已到达格式变化的位置）
+    input_ended: bool,
+    /// 下一段的理想输入位置（帧）
+    pos: f64,
+    /// 上一段末尾尚未输出、用于交叉淡化的部分的起始帧
+    tail: Option<u64>,
+    /// 当前输出段
+    chunk: Vec<f32>,
+    chunk_pos: usize,
+    /// 当前段直接输出输入时，段内剩余的采样数
+    bypass: usize,
+    /// 当前输出段的采样率
+    chunk_rate: SampleRate,
+    /// 当前输出段起始处的输入位置，及每输出一帧前进的输入帧数
+    chunk_start: f64,
+    chunk_step: f64,
+}
+
+impl TimeStretch {
+    pub fn new(
+        inner: Box<dyn Source + Send>,
+        settings: Arc<RateSettings>,
+        clock: Arc<MediaClock>,
+    ) -> Self {
+        let channels = inner.channels().max(1);
+        let input_rate = inner.sample_rate().max(1);
+        let span_remaining = inner.current_span_len();
+        let chunk_rate = Self::rate_for(input_rate, settings.stretch_ratio());
+        Self {
+            inner,
+            settings,
+            clock,
+            offset: 0.0,
+            channels,
+            input_rate,
+            span_remaining,
+            format_pending: false,
+            input: Vec::new(),
+            input_start: 0,
+            input_ended: false,
+            pos: 0.0,
+            tail: None,
+            chunk: Vec::new(),
+            chunk_pos: 0,
+            bypass: 0,
+            chunk_rate,
+            chunk_start: 0.0,
+            chunk_step: 1.0,
+        }
+    }
+
+    fn rate_for(sample_rate: SampleRate, ratio: f64) -> SampleRate {
+        ((sample_rate as f64 * ratio).round() as SampleRate).max(1)
+    }
+
+    fn frames(&self, ms: u64) -> u64 {
+        (self.input_rate as u64 * ms / 1000).max(1)
+    }
+
+    /// 已缓冲输入的结束位置（帧）
+    fn input_end(&self) -> u64 {
+        self.input_start + (self.input.len() / self.channels as usize) as u64
+    }
+
+    /// 到达输入的 span 边界时检查格式是否变化，变化时视为输入结束并设置 `format_pending`
+    fn check_span(&mut self) {
+        if self.span_remaining != Some(0) || self.format_pending {
+            return;
+        }
+        if self.inner.channels().max(1) != self.channels
+            || self.inner.sample_rate().max(1) != self.input_rate
+        {
+            self.format_pending = true;
+            self.input_ended = true;
+        } else {
+            self.span_remaining = self.inner.current_span_len();
+        }
+    }
+
+    /// 读取一个输入采样，到达格式变化的位置时返回 `None`
+    fn read_sample(&mut self) -> Option<f32> {
+        self.check_span();
+        if self.format_pending {
+            return None;
+        }
+        let sample = self.inner.next()?;
+        if let Some(ref mut remaining) = self.span_remaining {
+            *remaining = remaining.saturating_sub(1);
+        }
+        Some(sample)
+    }
+
+    /// 缓冲的输入均已输出后，按输入的新格式重新开始
+    fn switch_format(&mut self) {
+        self.offset += self.input_end() as f64 / self.input_rate as f64;
+        self.channels = self.inner.channels().max(1);
+        self.input_rate = self.inner.sample_rate().max(1);
+        self.span_remaining = self.inner.current_span_len();
+        self.format_pending = false;
+        self.input.clear();
+        self.input_start = 0;
+        self.input_ended = false;
+        self.pos = 0.0;
+        self.tail = None;
+    }
+
+    /// 读取输入直到指定位置或输入结束
+    fn fill_input(&mut self, end: u64) {
+        while !self.input_ended && self.input_end() < end {
+            for _ in 0..self.channels {
+                match self.read_sample() {
+                    Some(sample) => self.input.push(sample),
+                    None => {
+                        self.input_ended = true;
+                        // 丢弃不完整的帧
+                        let len = self.input.len() - self.input.len() % self.channels as usize;
+                        self.input.truncate(len);
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    fn frame(&self, pos: u64) -> &[f32] {
+        let channels = self.channels as usize;
+        let offset = (pos - self.input_start) as usize * channels;
+        &self.input[offset..offset + channels]
+    }
+
+    /// 将输入中 `[start, end)` 的帧追加到输出段
+    fn copy_frames(&mut self, start: u64, end: u64) {
+        let channels = self.channels as usize;
+        let from = (start - self.input_start) as usize * channels;
+        let to = (end - self.input_start) as usize * channels;
+        self.chunk.extend_from_slice(&self.input[from..to]);
+    }
+
+    /// 在 `[base, base + seek)` 中寻找与 `tail` 起始的重叠部分最相似的位置
+    fn best_offset(&self, tail: u64, base: u64, seek: u64, overlap: u64) -> u64 {
+        let mono = |pos: u64| self.frame(pos).iter().sum::<f32>();
+        let reference: Vec<f32> = (tail..tail + overlap).map(mono).collect();
+        let candidate: Vec<f32> = (base..base + seek + overlap).map(mono).collect();
+
+        let overlap = overlap as usize;
+        let mut energy: f32 = candidate[..overlap].iter().map(|s| s * s).sum();
+        let mut best = (0, f32::MIN);
+        for offset in 0..seek as usize {
+            if offset > 0 {
+                let removed = candidate[offset - 1];
+                let added = candidate[offset + overlap - 1];
+                energy = (energy - removed * removed + added * added).max(0.0);
+            }
+            let correlation: f32 = reference
+                .iter()
+                .zip(&candidate[offset..offset + overlap])
+                .map(|(a, b)| a * b)
+                .sum();
+            let score = correlation / (energy + 1e-6).sqrt();
+            if score > best.1 {
+                best = (offset, score);
+            }
+        }
+        best.0 as u64
+    }
+
+    /// 生成下一个输出段，输入结束时输出段为空
+    fn next_chunk(&mut self) {
+        self.chunk.clear();
+        self.chunk_pos = 0;
+        self.bypass = 0;
+        self.check_span();
+
+        let ratio = self.settings.stretch_ratio();
+        let sequence = self.frames(SEQUENCE_MS);
+        let seek = self.frames(SEEK_MS);
+        let overlap = self.frames(OVERLAP_MS).min(sequence / 2);
+        let hop = sequence - overlap;
+        let bypass = (ratio - 1.0).abs() < 1e-3;
+
+        if bypass && self.tail.is_none() && self.input.is_empty() && !self.input_ended {
+            // 无需伸缩且没有缓冲的输入，直接输出输入的采样，段不跨越输入的 span
+            let channels = self.channels as usize;
+            let samples = self.span_remaining.unwrap_or(usize::MAX);
+            self.bypass = (hop as usize * channels).min(samples).max(channels);
+            self.input_start = self.pos as u64;
+            self.chunk_rate = self.input_rate;
+            self.chunk_start = self.pos;
+            self.chunk_step = 1.0;
+            return;
+        }
+        if bypass {
+            // 无需伸缩，从上一段未输出的部分继续
+            let start = self.tail.take().unwrap_or(self.pos as u64);
+            self.fill_input(start + hop);
+            let end = (start + hop).min(self.input_end());
+            if start < end {
+                self.copy_frames(start, end);
+            }
+            self.pos = end as f64;
+            self.chunk_rate = self.input_rate;
+            self.chunk_start = start as f64;
+            self.chunk_step = 1.0;
+        } else {
+            let base = self.pos as u64;
+            self.fill_input(base + seek + sequence);
+            if self.input_end() < base + seek + sequence {
+                // 输入即将结束，直接输出剩余部分
+                let start = self.tail.take().unwrap_or(base).min(self.input_end());
+                let end = self.input_end();
+                self.copy_frames(start, end);
+                self.pos = end as f64;
+                self.chunk_start = start as f64;
+                self.chunk_step = 1.0;
+            } else {
+                let start = match self.tail {
+                    Some(tail) => {
+                        let start = base + self.best_offset(tail, base, seek, overlap);
+                        // 与上一段末尾交叉淡化
+                        for i in 0..overlap {
+                            let weight = i as f32 / overlap as f32;
+                            for channel in 0..self.channels as usize {
+                                let previous = self.frame(tail + i)[channel];
+                                let next = self.frame(start + i)[channel];
+                                self.chunk.push(previous * (1.0 - weight) + next * weight);
+                            }
+                        }
+                        self.copy_frames(start + overlap, start + hop);
+                        start
+                    }
+                    None => {
+                        self.copy_frames(base, base + hop);
+                        base
+                    }
+                };
+                self.tail = Some(start + hop);
+                self.chunk_start = self.pos;
+                self.chunk_step = 1.0 / ratio;
+                self.pos += hop as f64 / ratio;
+            }
+            self.chunk_rate = Self::rate_for(self.input_rate, ratio);
+        }
+
+        // 缓冲的输入均已输出，按新的格式继续
+        if self.chunk.is_empty() && self.format_pending {
+            self.switch_format();
+            self.next_chunk();
+            return;
+        }
+
+        // 丢弃不再需要的输入
+        let keep = self.tail.unwrap_or(u64::MAX).min(self.pos as u64);
+        if keep > self.input_start {
+            let frames = (keep - self.input_start).min(self.input_end() - self.input_start);
+            self.input.drain(..frames as usize * self.channels as usize);
+            self.input_start += frames;
+        }
+    }
+}
+
+impl TimeStretch {
+    /// 直接输出一个输入采样
+    fn next_bypassed(&mut self) -> Option<rodio::Sample> {
+        let channels = self.channels as usize;
+        if self.bypass.is_multiple_of(channels) {
+            self.clock.set(Duration::from_secs_f64(
+                self.offset + self.pos / self.input_rate as f64,
+            ));
+        }
+        let Some(sample) = self.read_sample() else {
+            self.bypass = 0;
+            if self.format_pending {
+                // 输入的 span 短于一帧时才会在段内遇到格式变化
+                self.next_chunk();
+                return self.next();
+            }
+            self.input_ended = true;
+            return None;
+        };
+        self.bypass -= 1;
+        if self.bypass.is_multiple_of(channels) {
+            self.pos += 1.0;
+            self.input_start = self.pos as u64;
+        }
+        if self.bypass == 0 {
+            self.next_chunk();
+        }
+        Some(sample)
+    }
+}
+
+impl Iterator for TimeStretch {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.bypass > 0 {
+            return self.next_bypassed();
+        }
+        if self.chunk_pos >= self.chunk.len() {
+            self.next_chunk();
+            if self.bypass > 0 {
+                return self.next_bypassed();
+            }
+        }
+        let sample = *self.chunk.get(self.chunk_pos)?;
+        let channels = self.channels as usize;
+        if self.chunk_pos.is_multiple_of(channels) {
+            let frame = (self.chunk_pos / channels) as f64;
+            let pos = self.chunk_start + frame * self.chunk_step;
+            self.clock.set(Duration::from_secs_f64(
+                self.offset + pos / self.input_rate as f64,
+            ));
+        }
+        self.chunk_pos += 1;
+        // 提前生成下一段，使 span 的长度及采样率在段的边界处可用
+        if self.chunk_pos == self.chunk.len() {
+            self.next_chunk();
+        }
+        Some(sample)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.bypass + self.chunk.len() - self.chunk_pos, None)
+    }
+}
+
+impl Source for TimeStretch {
+    fn current_span_len(&self) -> Option<usize> {
+        if self.bypass > 0 {
+            return Some(self.bypass);
+        }
+        match self.chunk.len() - self.chunk_pos {
+            0 => None,
+            len => Some(len),
+        }
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.channels
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.chunk_rate
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.inner.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        self.inner.try_seek(pos)?;
+        self.clock.set(pos);
+        self.offset = pos.as_secs_f64();
+        // 跳转后输入的格式可能变化
+        self.channels = self.inner.channels().max(1);
+        self.input_rate = self.inner.sample_rate().max(1);
+        self.span_remaining = self.inner.current_span_len();
+        self.format_pending = false;
+        self.bypass = 0;
+        self.input.clear();
+        self.input_start = 0;
+        self.input_ended = false;
+        self.pos = 0.0;
+        self.tail = None;
+        self.chunk.clear();
+        self.chunk_pos = 0;
+        Ok(())
+    }
+}
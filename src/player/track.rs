@@ -0,0 +1,180 @@
+use rodio::Source;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::loader::downloader::Downloader;
+use crate::loader::hls::HlsLoader;
+use crate::loader::LoaderOptions;
+use crate::metadata::AudioMetadata;
+use crate::reader::{
+    BlockingReader, BufferMonitor, MVecBytesAsyncReader, MVecBytesReader, MVecBytesWrapper,
+};
+
+/// 可作为音频数据来源的 Reader
+pub trait ReadSeek: Read + Seek + Send + Sync {}
+
+impl<T: Read + Seek + Send + Sync> ReadSeek for T {}
+
+/// 音轨的数据来源
+pub(crate) enum TrackSource {
+    /// 本地文件
+    File(PathBuf),
+    /// 网络地址及请求选项
+    Url(String, Arc<LoaderOptions>),
+    /// 自定义Reader
+    Reader(Box<dyn ReadSeek + 'static>),
+    /// 自定义Source
+    Source(Box<dyn Source + Send + 'static>),
+}
+
+/// 已打开、可直接追加到Sink中播放的音轨
+pub(crate) struct Track {
+    /// 解码后的音频源
+    pub source: Box<dyn Source + Send + 'static>,
+    /// 音轨时长
+    pub duration: Option<Duration>,
+    /// 音轨元数据
+    pub metadata: Option<AudioMetadata>,
+    /// 网络音频的下载资源
+    pub streaming: Option<Streaming>,
+}
+
+impl Track {
+    pub fn new(
+        source: Box<dyn Source + Send + 'static>,
+        metadata: Option<AudioMetadata>,
+        streaming: Option<Streaming>,
+    ) -> Self {
+        Self {
+            duration: source.total_duration(),
+            source,
+            metadata,
+            streaming,
+        }
+    }
+}
+
+/// 网络音频的加载器
+pub(crate) trait StreamLoader: Send + Sync {
+    /// 已缓冲的时间范围，`duration` 为音轨时长
+    fn buffered(&self, duration: Duration) -> Vec<Range<Duration>>;
+}
+
+impl StreamLoader for Downloader {
+    fn buffered(&self, duration: Duration) -> Vec<Range<Duration>> {
+        let total = self.total_bytes();
+        if total == 0 {
+            return Vec::new();
+        }
+        // 按平均码率将字节范围换算为时间范围
+        let time = |pos: u64| duration.mul_f64(pos.min(total) as f64 / total as f64);
+        self.buffered()
+            .into_iter()
+            .map(|range| time(range.start)..time(range.end))
+            .collect()
+    }
+}
+
+impl StreamLoader for HlsLoader {
+    fn buffered(&self, duration: Duration) -> Vec<Range<Duration>> {
+        // 分片按顺序下载，已缓冲的部分总是从头开始
+        let end = self.buffered_duration().min(duration);
+        if end.is_zero() {
+            return Vec::new();
+        }
+        vec![Duration::ZERO..end]
+    }
+}
+
+/// 网络音频的下载资源，释放时通知Reader停止等待
+pub(crate) struct Streaming {
+    /// 下载器（释放时中断下载）
+    loader: Box<dyn StreamLoader>,
+    /// 条件变量
+    condvar: Arc<Condvar>,
+    /// Reader的取消令牌
+    cancellation_token: CancellationToken,
+}
+
+impl Streaming {
+    pub fn new<L: StreamLoader + 'static>(
+        condvar: Arc<Condvar>,
+        cancellation_token: CancellationToken,
+        loader: L,
+    ) -> Self {
+        Self {
+            loader: Box::new(loader),
+            condvar,
+            cancellation_token,
+        }
+    }
+
+    /// 已缓冲的时间范围
+    pub fn buffered(&self, duration: Duration) -> Vec<Range<Duration>> {
+        self.loader.buffered(duration)
+    }
+}
+
+impl Drop for Streaming {
+    fn drop(&mut self) {
+        // 通知Reader取消读取，以免造成阻塞
+        self.cancellation_token.cancel();
+
+        // 通知Reader所在的播放线程无需等待，以免导致不再使用的播放进程仍然阻塞
+        self.condvar.notify_all();
+    }
+}
+
+/// 网络音频的Reader
+///
+/// 预解码时在解码任务中读取异步Reader，等待数据时不依赖加载器的条件变量；否则在播放线程中阻塞读取
+pub(crate) enum StreamReader {
+    Blocking(MVecBytesReader),
+    Async(BlockingReader<MVecBytesAsyncReader>),
+}
+
+impl StreamReader {
+    pub fn new(wrapper: MVecBytesWrapper, condvar: Arc<Condvar>, prefetch: bool) -> Self {
+        if prefetch {
+            StreamReader::Async(BlockingReader(MVecBytesAsyncReader::new(wrapper)))
+        } else {
+            StreamReader::Blocking(MVecBytesReader::new(wrapper, condvar))
+        }
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        match self {
+            StreamReader::Blocking(reader) => reader.cancellation_token(),
+            StreamReader::Async(reader) => reader.0.cancellation_token(),
+        }
+    }
+
+    pub fn buffer_monitor(&self) -> Arc<BufferMonitor> {
+        match self {
+            StreamReader::Blocking(reader) => reader.buffer_monitor(),
+            StreamReader::Async(reader) => reader.0.buffer_monitor(),
+        }
+    }
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            StreamReader::Blocking(reader) => reader.read(buf),
+            StreamReader::Async(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for StreamReader {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            StreamReader::Blocking(reader) => reader.seek(pos),
+            StreamReader::Async(reader) => reader.seek(pos),
+        }
+    }
+}
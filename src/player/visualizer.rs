@@ -0,0 +1,283 @@
+use rodio::{ChannelCount, SampleRate};
+use std::f32::consts::PI;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 缓冲区保存的帧数（2的幂）
+const TAP_FRAMES: usize = 1 << 15;
+/// 频谱分析支持的最大 FFT 长度
+pub const MAX_FFT_SIZE: usize = TAP_FRAMES / 2;
+/// 读取时数据被覆盖后的最大重试次数
+const READ_RETRIES: usize = 3;
+
+/// 输出音频的环形缓冲区
+///
+/// 音频线程写入采样时只进行原子操作，不会等待读取方；
+/// 读取时若数据已被新的采样覆盖则重新读取
+pub(crate) struct SampleTap {
+    /// 采样的位表示
+    buffer: Box<[AtomicU32]>,
+    /// 已写入的采样总数
+    written: AtomicU64,
+    /// 清空时的写入位置，读取时忽略此前的采样
+    start: AtomicU64,
+    channels: ChannelCount,
+    sample_rate: SampleRate,
+}
+
+impl SampleTap {
+    pub fn new(channels: ChannelCount, sample_rate: SampleRate) -> Self {
+        let capacity = (TAP_FRAMES * channels.max(1) as usize).next_power_of_two();
+        Self {
+            buffer: (0..capacity).map(|_| AtomicU32::new(0)).collect(),
+            written: AtomicU64::new(0),
+            start: AtomicU64::new(0),
+            channels: channels.max(1),
+            sample_rate,
+        }
+    }
+
+    /// 写入交错排列的整帧采样，仅由音频线程调用
+    pub fn write(&self, samples: &[f32]) {
+        let mask = self.buffer.len() - 1;
+        let written = self.written.load(Ordering::Relaxed);
+        for (i, sample) in samples.iter().enumerate() {
+            let index = (written as usize + i) & mask;
+            self.buffer[index].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.written
+            .store(written + samples.len() as u64, Ordering::Release);
+    }
+
+    /// 丢弃已写入的采样（如跳转后）
+    pub fn clear(&self) {
+        self.start
+            .store(self.written.load(Ordering::Acquire), Ordering::Release);
+    }
+
+    /// 读取最近的至多 `frames` 帧
+    fn read(&self, frames: usize) -> Vec<f32> {
+        let channels = self.channels as usize;
+        let capacity = self.buffer.len() as u64;
+        let mask = self.buffer.len() - 1;
+        let mut samples = Vec::new();
+        for _ in 0..READ_RETRIES {
+            let end = self.written.load(Ordering::Acquire);
+            let start = self
+                .start
+                .load(Ordering::Acquire)
+                .max(end.saturating_sub(capacity));
+            let len = ((end - start) as usize).min(frames * channels);
+            let len = len - len % channels;
+            let begin = end - len as u64;
+            samples.clear();
+            samples.extend(
+                (begin..end).map(|i| {
+                    f32::from_bits(self.buffer[i as usize & mask].load(Ordering::Relaxed))
+                }),
+            );
+            // 读取期间最早的采样未被覆盖
+            if self.written.load(Ordering::Acquire) - begin <= capacity {
+                break;
+            }
+        }
+        samples
+    }
+}
+
+/// 窗函数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WindowFunction {
+    Rectangular,
+    #[default]
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+impl WindowFunction {
+    /// 长度为 `size` 的窗的第 `n` 个系数
+    fn coefficient(&self, n: usize, size: usize) -> f32 {
+        let x = 2.0 * PI * n as f32 / (size - 1).max(1) as f32;
+        match self {
+            WindowFunction::Rectangular => 1.0,
+            WindowFunction::Hann => 0.5 - 0.5 * x.cos(),
+            WindowFunction::Hamming => 0.54 - 0.46 * x.cos(),
+            WindowFunction::Blackman => 0.42 - 0.5 * x.cos() + 0.08 * (2.0 * x).cos(),
+        }
+    }
+}
+
+/// 频谱分析设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpectrumOptions {
+    /// FFT 长度，须为 2 的幂，不超过 [`MAX_FFT_SIZE`]
+    pub size: usize,
+    pub window: WindowFunction,
+}
+
+impl Default for SpectrumOptions {
+    fn default() -> Self {
+        Self {
+            size: 2048,
+            window: WindowFunction::Hann,
+        }
+    }
+}
+
+/// 幅度谱
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spectrum {
+    /// 各频率的幅度，共 `size / 2 + 1` 个，满幅正弦波对应约 1.0
+    pub magnitudes: Vec<f32>,
+    /// 相邻频率的间隔（Hz）
+    pub bin_width: f32,
+}
+
+impl Spectrum {
+    /// 第 `bin` 个幅度对应的频率（Hz）
+    pub fn frequency(&self, bin: usize) -> f32 {
+        bin as f32 * self.bin_width
+    }
+}
+
+/// 单个声道的电平
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ChannelLevel {
+    /// 均方根
+    pub rms: f32,
+    /// 峰值
+    pub peak: f32,
+}
+
+/// 输出音频的可视化数据
+///
+/// 数据取自发送到音频输出的采样（已应用音量及交叉淡化），跳转后清空，
+/// 因此总是与 `position()` 对应；可在任意线程中读取，不会阻塞音频线程
+///
+/// # Example
+///
+/// ```no_run
+/// use remu_audio::player::{Player, SpectrumOptions, WindowFunction};
+/// use std::time::Duration;
+///
+/// let player = Player::new().unwrap();
+/// let visualizer = player.visualizer();
+/// // 每帧刷新界面时读取
+/// let levels = visualizer.levels(Duration::from_millis(50));
+/// let spectrum = visualizer.spectrum(SpectrumOptions {
+///     size: 4096,
+///     window: WindowFunction::Blackman,
+/// });
+/// ```
+#[derive(Clone)]
+pub struct Visualizer {
+    tap: Arc<SampleTap>,
+}
+
+impl Visualizer {
+    pub(crate) fn new(tap: Arc<SampleTap>) -> Self {
+        Self { tap }
+    }
+
+    pub fn channels(&self) -> ChannelCount {
+        self.tap.channels
+    }
+
+    pub fn sample_rate(&self) -> SampleRate {
+        self.tap.sample_rate
+    }
+
+    /// 最近至多 `frames` 帧的交错排列的采样，用于绘制波形
+    pub fn samples(&self, frames: usize) -> Vec<f32> {
+        self.tap.read(frames)
+    }
+
+    /// 最近 `window` 时长内各声道的电平，没有数据时均为 0
+    pub fn levels(&self, window: Duration) -> Vec<ChannelLevel> {
+        let channels = self.tap.channels as usize;
+        let frames = (window.as_secs_f64() * self.tap.sample_rate as f64).ceil() as usize;
+        let samples = self.tap.read(frames.max(1));
+        let mut levels = vec![ChannelLevel::default(); channels];
+        if samples.is_empty() {
+            return levels;
+        }
+        for frame in samples.chunks_exact(channels) {
+            for (level, &sample) in levels.iter_mut().zip(frame) {
+                level.rms += sample * sample;
+                level.peak = level.peak.max(sample.abs());
+            }
+        }
+        let frames = (samples.len() / channels) as f32;
+        for level in levels.iter_mut() {
+            level.rms = (level.rms / frames).sqrt();
+        }
+        levels
+    }
+
+    /// 最近 `options.size` 帧（各声道混合为单声道）的幅度谱，数据不足时以 0 补齐
+    ///
+    /// `options.size` 不是 2 的幂时向上取整，并限制在 [`MAX_FFT_SIZE`] 以内
+    pub fn spectrum(&self, options: SpectrumOptions) -> Spectrum {
+        let size = options.size.clamp(2, MAX_FFT_SIZE).next_power_of_two();
+        let channels = self.tap.channels as usize;
+        let samples = self.tap.read(size);
+
+        // 数据不足时保持最新的采样位于末尾
+        let mut re = vec![0.0; size];
+        let offset = size - samples.len() / channels;
+        for (i, frame) in samples.chunks_exact(channels).enumerate() {
+            re[offset + i] = frame.iter().sum::<f32>() / channels as f32;
+        }
+        let mut gain = 0.0;
+        for (n, value) in re.iter_mut().enumerate() {
+            let w = options.window.coefficient(n, size);
+            *value *= w;
+            gain += w;
+        }
+        let mut im = vec![0.0; size];
+        fft(&mut re, &mut im);
+
+        let scale = 2.0 / gain;
+        let magnitudes = (0..=size / 2)
+            .map(|k| (re[k] * re[k] + im[k] * im[k]).sqrt() * scale)
+            .collect();
+        Spectrum {
+            magnitudes,
+            bin_width: self.tap.sample_rate as f32 / size as f32,
+        }
+    }
+}
+
+/// 原地计算长度为 2 的幂的复数 FFT（基2，按时间抽取）
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+    let bits = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - bits);
+        if j > i {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let (sin, cos) = (-2.0 * PI / len as f32).sin_cos();
+        for start in (0..n).step_by(len) {
+            let (mut w_re, mut w_im) = (1.0f32, 0.0f32);
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * w_re - im[b] * w_im;
+                let t_im = re[b] * w_im + im[b] * w_re;
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+                (w_re, w_im) = (w_re * cos - w_im * sin, w_re * sin + w_im * cos);
+            }
+        }
+        len <<= 1;
+    }
+}
@@ -1,8 +1,16 @@
 mod mutex_vec_bytes;
 mod mutex_vec_u8;
 
-pub use mutex_vec_bytes::{MVecBytesReader, MVecBytesWrapper};
-pub use mutex_vec_u8::{MVecU8Reader, MVecU8Wrapper};
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::Waker;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt};
+use tokio::sync::Notify;
+
+pub use mutex_vec_bytes::{ChunkData, MVecBytesAsyncReader, MVecBytesReader, MVecBytesWrapper};
+pub use mutex_vec_u8::{MVecU8AsyncReader, MVecU8Reader, MVecU8Wrapper};
 
 pub trait AppendableDataWrapper {
     /// 添加数据
@@ -11,4 +19,151 @@ pub trait AppendableDataWrapper {
     fn complete(&mut self);
     /// 设置容量
     fn set_capacity(&mut self, capacity: usize);
+
+    /// 设置数据总长度，以便Reader支持 `SeekFrom::End`
+    fn set_total_len(&mut self, _len: u64) {}
+    /// 启用Range请求，返回Reader发起的请求；不支持不连续写入时返回 `None`
+    fn enable_ranges(&mut self) -> Option<Arc<RangeRequest>> {
+        None
+    }
+    /// 将写入位置移动到 `pos` 附近可写入的位置，返回新的写入位置
+    fn seek_write(&mut self, pos: u64) -> u64 {
+        pos
+    }
+    /// 写入位置的数据是否已存在
+    fn write_pos_filled(&self) -> bool {
+        false
+    }
+    /// 第一个缺失数据的位置，数据完整时返回 `None`
+    fn first_missing(&self) -> Option<u64> {
+        None
+    }
+    /// 可供Reader读取的字节范围
+    fn buffered(&self) -> Vec<Range<u64>> {
+        Vec::new()
+    }
+}
+
+/// 数据变化时唤醒等待的异步Reader
+///
+/// 异步Reader在检查数据前注册唤醒器，数据写入、完成或总长度确定后由Wrapper唤醒
+#[derive(Debug, Default)]
+pub(crate) struct DataSignal {
+    wakers: Mutex<Vec<Waker>>,
+}
+
+impl DataSignal {
+    pub(crate) fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock().unwrap();
+        if !wakers.iter().any(|w| w.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+    }
+
+    pub(crate) fn wake(&self) {
+        let wakers = std::mem::take(&mut *self.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+/// 在当前线程中等待异步Reader的同步Reader
+///
+/// 用于在解码线程中读取异步Reader（如 [`MVecBytesAsyncReader`]、[`MVecU8AsyncReader`]），不应在异步任务中直接使用
+pub(crate) struct BlockingReader<R>(pub R);
+
+impl<R: AsyncRead + Unpin> Read for BlockingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        futures::executor::block_on(self.0.read(buf))
+    }
+}
+
+impl<R: AsyncSeek + Unpin> Seek for BlockingReader<R> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        futures::executor::block_on(self.0.seek(pos))
+    }
+}
+
+/// Reader发起的Range请求
+///
+/// Reader读取的位置远离下载位置时，通过此请求通知下载器从该位置重新下载
+#[derive(Debug, Default)]
+pub struct RangeRequest {
+    pos: Mutex<Option<u64>>,
+    notify: Notify,
+}
+
+impl RangeRequest {
+    /// 请求从指定位置开始下载
+    pub fn request(&self, pos: u64) {
+        *self.pos.lock().unwrap() = Some(pos);
+        self.notify.notify_one();
+    }
+
+    /// 等待下一个请求
+    pub async fn wait(&self) -> u64 {
+        loop {
+            if let Some(pos) = self.pos.lock().unwrap().take() {
+                return pos;
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Reader的缓冲状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferState {
+    /// 读取位置追上了下载位置，正在等待数据
+    Waiting,
+    /// 已缓冲足够的数据，恢复读取
+    Playing,
+}
+
+type BufferCallback = Box<dyn Fn(BufferState) + Send + Sync + 'static>;
+
+/// 缓冲状态监视器
+///
+/// Reader因数据不足而阻塞时通知 `Waiting`，之后缓冲的数据达到 `resume_bytes` 时才恢复读取并通知 `Playing`，
+/// 以免在下载速度接近播放速度时频繁切换状态。未设置回调时不做任何处理
+#[derive(Default)]
+pub struct BufferMonitor {
+    /// 恢复读取前需要缓冲的字节数
+    resume_bytes: AtomicU64,
+    callback: Mutex<Option<BufferCallback>>,
+}
+
+impl BufferMonitor {
+    /// 设置恢复读取前需要缓冲的字节数
+    pub fn set_resume_bytes(&self, bytes: u64) {
+        self.resume_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    /// 获取恢复读取前需要缓冲的字节数
+    pub fn resume_bytes(&self) -> u64 {
+        self.resume_bytes.load(Ordering::Relaxed)
+    }
+
+    /// 设置缓冲状态回调函数
+    ///
+    /// # 注意
+    /// 回调函数在读取数据的线程中调用，不应阻塞
+    pub fn set_callback<F>(&self, callback: F)
+    where
+        F: Fn(BufferState) + Send + Sync + 'static,
+    {
+        *self.callback.lock().unwrap() = Some(Box::new(callback));
+    }
+
+    /// 是否已设置回调函数
+    pub(crate) fn active(&self) -> bool {
+        self.callback.lock().unwrap().is_some()
+    }
+
+    pub(crate) fn notify(&self, state: BufferState) {
+        if let Some(ref cb) = *self.callback.lock().unwrap() {
+            cb(state);
+        }
+    }
 }
@@ -1,15 +1,93 @@
 use bytes::{Bytes, BytesMut};
+use std::future::Future;
 use std::io::{Read, Result, Seek, SeekFrom};
+use std::ops::Range;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
-use tokio_util::sync::CancellationToken;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
 
-use super::AppendableDataWrapper;
+use super::{AppendableDataWrapper, BufferMonitor, BufferState, DataSignal, RangeRequest};
+
+/// 读取位置超出下载位置的字节数达到此值时，发起Range请求
+const RANGE_REQUEST_DISTANCE: u64 = 1024 * 1024;
+
+/// 分块存储的数据
+///
+/// 数据按固定大小分块，Range请求下载的块可以不连续，未下载的块为 `None`
+#[derive(Debug)]
+pub struct ChunkData {
+    chunks: Vec<Option<Bytes>>,
+    chunk_size: usize,
+    /// 数据总长度（已知时）
+    total_len: Option<u64>,
+    /// 下载器正在写入的块索引
+    write_chunk: usize,
+    /// Reader发起的Range请求（服务器支持时）
+    range_request: Option<Arc<RangeRequest>>,
+}
+
+impl ChunkData {
+    fn new(chunk_size: usize) -> Self {
+        Self {
+            chunks: Vec::new(),
+            chunk_size,
+            total_len: None,
+            write_chunk: 0,
+            range_request: None,
+        }
+    }
+
+    /// 获取指定索引的块
+    pub fn chunk(&self, index: usize) -> Option<&Bytes> {
+        self.chunks.get(index).and_then(|c| c.as_ref())
+    }
+
+    /// 获取数据总长度
+    pub fn total_len(&self) -> Option<u64> {
+        self.total_len
+    }
+
+    /// 获取已下载的字节范围
+    pub fn ranges(&self) -> Vec<Range<u64>> {
+        let mut ranges: Vec<Range<u64>> = Vec::new();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            let Some(chunk) = chunk else {
+                continue;
+            };
+            let start = (index * self.chunk_size) as u64;
+            let end = start + chunk.len() as u64;
+            match ranges.last_mut() {
+                Some(last) if last.end == start => last.end = end,
+                _ => ranges.push(start..end),
+            }
+        }
+        ranges
+    }
+
+    fn set(&mut self, index: usize, chunk: Bytes) {
+        if index >= self.chunks.len() {
+            self.chunks.resize(index + 1, None);
+        }
+        // 已存在的块不再覆盖
+        self.chunks[index].get_or_insert(chunk);
+    }
+
+    /// 第一个缺失的块索引，总长度未知或数据完整时返回 `None`
+    fn first_missing(&self) -> Option<usize> {
+        let count = self.total_len?.div_ceil(self.chunk_size as u64) as usize;
+        (0..count).find(|&index| self.chunk(index).is_none())
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct MVecBytesWrapper {
-    data: Arc<Mutex<Vec<Bytes>>>,
+    data: Arc<Mutex<ChunkData>>,
     completed: Arc<AtomicBool>,
+    /// 唤醒等待数据的异步Reader
+    signal: Arc<DataSignal>,
     chunk_size: usize,
     current_chunk: BytesMut,
 }
@@ -17,14 +95,15 @@ pub struct MVecBytesWrapper {
 impl MVecBytesWrapper {
     pub fn new(chunk_size: usize) -> Self {
         Self {
-            data: Arc::new(Mutex::new(Vec::new())),
+            data: Arc::new(Mutex::new(ChunkData::new(chunk_size))),
             completed: Arc::new(AtomicBool::new(false)),
+            signal: Arc::new(DataSignal::default()),
             chunk_size,
             current_chunk: BytesMut::with_capacity(chunk_size),
         }
     }
 
-    pub fn data(&self) -> Arc<Mutex<Vec<Bytes>>> {
+    pub fn data(&self) -> Arc<Mutex<ChunkData>> {
         self.data.clone()
     }
     pub fn completed(&self) -> Arc<AtomicBool> {
@@ -33,223 +112,374 @@ impl MVecBytesWrapper {
     pub fn chunk_size(&self) -> usize {
         self.chunk_size
     }
+    pub(crate) fn signal(&self) -> Arc<DataSignal> {
+        self.signal.clone()
+    }
+
+    /// 冻结 current_chunk 并写入当前块
+    fn flush_chunk(&mut self) {
+        let chunk = std::mem::replace(
+            &mut self.current_chunk,
+            BytesMut::with_capacity(self.chunk_size),
+        );
+        let mut data = self.data.lock().unwrap();
+        let index = data.write_chunk;
+        data.set(index, chunk.freeze());
+        data.write_chunk += 1;
+        drop(data);
+        self.signal.wake();
+    }
 }
 
 impl AppendableDataWrapper for MVecBytesWrapper {
-    fn append_data(&mut self, slice: &[u8]) {
+    fn append_data(&mut self, mut slice: &[u8]) {
         if self.completed.load(Ordering::SeqCst) {
             return;
         }
-        let current_chunk_len = self.current_chunk.len();
 
-        // 情况1: current_chunk.len() + slice.len() <= chunk_size
-        if current_chunk_len + slice.len() <= self.chunk_size {
-            self.current_chunk.extend_from_slice(slice);
-            // 如果恰好达到 chunk_size，冻结并推入 data
+        // 补齐 current_chunk，每满 chunk_size 即写入
+        while !slice.is_empty() {
+            let len = (self.chunk_size - self.current_chunk.len()).min(slice.len());
+            self.current_chunk.extend_from_slice(&slice[..len]);
+            slice = &slice[len..];
             if self.current_chunk.len() == self.chunk_size {
-                self.data
-                    .lock()
-                    .unwrap()
-                    .push(self.current_chunk.clone().freeze());
-
-                // 重置 current_chunk
-                self.current_chunk = BytesMut::with_capacity(self.chunk_size);
+                self.flush_chunk();
             }
         }
-        // 情况2: current_chunk.len() + slice.len() > chunk_size
-        else {
-            let mut append_data: Vec<Bytes> = Vec::new();
-            let mut offset = 0;
-
-            // 如果 current_chunk 长度不为 0
-            if current_chunk_len != 0 {
-                let first_part_len = self.chunk_size - current_chunk_len;
-
-                // 补齐 current_chunk 到 chunk_size，冻结并推入 append_data
-                let first_part = &slice[..first_part_len];
-                self.current_chunk.extend_from_slice(first_part);
-                append_data.push(self.current_chunk.clone().freeze());
-
-                offset += first_part_len;
 
-                // 重置 current_chunk
-                self.current_chunk = BytesMut::with_capacity(self.chunk_size);
+        // 已写到数据末尾时，写入最后一个不完整的块
+        if !self.current_chunk.is_empty() {
+            let data = self.data.lock().unwrap();
+            let end = (data.write_chunk * self.chunk_size + self.current_chunk.len()) as u64;
+            let reached_end = data.total_len.is_some_and(|len| end >= len);
+            drop(data);
+            if reached_end {
+                self.flush_chunk();
             }
-            // 按 chunk_size 分割 slice
-            while offset + self.chunk_size <= slice.len() {
-                append_data.push(Bytes::copy_from_slice(
-                    &slice[offset..offset + self.chunk_size],
-                ));
-                offset += self.chunk_size;
-            }
-
-            // 处理最后一个不足 chunk_size 的部分
-            if offset < slice.len() {
-                let remaining = &slice[offset..];
-                self.current_chunk.extend_from_slice(remaining);
-            } else {
-                // 如果刚好分割完，current_chunk 保持为空
-                self.current_chunk = BytesMut::with_capacity(self.chunk_size);
-            }
-
-            // 将 append_data 中的所有完整块推入 data
-            self.data.lock().unwrap().append(&mut append_data);
         }
     }
     fn complete(&mut self) {
-        if self.current_chunk.len() > 0 {
-            self.data
-                .lock()
-                .unwrap()
-                .push(self.current_chunk.clone().freeze());
+        if !self.current_chunk.is_empty() {
+            self.flush_chunk();
             self.current_chunk = BytesMut::new();
         }
         self.completed.store(true, Ordering::SeqCst);
+        self.signal.wake();
     }
     fn set_capacity(&mut self, capacity: usize) {
         let mut data = self.data.lock().unwrap();
-        let len = data.len();
-        data.reserve_exact((capacity - len) / self.chunk_size + 1);
+        let count = capacity.div_ceil(self.chunk_size);
+        let len = data.chunks.len();
+        data.chunks.reserve_exact(count.saturating_sub(len));
+    }
+    fn set_total_len(&mut self, len: u64) {
+        self.data.lock().unwrap().total_len = Some(len);
+        self.signal.wake();
+    }
+    fn enable_ranges(&mut self) -> Option<Arc<RangeRequest>> {
+        let request = Arc::new(RangeRequest::default());
+        self.data.lock().unwrap().range_request = Some(request.clone());
+        Some(request)
+    }
+    fn seek_write(&mut self, pos: u64) -> u64 {
+        // 只能从块的起始处写入，丢弃未写满的块
+        self.current_chunk.clear();
+        let index = pos as usize / self.chunk_size;
+        self.data.lock().unwrap().write_chunk = index;
+        (index * self.chunk_size) as u64
+    }
+    fn write_pos_filled(&self) -> bool {
+        let data = self.data.lock().unwrap();
+        self.current_chunk.is_empty() && data.chunk(data.write_chunk).is_some()
+    }
+    fn first_missing(&self) -> Option<u64> {
+        let data = self.data.lock().unwrap();
+        data.first_missing()
+            .map(|index| (index * self.chunk_size) as u64)
+    }
+    fn buffered(&self) -> Vec<Range<u64>> {
+        self.data.lock().unwrap().ranges()
     }
 }
 
-pub struct MVecBytesReader {
-    data: Arc<Mutex<Vec<Bytes>>>,
+/// 读取位置及等待状态，同步及异步Reader共用
+struct ChunkCursor {
+    data: Arc<Mutex<ChunkData>>,
     chunk_size: usize,
-    condvar: Arc<Condvar>,
     pos: u64,
     download_completed: Arc<AtomicBool>,
     cancellation_token: CancellationToken,
+    /// 已发起Range请求的块索引
+    requested: Option<usize>,
+    /// 缓冲状态监视器
+    buffer_monitor: Arc<BufferMonitor>,
+    /// 是否正在等待数据
+    waiting: bool,
 }
 
-impl MVecBytesReader {
-    pub fn new(wrapper: MVecBytesWrapper, condvar: Arc<Condvar>) -> Self {
+/// 读取位置的数据状态
+enum Readiness {
+    /// 有可读的数据
+    Ready,
+    /// 已到达数据末尾，或播放已取消
+    Eof,
+    /// 需要等待数据
+    Pending,
+}
+
+impl ChunkCursor {
+    fn new(wrapper: &MVecBytesWrapper) -> Self {
         Self {
             data: wrapper.data(),
-            condvar,
             chunk_size: wrapper.chunk_size(),
             pos: 0,
             download_completed: wrapper.completed(),
             cancellation_token: CancellationToken::new(),
+            requested: None,
+            buffer_monitor: Arc::new(BufferMonitor::default()),
+            waiting: false,
         }
     }
 
-    pub fn cancellation_token(&self) -> CancellationToken {
-        self.cancellation_token.clone()
+    /// 从读取位置开始连续可读的字节数（统计到 `limit` 为止），以及其后第一个缺失的块
+    fn contiguous(&self, data: &ChunkData, index: usize, limit: u64) -> (u64, usize) {
+        let offset = self.pos - (index * self.chunk_size) as u64;
+        let mut end = index;
+        let mut len: u64 = 0;
+        while let Some(chunk) = data.chunk(end) {
+            len += chunk.len() as u64;
+            end += 1;
+            if len.saturating_sub(offset) >= limit {
+                break;
+            }
+        }
+        (len.saturating_sub(offset), end)
     }
-}
 
-impl Read for MVecBytesReader {
-    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
-        let lock = &*self.data;
-        let mut data = lock.lock().unwrap();
+    /// 结束等待，通知恢复读取
+    fn resume(&mut self) {
+        if self.waiting {
+            self.waiting = false;
+            self.buffer_monitor.notify(BufferState::Playing);
+        }
+    }
 
-        // 如果需要读取的数据位置超出当前缓冲区的数据，则等待数据到达
-        while self.pos as usize >= data.len() * self.chunk_size {
-            // 检查下载是否已完成
-            if self.download_completed.load(Ordering::Acquire) {
-                // 下载已完成，没有更多数据了，返回 EOF
-                return Ok(0);
-            }
+    /// 检查读取位置的数据是否可读，数据不足时发起Range请求并通知等待
+    fn poll_data(&mut self, data: &ChunkData) -> Readiness {
+        if data.total_len.is_some_and(|len| self.pos >= len) {
+            // 已到达数据末尾
+            self.resume();
+            return Readiness::Eof;
+        }
+        let index = self.pos as usize / self.chunk_size;
 
-            if self.cancellation_token.is_cancelled() {
-                // 播放已取消，跳出循环以防止阻塞
-                return Ok(0);
-            }
-            // 等待更多数据或下载完成的通知
-            data = self.condvar.wait(data).unwrap();
+        // 等待数据期间需缓冲足够的数据才恢复读取
+        let limit = if self.waiting {
+            self.buffer_monitor.resume_bytes().max(1)
+        } else {
+            1
+        };
+        let (available, missing) = self.contiguous(data, index, limit);
+        let reached_end = data
+            .total_len
+            .is_some_and(|len| (missing * self.chunk_size) as u64 >= len);
+        let completed = self.download_completed.load(Ordering::Acquire);
+        if available > 0 && (available >= limit || reached_end || completed) {
+            self.requested = None;
+            self.resume();
+            return Readiness::Ready;
         }
 
-        // 找到当前位置所在的块
-        let chunk_start_idx = self.pos as usize / self.chunk_size;
-        let chunk_start_offset = self.pos as usize % self.chunk_size;
+        // 检查下载是否已完成
+        if completed {
+            // 下载已完成，没有更多数据了，返回 EOF
+            self.resume();
+            return Readiness::Eof;
+        }
 
-        let mut chunk_end_idx = (self.pos as usize + buf.len()) / self.chunk_size;
-        let mut chunk_end_offset = (self.pos as usize + buf.len()) % self.chunk_size;
+        if self.cancellation_token.is_cancelled() {
+            // 播放已取消，结束读取以防止阻塞
+            return Readiness::Eof;
+        }
 
-        if chunk_end_idx >= data.len() {
-            chunk_end_idx = data.len();
-            chunk_end_offset = 0;
+        // 缺失的数据远离下载位置时，请求从该位置开始下载
+        if let Some(ref request) = data.range_request {
+            let write_pos = (data.write_chunk * self.chunk_size) as u64;
+            let missing_pos = (missing * self.chunk_size) as u64;
+            let far = missing_pos < write_pos || missing_pos - write_pos > RANGE_REQUEST_DISTANCE;
+            if far && self.requested != Some(missing) {
+                request.request(missing_pos);
+                self.requested = Some(missing);
+            }
         }
 
-        // 获取起始块
-        let start_chunk = data[chunk_start_idx].clone();
+        if !self.waiting && self.buffer_monitor.active() {
+            self.waiting = true;
+            self.buffer_monitor.notify(BufferState::Waiting);
+        }
+        Readiness::Pending
+    }
 
-        // 获取中间块
-        let middle_chunks: Option<Vec<Bytes>> = if chunk_end_idx - chunk_start_idx > 1 {
-            Some(data[chunk_start_idx + 1..chunk_end_idx].to_vec())
-        } else {
-            None
-        };
+    /// 从当前位置开始，依次读取连续的块
+    fn copy_to(&mut self, data: &ChunkData, buf: &mut [u8]) -> usize {
+        let mut offset: usize = 0;
+        let mut pos = self.pos as usize;
+        while offset < buf.len() {
+            let Some(chunk) = data.chunk(pos / self.chunk_size) else {
+                break;
+            };
+            let chunk_offset = pos % self.chunk_size;
+            if chunk_offset >= chunk.len() {
+                break;
+            }
+            // 可读取长度
+            let len = (chunk.len() - chunk_offset).min(buf.len() - offset);
+            buf[offset..offset + len].copy_from_slice(&chunk[chunk_offset..chunk_offset + len]);
+            offset += len;
+            pos += len;
+        }
+        self.pos += offset as u64;
+        offset
+    }
 
-        // 获取结束块
-        let end_chunk = if chunk_end_idx > chunk_start_idx && chunk_end_offset > 0 {
-            Some(data[chunk_end_idx].clone())
-        } else {
-            None
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let new_pos = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::Current(off) => (self.pos as i64 + off) as u64,
+            SeekFrom::End(off) => match self.data.lock().unwrap().total_len {
+                Some(len) => (len as i64 + off) as u64,
+                None => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        "SeekFrom::End not supported",
+                    ));
+                }
+            },
         };
-        drop(data);
 
-        // 计算偏移量（总读取字节数）
-        let mut offset: usize = 0;
+        self.pos = new_pos;
+        Ok(self.pos)
+    }
+}
 
-        if chunk_start_idx == chunk_end_idx {
-            // 只有一个块，直接读取
-            let chunk = start_chunk;
-            // 可读取长度
-            let len = chunk_end_offset.min(chunk.len()) - chunk_start_offset;
+pub struct MVecBytesReader {
+    cursor: ChunkCursor,
+    condvar: Arc<Condvar>,
+}
 
-            buf[..len].copy_from_slice(&chunk[chunk_start_offset..chunk_start_offset + len]);
+impl MVecBytesReader {
+    pub fn new(wrapper: MVecBytesWrapper, condvar: Arc<Condvar>) -> Self {
+        Self {
+            cursor: ChunkCursor::new(&wrapper),
+            condvar,
+        }
+    }
 
-            offset += len;
-        } else {
-            // 处理多个块的情况
-
-            // 先处理起始块
-            {
-                // 首个分块可读取长度
-                let len = start_chunk.len() - chunk_start_offset;
-                buf[..len].copy_from_slice(&start_chunk[chunk_start_offset..]);
-                offset += len;
-            }
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cursor.cancellation_token.clone()
+    }
 
-            // 处理中间块
-            if let Some(middle_chunks) = middle_chunks {
-                for chunk in middle_chunks {
-                    // 可读取长度
-                    let len = chunk.len();
-                    buf[offset..offset + len].copy_from_slice(&chunk);
-                    offset += len;
-                }
-            }
+    /// 获取缓冲状态监视器
+    pub fn buffer_monitor(&self) -> Arc<BufferMonitor> {
+        self.cursor.buffer_monitor.clone()
+    }
+}
 
-            // 处理结束块
-            if let Some(end_chunk) = end_chunk {
-                // 可读取长度
-                let len = chunk_end_offset.min(end_chunk.len());
-                buf[offset..offset + len].copy_from_slice(&end_chunk[..len]);
-                offset += len;
+impl Read for MVecBytesReader {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let lock = Arc::clone(&self.cursor.data);
+        let mut data = lock.lock().unwrap();
+
+        // 如果需要读取的数据尚未下载，则等待数据到达
+        loop {
+            match self.cursor.poll_data(&data) {
+                Readiness::Ready => break,
+                Readiness::Eof => return Ok(0),
+                // 等待更多数据或下载完成的通知
+                Readiness::Pending => data = self.condvar.wait(data).unwrap(),
             }
         }
-        self.pos += offset as u64;
-        Ok(offset)
+        Ok(self.cursor.copy_to(&data, buf))
     }
 }
 
 impl Seek for MVecBytesReader {
     fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
-        let new_pos = match pos {
-            SeekFrom::Start(p) => p,
-            SeekFrom::Current(off) => (self.pos as i64 + off) as u64,
-            SeekFrom::End(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Unsupported,
-                    "SeekFrom::End not supported",
-                ));
+        self.cursor.seek(pos)
+    }
+}
+
+/// 异步读取分块存储的数据
+///
+/// 与 [`MVecBytesReader`] 读取同一份数据，等待数据时不阻塞线程，可直接在 tokio 中使用，
+/// 由Wrapper在数据写入或下载完成时唤醒
+pub struct MVecBytesAsyncReader {
+    cursor: ChunkCursor,
+    signal: Arc<DataSignal>,
+    /// 取消时唤醒等待中的读取
+    cancelled: Pin<Box<WaitForCancellationFutureOwned>>,
+}
+
+impl MVecBytesAsyncReader {
+    pub fn new(wrapper: MVecBytesWrapper) -> Self {
+        let cursor = ChunkCursor::new(&wrapper);
+        let cancelled = Box::pin(cursor.cancellation_token.clone().cancelled_owned());
+        Self {
+            cursor,
+            signal: wrapper.signal(),
+            cancelled,
+        }
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cursor.cancellation_token.clone()
+    }
+
+    /// 获取缓冲状态监视器
+    pub fn buffer_monitor(&self) -> Arc<BufferMonitor> {
+        self.cursor.buffer_monitor.clone()
+    }
+}
+
+impl AsyncRead for MVecBytesAsyncReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        // 先注册唤醒器再检查数据，以免错过检查之后写入的数据
+        this.signal.register(cx.waker());
+        if !this.cursor.cancellation_token.is_cancelled() {
+            let _ = this.cancelled.as_mut().poll(cx);
+        }
+
+        let lock = Arc::clone(&this.cursor.data);
+        let data = lock.lock().unwrap();
+        match this.cursor.poll_data(&data) {
+            Readiness::Ready => {
+                let len = this.cursor.copy_to(&data, buf.initialize_unfilled());
+                buf.advance(len);
+                Poll::Ready(Ok(()))
             }
-        };
+            Readiness::Eof => Poll::Ready(Ok(())),
+            Readiness::Pending => Poll::Pending,
+        }
+    }
+}
 
-        self.pos = new_pos;
-        Ok(self.pos)
+impl AsyncSeek for MVecBytesAsyncReader {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> Result<()> {
+        self.get_mut().cursor.seek(position).map(|_| ())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<u64>> {
+        Poll::Ready(Ok(self.cursor.pos))
     }
 }
@@ -1,14 +1,21 @@
+use std::future::Future;
 use std::io::{Read, Result, Seek, SeekFrom};
+use std::ops::Range;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
-use tokio_util::sync::CancellationToken;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+use tokio_util::sync::{CancellationToken, WaitForCancellationFutureOwned};
 
-use crate::reader::AppendableDataWrapper;
+use crate::reader::{AppendableDataWrapper, DataSignal};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct MVecU8Wrapper {
     data: Arc<Mutex<Vec<u8>>>,
     completed: Arc<AtomicBool>,
+    /// 唤醒等待数据的异步Reader
+    signal: Arc<DataSignal>,
 }
 
 impl MVecU8Wrapper {
@@ -16,6 +23,7 @@ impl MVecU8Wrapper {
         Self {
             data: Arc::new(Mutex::new(Vec::new())),
             completed: Arc::new(AtomicBool::new(false)),
+            signal: Arc::new(DataSignal::default()),
         }
     }
 
@@ -25,6 +33,9 @@ impl MVecU8Wrapper {
     pub fn completed(&self) -> Arc<AtomicBool> {
         self.completed.clone()
     }
+    pub(crate) fn signal(&self) -> Arc<DataSignal> {
+        self.signal.clone()
+    }
 }
 
 impl AppendableDataWrapper for MVecU8Wrapper {
@@ -32,15 +43,25 @@ impl AppendableDataWrapper for MVecU8Wrapper {
         // 将数据追加到data中
         let mut data_lock = self.data.lock().unwrap();
         data_lock.extend_from_slice(slice);
+        drop(data_lock);
+        self.signal.wake();
     }
     fn complete(&mut self) {
         self.completed.store(true, Ordering::SeqCst);
+        self.signal.wake();
     }
     fn set_capacity(&mut self, capacity: usize) {
         let mut data = self.data.lock().unwrap();
         let len = data.len();
         data.reserve_exact(capacity - len);
     }
+    fn buffered(&self) -> Vec<Range<u64>> {
+        let len = self.data.lock().unwrap().len() as u64;
+        if len == 0 {
+            return Vec::new();
+        }
+        std::iter::once(0..len).collect()
+    }
 }
 
 pub struct MVecU8Reader {
@@ -101,18 +122,97 @@ impl Read for MVecU8Reader {
 
 impl Seek for MVecU8Reader {
     fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
-        let new_pos = match pos {
-            SeekFrom::Start(p) => p,
-            SeekFrom::Current(off) => (self.pos as i64 + off) as u64,
-            SeekFrom::End(_) => {
-                return Err(std::io::Error::new(
-                    std::io::ErrorKind::Unsupported,
-                    "SeekFrom::End not supported",
-                ));
+        self.pos = seek_pos(self.pos, pos)?;
+        Ok(self.pos)
+    }
+}
+
+fn seek_pos(current: u64, pos: SeekFrom) -> Result<u64> {
+    match pos {
+        SeekFrom::Start(p) => Ok(p),
+        SeekFrom::Current(off) => Ok((current as i64 + off) as u64),
+        SeekFrom::End(_) => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "SeekFrom::End not supported",
+        )),
+    }
+}
+
+/// 异步读取连续存储的数据
+///
+/// 与 [`MVecU8Reader`] 读取同一份数据，等待数据时不阻塞线程，可直接在 tokio 中使用，
+/// 由Wrapper在数据写入或下载完成时唤醒；跳转到尚未写入的位置时，读取等待数据写入到该位置
+pub struct MVecU8AsyncReader {
+    data: Arc<Mutex<Vec<u8>>>,
+    signal: Arc<DataSignal>,
+    pos: u64,
+    download_completed: Arc<AtomicBool>,
+    cancellation_token: CancellationToken,
+    /// 取消时唤醒等待中的读取
+    cancelled: Pin<Box<WaitForCancellationFutureOwned>>,
+}
+
+impl MVecU8AsyncReader {
+    pub fn new(wrapper: MVecU8Wrapper) -> Self {
+        let cancellation_token = CancellationToken::new();
+        Self {
+            data: wrapper.data(),
+            signal: wrapper.signal(),
+            pos: 0,
+            download_completed: wrapper.completed(),
+            cancelled: Box::pin(cancellation_token.clone().cancelled_owned()),
+            cancellation_token,
+        }
+    }
+
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation_token.clone()
+    }
+}
+
+impl AsyncRead for MVecU8AsyncReader {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        // 先注册唤醒器再检查数据，以免错过检查之后写入的数据
+        this.signal.register(cx.waker());
+        if !this.cancellation_token.is_cancelled() {
+            let _ = this.cancelled.as_mut().poll(cx);
+        }
+
+        let data = this.data.lock().unwrap();
+        if this.pos as usize >= data.len() {
+            // 下载已完成或播放已取消时返回 EOF，否则等待数据
+            if this.download_completed.load(Ordering::Acquire)
+                || this.cancellation_token.is_cancelled()
+            {
+                return Poll::Ready(Ok(()));
             }
-        };
+            return Poll::Pending;
+        }
 
-        self.pos = new_pos;
-        Ok(self.pos)
+        let available = &data[this.pos as usize..];
+        let len = available.len().min(buf.remaining());
+        buf.put_slice(&available[..len]);
+        this.pos += len as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for MVecU8AsyncReader {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> Result<()> {
+        let this = self.get_mut();
+        this.pos = seek_pos(this.pos, position)?;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<u64>> {
+        Poll::Ready(Ok(self.pos))
     }
 }
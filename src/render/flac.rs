@@ -0,0 +1,348 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+use super::{PcmEncoder, SampleFormat};
+
+/// 每帧的采样数
+const BLOCK_SIZE: usize = 4096;
+/// 固定预测器的最高阶数
+const MAX_FIXED_ORDER: usize = 4;
+/// Rice 参数的上限（4位参数中 15 表示转义）
+const MAX_RICE_PARAM: u32 = 14;
+
+/// FLAC 编码器
+///
+/// 每个声道独立编码，子帧在常量、原始数据及 0~4 阶固定预测器中选择最短的一种，
+/// 残差使用单分区的 Rice 编码。STREAMINFO 中的 MD5 置为 0（表示未计算）
+pub(super) struct FlacEncoder {
+    writer: BufWriter<File>,
+    channels: usize,
+    sample_rate: u32,
+    bits: u32,
+    format: SampleFormat,
+    /// 各声道尚未编码的采样
+    buffers: Vec<Vec<i32>>,
+    /// 下一帧的序号
+    frame_number: u64,
+    /// 已编码的采样数（每声道）
+    total_samples: u64,
+    min_frame_size: u32,
+    max_frame_size: u32,
+}
+
+impl FlacEncoder {
+    pub fn new(
+        file: File,
+        format: SampleFormat,
+        channels: u16,
+        sample_rate: u32,
+    ) -> io::Result<Self> {
+        if format == SampleFormat::F32 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "FLAC does not support floating point samples",
+            ));
+        }
+        if !(1..=8).contains(&channels) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "FLAC supports 1 to 8 channels",
+            ));
+        }
+
+        let mut encoder = Self {
+            writer: BufWriter::new(file),
+            channels: channels as usize,
+            sample_rate,
+            bits: format.bits() as u32,
+            format,
+            buffers: vec![Vec::with_capacity(BLOCK_SIZE); channels as usize],
+            frame_number: 0,
+            total_samples: 0,
+            min_frame_size: u32::MAX,
+            max_frame_size: 0,
+        };
+        encoder.writer.write_all(b"fLaC")?;
+        // 最后一个元数据块，类型 0（STREAMINFO），长度 34
+        encoder.writer.write_all(&[0x80, 0, 0, 34])?;
+        let stream_info = encoder.stream_info();
+        encoder.writer.write_all(&stream_info)?;
+        Ok(encoder)
+    }
+
+    fn stream_info(&self) -> Vec<u8> {
+        let mut writer = BitWriter::default();
+        writer.write(BLOCK_SIZE as u64, 16);
+        writer.write(BLOCK_SIZE as u64, 16);
+        if self.max_frame_size == 0 {
+            writer.write(0, 24);
+            writer.write(0, 24);
+        } else {
+            writer.write(self.min_frame_size as u64, 24);
+            writer.write(self.max_frame_size as u64, 24);
+        }
+        writer.write(self.sample_rate as u64, 20);
+        writer.write(self.channels as u64 - 1, 3);
+        writer.write(self.bits as u64 - 1, 5);
+        writer.write(self.total_samples, 36);
+        // MD5
+        writer.write(0, 64);
+        writer.write(0, 64);
+        writer.into_bytes()
+    }
+
+    /// 编码一帧
+    fn write_frame(&mut self) -> io::Result<()> {
+        let block_size = self.buffers[0].len();
+        if block_size == 0 {
+            return Ok(());
+        }
+
+        let mut writer = BitWriter::default();
+        // 同步码及固定块大小
+        writer.write(0xfff8, 16);
+        // 块大小在帧头末尾以16位给出，采样率取自 STREAMINFO
+        writer.write(0b0111, 4);
+        writer.write(0b0000, 4);
+        // 各声道独立编码
+        writer.write(self.channels as u64 - 1, 4);
+        writer.write(if self.bits == 16 { 0b100 } else { 0b110 }, 3);
+        writer.write(0, 1);
+        writer.write_utf8(self.frame_number);
+        writer.write(block_size as u64 - 1, 16);
+        let crc = crc8(writer.bytes());
+        writer.write(crc as u64, 8);
+
+        for channel in self.buffers.iter() {
+            write_subframe(&mut writer, channel, self.bits);
+        }
+        writer.align();
+        let crc = crc16(writer.bytes());
+        writer.write(crc as u64, 16);
+
+        let frame = writer.into_bytes();
+        self.writer.write_all(&frame)?;
+        self.min_frame_size = self.min_frame_size.min(frame.len() as u32);
+        self.max_frame_size = self.max_frame_size.max(frame.len() as u32);
+        self.frame_number += 1;
+        self.total_samples += block_size as u64;
+        for channel in self.buffers.iter_mut() {
+            channel.clear();
+        }
+        Ok(())
+    }
+}
+
+impl PcmEncoder for FlacEncoder {
+    fn write(&mut self, samples: &[f32]) -> io::Result<()> {
+        for frame in samples.chunks(self.channels) {
+            for (channel, &sample) in frame.iter().enumerate() {
+                self.buffers[channel].push(self.format.quantize(sample));
+            }
+            if self.buffers[0].len() == BLOCK_SIZE {
+                self.write_frame()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        // 丢弃不完整的采样帧，保证各声道的采样数一致
+        let len = self.buffers.iter().map(Vec::len).min().unwrap_or(0);
+        for channel in self.buffers.iter_mut() {
+            channel.truncate(len);
+        }
+        self.write_frame()?;
+
+        let stream_info = self.stream_info();
+        self.writer.seek(SeekFrom::Start(8))?;
+        self.writer.write_all(&stream_info)?;
+        self.writer.flush()
+    }
+}
+
+/// 编码一个声道的子帧，选择编码后最短的方式
+fn write_subframe(writer: &mut BitWriter, samples: &[i32], bits: u32) {
+    // 常量子帧
+    if samples.iter().all(|&s| s == samples[0]) {
+        writer.write(0, 8);
+        writer.write_signed(samples[0] as i64, bits);
+        return;
+    }
+
+    let verbatim_bits = samples.len() as u64 * bits as u64;
+    let best = (0..=MAX_FIXED_ORDER.min(samples.len() - 1))
+        .map(|order| {
+            let residuals = fixed_residuals(samples, order);
+            let (param, residual_bits) = rice_param(&residuals);
+            let bits = order as u64 * bits as u64 + 18 + residual_bits;
+            (order, residuals, param, bits)
+        })
+        .min_by_key(|(_, _, _, bits)| *bits);
+
+    match best {
+        Some((order, residuals, param, fixed_bits)) if fixed_bits < verbatim_bits => {
+            writer.write(0b001000 | order as u64, 7);
+            writer.write(0, 1);
+            for &sample in &samples[..order] {
+                writer.write_signed(sample as i64, bits);
+            }
+            // Rice 编码，分区阶数 0
+            writer.write(0b00, 2);
+            writer.write(0, 4);
+            writer.write(param as u64, 4);
+            for &residual in residuals.iter() {
+                let value = zigzag(residual);
+                writer.write_unary(value >> param);
+                writer.write(value & ((1 << param) - 1), param);
+            }
+        }
+        _ => {
+            writer.write(0b00000010, 8);
+            for &sample in samples {
+                writer.write_signed(sample as i64, bits);
+            }
+        }
+    }
+}
+
+/// 计算固定预测器的残差
+fn fixed_residuals(samples: &[i32], order: usize) -> Vec<i64> {
+    (order..samples.len())
+        .map(|i| {
+            let x = |n: usize| samples[i - n] as i64;
+            match order {
+                0 => x(0),
+                1 => x(0) - x(1),
+                2 => x(0) - 2 * x(1) + x(2),
+                3 => x(0) - 3 * x(1) + 3 * x(2) - x(3),
+                _ => x(0) - 4 * x(1) + 6 * x(2) - 4 * x(3) + x(4),
+            }
+        })
+        .collect()
+}
+
+/// 选择编码后最短的 Rice 参数，返回参数及编码后的位数
+fn rice_param(residuals: &[i64]) -> (u32, u64) {
+    let values: Vec<u64> = residuals.iter().map(|&r| zigzag(r)).collect();
+    (0..=MAX_RICE_PARAM)
+        .map(|param| {
+            let bits = values
+                .iter()
+                .map(|&v| (v >> param) + 1 + param as u64)
+                .sum::<u64>();
+            (param, bits)
+        })
+        .min_by_key(|(_, bits)| *bits)
+        .unwrap_or((0, 0))
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn crc8(data: &[u8]) -> u8 {
+    let mut crc: u8 = 0;
+    for &byte in data {
+        crc ^= byte;
+        for _ in 0..8 {
+            crc = if crc & 0x80 != 0 {
+                (crc << 1) ^ 0x07
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for &byte in data {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x8005
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+/// 按位写入（高位在前）
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    /// 尚未写满一个字节的位
+    acc: u8,
+    /// `acc` 中的位数
+    len: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u64, bits: u32) {
+        for i in (0..bits).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1) as u8;
+            self.len += 1;
+            if self.len == 8 {
+                self.bytes.push(self.acc);
+                self.acc = 0;
+                self.len = 0;
+            }
+        }
+    }
+
+    fn write_signed(&mut self, value: i64, bits: u32) {
+        self.write(value as u64 & ((1 << bits) - 1), bits);
+    }
+
+    /// 写入 `value` 个 0 及一个 1
+    fn write_unary(&mut self, mut value: u64) {
+        while self.len != 0 && value > 0 {
+            self.write(0, 1);
+            value -= 1;
+        }
+        while value >= 8 {
+            self.bytes.push(0);
+            value -= 8;
+        }
+        self.write(0, value as u32);
+        self.write(1, 1);
+    }
+
+    /// 以 UTF-8 的方式写入帧序号
+    fn write_utf8(&mut self, value: u64) {
+        if value < 0x80 {
+            self.write(value, 8);
+            return;
+        }
+        let mut extra = 1;
+        while value >= 1 << (6 * extra + 6 - extra) {
+            extra += 1;
+        }
+        let prefix = (0xff00u64 >> (extra + 1)) & 0xff;
+        self.write(prefix | (value >> (6 * extra)), 8);
+        for i in (0..extra).rev() {
+            self.write(0x80 | ((value >> (6 * i)) & 0x3f), 8);
+        }
+    }
+
+    /// 补齐到字节边界
+    fn align(&mut self) {
+        if self.len > 0 {
+            self.write(0, 8 - self.len);
+        }
+    }
+
+    /// 已写满的字节
+    fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    fn into_bytes(mut self) -> Vec<u8> {
+        self.align();
+        self.bytes
+    }
+}
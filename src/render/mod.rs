@@ -0,0 +1,210 @@
+//! 离线渲染
+//!
+//! 不经过音频设备，以尽可能快的速度读取音频源并写入 WAV 或 FLAC 文件
+
+use crate::error::{Error, Result};
+use rodio::source::UniformSourceIterator;
+use rodio::{ChannelCount, SampleRate, Source};
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+mod flac;
+mod wav;
+
+use flac::FlacEncoder;
+use wav::WavEncoder;
+
+/// 每次读取的采样数
+const BLOCK_SAMPLES: usize = 4096;
+/// 进度回调的间隔（按渲染的音频时长计算）
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+type ProgressCallback = Arc<dyn Fn(RenderProgress) + Send + Sync + 'static>;
+
+/// 输出文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RenderFormat {
+    #[default]
+    Wav,
+    /// 无损压缩，不支持浮点采样
+    Flac,
+}
+
+/// 输出的采样格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleFormat {
+    /// 16位整数
+    #[default]
+    I16,
+    /// 24位整数
+    I24,
+    /// 32位浮点
+    F32,
+}
+
+impl SampleFormat {
+    /// 每个采样的位数
+    pub fn bits(&self) -> u16 {
+        match self {
+            SampleFormat::I16 => 16,
+            SampleFormat::I24 => 24,
+            SampleFormat::F32 => 32,
+        }
+    }
+
+    /// 将浮点采样量化为整数，超出 [-1, 1] 的部分被截断（仅用于整数格式）
+    fn quantize(&self, sample: f32) -> i32 {
+        let scale = match self {
+            SampleFormat::I16 => i16::MAX as f32,
+            SampleFormat::I24 => 8_388_607.0,
+            SampleFormat::F32 => 1.0,
+        };
+        (sample.clamp(-1.0, 1.0) * scale).round() as i32
+    }
+}
+
+/// 渲染设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RenderOptions {
+    pub format: RenderFormat,
+    pub sample_format: SampleFormat,
+    /// 输出采样率，为 `None` 时使用第一个音频源的采样率
+    pub sample_rate: Option<SampleRate>,
+    /// 输出声道数，为 `None` 时使用第一个音频源的声道数
+    pub channels: Option<ChannelCount>,
+}
+
+/// 渲染进度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenderProgress {
+    /// 已渲染的音频时长
+    pub rendered: Duration,
+    /// 音频总时长，未知时为 `None`
+    pub total: Option<Duration>,
+}
+
+/// PCM 编码器
+trait PcmEncoder {
+    /// 写入交错排列的采样
+    fn write(&mut self, samples: &[f32]) -> io::Result<()>;
+    /// 完成写入，回填文件头
+    fn finish(self: Box<Self>) -> io::Result<()>;
+}
+
+/// 离线渲染器
+///
+/// # Example
+///
+/// ```no_run
+/// use remu_audio::decoder::Decoder;
+/// use remu_audio::render::{RenderFormat, RenderOptions, Renderer};
+/// use std::fs::File;
+///
+/// let source = Decoder::try_from(File::open("input.mp3").unwrap()).unwrap();
+/// let mut renderer = Renderer::new(RenderOptions {
+///     format: RenderFormat::Flac,
+///     ..Default::default()
+/// });
+/// renderer.set_progress_callback(|progress| println!("{:?}", progress.rendered));
+/// renderer.render(source, "output.flac").unwrap();
+/// ```
+#[derive(Clone, Default)]
+pub struct Renderer {
+    options: RenderOptions,
+    progress_callback: Option<ProgressCallback>,
+}
+
+impl Renderer {
+    pub fn new(options: RenderOptions) -> Self {
+        Self {
+            options,
+            progress_callback: None,
+        }
+    }
+
+    pub fn options(&self) -> RenderOptions {
+        self.options
+    }
+
+    /// 设置进度回调，每渲染约一秒音频及渲染完成时调用
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(RenderProgress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Arc::new(callback));
+    }
+
+    /// 渲染音频源到文件
+    pub fn render<S>(&self, source: S, path: impl AsRef<Path>) -> Result<()>
+    where
+        S: Source + Send + 'static,
+    {
+        self.render_all(vec![Box::new(source)], path)
+    }
+
+    /// 依次渲染多个音频源到同一文件
+    pub fn render_all(
+        &self,
+        sources: Vec<Box<dyn Source + Send + 'static>>,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        let first = sources
+            .first()
+            .ok_or_else(|| Error::invalid_input("No audio source to render"))?;
+        let channels = self.options.channels.unwrap_or(first.channels());
+        let sample_rate = self.options.sample_rate.unwrap_or(first.sample_rate());
+        if channels == 0 || sample_rate == 0 {
+            return Err(Error::invalid_input("Invalid channel count or sample rate"));
+        }
+        // 所有音频源的时长均已知时才能得到总时长
+        let total = sources
+            .iter()
+            .map(|source| source.total_duration())
+            .sum::<Option<Duration>>();
+
+        let file = File::create(path)?;
+        let format = self.options.sample_format;
+        let mut encoder: Box<dyn PcmEncoder> = match self.options.format {
+            RenderFormat::Wav => Box::new(WavEncoder::new(file, format, channels, sample_rate)?),
+            RenderFormat::Flac => Box::new(FlacEncoder::new(file, format, channels, sample_rate)?),
+        };
+
+        let samples_per_second = sample_rate as u64 * channels as u64;
+        let mut written: u64 = 0;
+        let mut next_progress = PROGRESS_INTERVAL;
+        let mut block = Vec::with_capacity(BLOCK_SAMPLES);
+        for source in sources {
+            // 统一转换为输出的采样率及声道数
+            let mut source = UniformSourceIterator::new(source, channels, sample_rate);
+            loop {
+                block.clear();
+                block.extend(source.by_ref().take(BLOCK_SAMPLES));
+                if block.is_empty() {
+                    break;
+                }
+                encoder.write(&block)?;
+                written += block.len() as u64;
+
+                let rendered = Duration::from_secs_f64(written as f64 / samples_per_second as f64);
+                if rendered >= next_progress {
+                    next_progress = rendered + PROGRESS_INTERVAL;
+                    self.emit_progress(RenderProgress { rendered, total });
+                }
+            }
+        }
+        encoder.finish()?;
+
+        let rendered = Duration::from_secs_f64(written as f64 / samples_per_second as f64);
+        self.emit_progress(RenderProgress { rendered, total });
+        Ok(())
+    }
+
+    fn emit_progress(&self, progress: RenderProgress) {
+        if let Some(ref cb) = self.progress_callback {
+            cb(progress);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+
+use super::{PcmEncoder, SampleFormat};
+
+/// WAV 编码器
+///
+/// 先写入长度为 0 的文件头，完成时回填 RIFF 及 data 块的长度
+pub(super) struct WavEncoder {
+    writer: BufWriter<File>,
+    format: SampleFormat,
+    /// 已写入的数据字节数
+    data_len: u64,
+}
+
+impl WavEncoder {
+    pub fn new(
+        file: File,
+        format: SampleFormat,
+        channels: u16,
+        sample_rate: u32,
+    ) -> io::Result<Self> {
+        let mut writer = BufWriter::new(file);
+        let bytes_per_sample = format.bits() / 8;
+        // 1: PCM, 3: IEEE float
+        let format_tag: u16 = if format == SampleFormat::F32 { 3 } else { 1 };
+        let block_align = channels * bytes_per_sample;
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&format_tag.to_le_bytes())?;
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&format.bits().to_le_bytes())?;
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            format,
+            data_len: 0,
+        })
+    }
+}
+
+impl PcmEncoder for WavEncoder {
+    fn write(&mut self, samples: &[f32]) -> io::Result<()> {
+        for &sample in samples {
+            match self.format {
+                SampleFormat::I16 => {
+                    let value = self.format.quantize(sample) as i16;
+                    self.writer.write_all(&value.to_le_bytes())?;
+                }
+                SampleFormat::I24 => {
+                    let value = self.format.quantize(sample);
+                    self.writer.write_all(&value.to_le_bytes()[..3])?;
+                }
+                SampleFormat::F32 => {
+                    self.writer.write_all(&sample.to_le_bytes())?;
+                }
+            }
+        }
+        self.data_len += samples.len() as u64 * (self.format.bits() / 8) as u64;
+        Ok(())
+    }
+
+    fn finish(mut self: Box<Self>) -> io::Result<()> {
+        // 数据块长度为奇数时需补齐一个字节
+        if self.data_len % 2 == 1 {
+            self.writer.write_all(&[0])?;
+        }
+        let data_len = u32::try_from(self.data_len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "WAV data exceeds 4 GiB"))?;
+        let riff_len = 36 + data_len + (data_len % 2);
+
+        self.writer.seek(SeekFrom::Start(4))?;
+        self.writer.write_all(&riff_len.to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&data_len.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,467 @@
+//! 波形概览
+//!
+//! 解码整个音频，按数据点统计各声道的最小值、最大值及均方根，用于绘制进度条缩略图；
+//! 结果可读写为 audiowaveform 的 `.dat` 格式以便缓存
+
+use rodio::Source;
+use std::fs::File;
+use std::io::{self, Read, Seek, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::decoder::Decoder;
+use crate::error::{Error, Result};
+
+/// 每次读取的采样数
+const BLOCK_SAMPLES: usize = 4096;
+/// 进度回调的间隔（按解码的音频时长计算）
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+/// 总时长未知时，每个数据点的初始帧数
+const INITIAL_SAMPLES_PER_BUCKET: u32 = 256;
+/// `.dat` 文件头中表示 8 位数据的标志位
+const DAT_FLAG_8BIT: u32 = 1;
+
+type ProgressCallback = Arc<dyn Fn(&Waveform) + Send + Sync + 'static>;
+
+/// 一个数据点内的采样统计
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WaveformBucket {
+    pub min: f32,
+    pub max: f32,
+    /// 均方根，从 `.dat` 文件读取时为 `None`
+    pub rms: Option<f32>,
+}
+
+/// `.dat` 文件中数据的位数
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DatBits {
+    Eight,
+    #[default]
+    Sixteen,
+}
+
+impl DatBits {
+    /// 满幅对应的整数值
+    fn scale(&self) -> f32 {
+        match self {
+            DatBits::Eight => 128.0,
+            DatBits::Sixteen => 32768.0,
+        }
+    }
+}
+
+/// 波形概览数据
+#[derive(Debug, Clone, PartialEq)]
+pub struct Waveform {
+    pub sample_rate: u32,
+    /// 每个数据点包含的帧数
+    pub samples_per_bucket: u32,
+    /// 各声道的数据点
+    pub channels: Vec<Vec<WaveformBucket>>,
+}
+
+impl Waveform {
+    /// 数据点的数量
+    pub fn len(&self) -> usize {
+        self.channels.first().map_or(0, Vec::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 每个数据点对应的时长
+    pub fn bucket_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.samples_per_bucket as f64 / self.sample_rate.max(1) as f64)
+    }
+
+    /// 写入 audiowaveform 的 `.dat` 格式（单声道为版本 1，多声道为版本 2）
+    pub fn write_dat<W: Write>(&self, mut writer: W, bits: DatBits) -> io::Result<()> {
+        let version: i32 = if self.channels.len() > 1 { 2 } else { 1 };
+        let flags = match bits {
+            DatBits::Eight => DAT_FLAG_8BIT,
+            DatBits::Sixteen => 0,
+        };
+        let len = self.channels.iter().map(Vec::len).min().unwrap_or(0);
+        writer.write_all(&version.to_le_bytes())?;
+        writer.write_all(&flags.to_le_bytes())?;
+        writer.write_all(&(self.sample_rate as i32).to_le_bytes())?;
+        writer.write_all(&(self.samples_per_bucket as i32).to_le_bytes())?;
+        writer.write_all(&(len as u32).to_le_bytes())?;
+        if version == 2 {
+            writer.write_all(&(self.channels.len() as i32).to_le_bytes())?;
+        }
+
+        let scale = bits.scale();
+        let quantize = |value: f32| (value * scale).round().clamp(-scale, scale - 1.0);
+        let mut data = Vec::with_capacity(len * self.channels.len() * 4);
+        for index in 0..len {
+            for channel in &self.channels {
+                let bucket = channel[index];
+                for value in [quantize(bucket.min), quantize(bucket.max)] {
+                    match bits {
+                        DatBits::Eight => data.push(value as i8 as u8),
+                        DatBits::Sixteen => data.extend((value as i16).to_le_bytes()),
+                    }
+                }
+            }
+        }
+        writer.write_all(&data)
+    }
+
+    /// 转换为 audiowaveform 的 `.dat` 格式
+    pub fn to_dat(&self, bits: DatBits) -> Vec<u8> {
+        let mut data = Vec::new();
+        // 写入 Vec 不会失败
+        let _ = self.write_dat(&mut data, bits);
+        data
+    }
+
+    /// 读取 audiowaveform 的 `.dat` 格式（版本 1 及 2），数据点不包含均方根
+    pub fn read_dat<R: Read>(mut reader: R) -> Result<Self> {
+        let mut read_u32 = || -> io::Result<u32> {
+            let mut bytes = [0; 4];
+            reader.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        };
+        let version = read_u32()?;
+        if version != 1 && version != 2 {
+            return Err(Error::UnsupportedFormat(format!(
+                "Unsupported waveform data version: {}",
+                version
+            )));
+        }
+        let flags = read_u32()?;
+        let sample_rate = read_u32()?;
+        let samples_per_bucket = read_u32()?;
+        let len = read_u32()? as usize;
+        let channels = if version == 2 {
+            read_u32()? as usize
+        } else {
+            1
+        };
+        if channels == 0 {
+            return Err(Error::UnsupportedFormat(
+                "Invalid waveform channel count".to_string(),
+            ));
+        }
+
+        let bits = if flags & DAT_FLAG_8BIT != 0 {
+            DatBits::Eight
+        } else {
+            DatBits::Sixteen
+        };
+        let width = match bits {
+            DatBits::Eight => 1,
+            DatBits::Sixteen => 2,
+        };
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        if data.len() < len * channels * 2 * width {
+            return Err(Error::UnsupportedFormat(
+                "Truncated waveform data".to_string(),
+            ));
+        }
+
+        let scale = bits.scale();
+        let value = |index: usize| match bits {
+            DatBits::Eight => data[index] as i8 as f32 / scale,
+            DatBits::Sixteen => {
+                i16::from_le_bytes([data[index * 2], data[index * 2 + 1]]) as f32 / scale
+            }
+        };
+        let mut waveform = Waveform {
+            sample_rate,
+            samples_per_bucket,
+            channels: vec![Vec::with_capacity(len); channels],
+        };
+        for index in 0..len {
+            for (channel, buckets) in waveform.channels.iter_mut().enumerate() {
+                let offset = (index * channels + channel) * 2;
+                buckets.push(WaveformBucket {
+                    min: value(offset),
+                    max: value(offset + 1),
+                    rms: None,
+                });
+            }
+        }
+        Ok(waveform)
+    }
+}
+
+/// 正在统计的数据点
+#[derive(Debug, Clone, Copy)]
+struct Accumulator {
+    min: f32,
+    max: f32,
+    sum_squares: f64,
+}
+
+impl Default for Accumulator {
+    fn default() -> Self {
+        Self {
+            min: f32::INFINITY,
+            max: f32::NEG_INFINITY,
+            sum_squares: 0.0,
+        }
+    }
+}
+
+impl Accumulator {
+    fn push(&mut self, sample: f32) {
+        self.min = self.min.min(sample);
+        self.max = self.max.max(sample);
+        self.sum_squares += sample as f64 * sample as f64;
+    }
+
+    fn merge(&mut self, other: &Accumulator) {
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+        self.sum_squares += other.sum_squares;
+    }
+
+    fn bucket(&self, frames: u64) -> WaveformBucket {
+        if frames == 0 {
+            return WaveformBucket {
+                min: 0.0,
+                max: 0.0,
+                rms: Some(0.0),
+            };
+        }
+        WaveformBucket {
+            min: self.min,
+            max: self.max,
+            rms: Some((self.sum_squares / frames as f64).sqrt() as f32),
+        }
+    }
+}
+
+/// 逐个采样构建波形概览，可随时取得已处理部分的结果
+///
+/// 适合边下载边生成：每解码一段音频即可 [`WaveformBuilder::waveform`] 刷新界面
+#[derive(Debug, Clone)]
+pub struct WaveformBuilder {
+    channels: usize,
+    sample_rate: u32,
+    samples_per_bucket: u32,
+    /// 数据点数量的上限
+    max_buckets: Option<usize>,
+    /// 各数据点的统计，按帧交错排列各声道
+    buckets: Vec<Accumulator>,
+    /// 已处理的帧数
+    frames: u64,
+    /// 下一个采样所属的声道
+    channel: usize,
+}
+
+impl WaveformBuilder {
+    /// 每个数据点包含 `samples_per_bucket` 帧
+    pub fn new(channels: u16, sample_rate: u32, samples_per_bucket: u32) -> Self {
+        Self {
+            channels: channels.max(1) as usize,
+            sample_rate,
+            samples_per_bucket: samples_per_bucket.max(1),
+            max_buckets: None,
+            buckets: Vec::new(),
+            frames: 0,
+            channel: 0,
+        }
+    }
+
+    /// 限制数据点的数量，超出时相邻的数据点两两合并，每个数据点包含的帧数加倍
+    ///
+    /// 用于总时长未知的音频，最终的数据点数量在上限的一半到上限之间
+    pub fn with_max_buckets(mut self, max_buckets: usize) -> Self {
+        self.max_buckets = Some(max_buckets.max(1));
+        self
+    }
+
+    /// 添加一个采样，多声道的采样按帧交错排列
+    pub fn push(&mut self, sample: f32) {
+        let frame = self.frames;
+        if self.channel == 0 && frame.is_multiple_of(self.samples_per_bucket as u64) {
+            if self
+                .max_buckets
+                .is_some_and(|max| self.buckets.len() / self.channels >= max)
+            {
+                self.merge();
+            }
+            if frame.is_multiple_of(self.samples_per_bucket as u64) {
+                self.buckets
+                    .extend(std::iter::repeat_n(Accumulator::default(), self.channels));
+            }
+        }
+
+        let index = self.buckets.len() - self.channels + self.channel;
+        self.buckets[index].push(sample);
+        self.channel += 1;
+        if self.channel == self.channels {
+            self.channel = 0;
+            self.frames += 1;
+        }
+    }
+
+    /// 两两合并相邻的数据点
+    fn merge(&mut self) {
+        let channels = self.channels;
+        let len = self.buckets.len() / channels;
+        for index in 0..len.div_ceil(2) {
+            for channel in 0..channels {
+                let mut bucket = self.buckets[index * 2 * channels + channel];
+                if index * 2 + 1 < len {
+                    bucket.merge(&self.buckets[(index * 2 + 1) * channels + channel]);
+                }
+                self.buckets[index * channels + channel] = bucket;
+            }
+        }
+        self.buckets.truncate(len.div_ceil(2) * channels);
+        self.samples_per_bucket *= 2;
+    }
+
+    /// 已处理的帧数
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    /// 已处理部分的波形概览，最后一个数据点可能未满
+    pub fn waveform(&self) -> Waveform {
+        let len = self.buckets.len() / self.channels;
+        let spb = self.samples_per_bucket as u64;
+        let channels = (0..self.channels)
+            .map(|channel| {
+                (0..len)
+                    .map(|index| {
+                        let frames = (self.frames - index as u64 * spb).min(spb);
+                        self.buckets[index * self.channels + channel].bucket(frames)
+                    })
+                    .collect()
+            })
+            .collect();
+        Waveform {
+            sample_rate: self.sample_rate,
+            samples_per_bucket: self.samples_per_bucket,
+            channels,
+        }
+    }
+}
+
+/// 波形概览生成器
+///
+/// # Example
+///
+/// ```no_run
+/// use remu_audio::waveform::{DatBits, WaveformGenerator};
+///
+/// let waveform = WaveformGenerator::new(1000).generate_file("input.flac").unwrap();
+/// std::fs::write("input.dat", waveform.to_dat(DatBits::Eight)).unwrap();
+/// ```
+///
+/// 边下载边生成：
+///
+/// ```no_run
+/// use remu_audio::loader::downloader::Downloader;
+/// use remu_audio::reader::{MVecBytesReader, MVecBytesWrapper};
+/// use remu_audio::waveform::WaveformGenerator;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let wrapper = MVecBytesWrapper::new(256 * 1024);
+/// let loader = Downloader::new(wrapper.clone());
+/// loader
+///     .download("https://example.com/audio.mp3", None)
+///     .await?;
+/// let reader = MVecBytesReader::new(wrapper, loader.condvar());
+///
+/// let mut generator = WaveformGenerator::new(800);
+/// // 每解码约一秒音频回调一次已生成的部分
+/// generator.set_progress_callback(|waveform| println!("{} buckets", waveform.len()));
+/// let waveform = tokio::task::spawn_blocking(move || generator.generate_reader(reader)).await??;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct WaveformGenerator {
+    buckets: usize,
+    progress_callback: Option<ProgressCallback>,
+}
+
+impl WaveformGenerator {
+    /// 生成每个声道约 `buckets` 个数据点的波形概览
+    ///
+    /// 音频总时长未知时，数据点数量在 `buckets` 的一半到 `buckets` 之间
+    pub fn new(buckets: usize) -> Self {
+        Self {
+            buckets: buckets.max(1),
+            progress_callback: None,
+        }
+    }
+
+    /// 设置进度回调，每解码约一秒音频及完成时以已生成的部分调用
+    pub fn set_progress_callback<F>(&mut self, callback: F)
+    where
+        F: Fn(&Waveform) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Arc::new(callback));
+    }
+
+    /// 生成音频文件的波形概览
+    pub fn generate_file(&self, path: impl AsRef<Path>) -> Result<Waveform> {
+        let source = Decoder::try_from(File::open(path)?)?;
+        Ok(self.generate(source))
+    }
+
+    /// 生成 Reader 中音频的波形概览，Reader 的数据未就绪时阻塞等待
+    pub fn generate_reader<R>(&self, reader: R) -> Result<Waveform>
+    where
+        R: Read + Seek + Send + Sync + 'static,
+    {
+        let source = Decoder::new(reader)?;
+        Ok(self.generate(source))
+    }
+
+    /// 生成音频源的波形概览，声道数及采样率以开始时为准
+    pub fn generate<S: Source>(&self, mut source: S) -> Waveform {
+        let channels = source.channels().max(1);
+        let sample_rate = source.sample_rate().max(1);
+        let mut builder = match source.total_duration() {
+            Some(total) => {
+                let frames = (total.as_secs_f64() * sample_rate as f64).ceil() as u64;
+                let samples_per_bucket = frames.div_ceil(self.buckets as u64).max(1);
+                WaveformBuilder::new(
+                    channels,
+                    sample_rate,
+                    u32::try_from(samples_per_bucket).unwrap_or(u32::MAX),
+                )
+            }
+            None => WaveformBuilder::new(channels, sample_rate, INITIAL_SAMPLES_PER_BUCKET)
+                .with_max_buckets(self.buckets),
+        };
+
+        let progress_frames = (PROGRESS_INTERVAL.as_secs_f64() * sample_rate as f64) as u64;
+        let mut next_progress = progress_frames;
+        loop {
+            let mut count = 0;
+            for sample in source.by_ref().take(BLOCK_SAMPLES) {
+                builder.push(sample);
+                count += 1;
+            }
+            if count == 0 {
+                break;
+            }
+            if builder.frames() >= next_progress {
+                next_progress = builder.frames() + progress_frames;
+                self.emit_progress(&builder.waveform());
+            }
+        }
+
+        let waveform = builder.waveform();
+        self.emit_progress(&waveform);
+        waveform
+    }
+
+    fn emit_progress(&self, waveform: &Waveform) {
+        if let Some(ref cb) = self.progress_callback {
+            cb(waveform);
+        }
+    }
+}
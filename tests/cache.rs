@@ -0,0 +1,90 @@
+use remu_audio::loader::cache::{CacheEntry, DiskCache};
+
+const URL: &str = "http://example.com/audio.mp3";
+
+fn data() -> Vec<u8> {
+    (0..300u32).map(|i| i as u8).collect()
+}
+
+fn entry() -> CacheEntry {
+    CacheEntry {
+        url: URL.to_string(),
+        etag: Some("\"v1\"".to_string()),
+        total_len: Some(300),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn sparse_writes_are_kept_until_complete() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), u64::MAX).unwrap();
+    let data = data();
+
+    // 跳转后下载的数据与开头不连续
+    let mut writer = cache.writer(entry(), false).unwrap();
+    writer.write_at(0, &data[..100]);
+    writer.write_at(200, &data[200..]);
+    writer.finish();
+
+    let cached = cache.lookup(URL).unwrap();
+    assert_eq!(cached.ranges, vec![0..100, 200..300]);
+    // 继续下载时从连续部分的末尾开始
+    assert_eq!(cached.len, 100);
+    assert!(!cached.complete);
+
+    // 补齐缺失的部分后标记为已完整缓存
+    let mut writer = cache.writer(cached, true).unwrap();
+    writer.write_at(100, &data[100..200]);
+    writer.finish();
+
+    let cached = cache.lookup(URL).unwrap();
+    assert_eq!(cached.ranges, vec![0..300]);
+    assert_eq!(cached.len, 300);
+    assert!(cached.complete);
+    assert_eq!(cache.read(URL).unwrap(), data);
+}
+
+#[test]
+fn overlapping_and_adjacent_writes_are_merged() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), u64::MAX).unwrap();
+    let data = data();
+
+    let mut writer = cache.writer(entry(), false).unwrap();
+    writer.write_at(50, &data[50..100]);
+    writer.write_at(150, &data[150..200]);
+    writer.write_at(80, &data[80..160]);
+    writer.write_at(200, &data[200..250]);
+    writer.finish();
+
+    let cached = cache.lookup(URL).unwrap();
+    assert_eq!(cached.ranges, vec![50..250]);
+    assert_eq!(cached.len, 0);
+    assert_eq!(&cache.read(URL).unwrap()[50..250], &data[50..250]);
+}
+
+#[test]
+fn interrupted_writer_keeps_cached_ranges() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), u64::MAX).unwrap();
+    let data = data();
+
+    // 下载中断时未调用 finish
+    let mut writer = cache.writer(entry(), false).unwrap();
+    writer.write_at(0, &data[..120]);
+    drop(writer);
+
+    let cached = cache.lookup(URL).unwrap();
+    assert_eq!(cached.ranges, vec![0..120]);
+    assert_eq!(cached.len, 120);
+    assert!(!cached.complete);
+
+    // 不继续下载时覆盖原有数据
+    let mut writer = cache.writer(cached, false).unwrap();
+    writer.write_at(0, &data[..10]);
+    writer.finish();
+    let cached = cache.lookup(URL).unwrap();
+    assert_eq!(cached.ranges, vec![0..10]);
+    assert_eq!(cache.read(URL).unwrap(), &data[..10]);
+}
@@ -0,0 +1,111 @@
+//! 集成测试共用的本地 HTTP 服务器
+
+#![allow(dead_code)]
+
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// 收到的请求
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    /// 请求头的值，名称不区分大小写
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 返回的响应
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn ok(body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+}
+
+/// 在随机端口上启动 HTTP/1.1 服务器，返回形如 `http://127.0.0.1:port` 的地址
+///
+/// 每个连接只处理一个请求，忽略请求体
+pub async fn serve<F>(handler: F) -> String
+where
+    F: Fn(Request) -> Response + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let handler = Arc::new(handler);
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let handler = handler.clone();
+            tokio::spawn(async move {
+                let mut buf = Vec::new();
+                let mut chunk = [0u8; 1024];
+                while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut chunk).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => buf.extend_from_slice(&chunk[..n]),
+                    }
+                }
+                let text = String::from_utf8_lossy(&buf);
+                let mut lines = text.split("\r\n");
+                let mut request_line = lines.next().unwrap_or("").split(' ');
+                let method = request_line.next().unwrap_or("").to_string();
+                let path = request_line.next().unwrap_or("").to_string();
+                let headers = lines
+                    .take_while(|line| !line.is_empty())
+                    .filter_map(|line| line.split_once(':'))
+                    .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                    .collect();
+
+                let response = handler(Request {
+                    method,
+                    path,
+                    headers,
+                });
+                let mut head = format!("HTTP/1.1 {} Status\r\n", response.status);
+                for (key, value) in &response.headers {
+                    head.push_str(&format!("{}: {}\r\n", key, value));
+                }
+                head.push_str(&format!(
+                    "Content-Length: {}\r\nConnection: close\r\n\r\n",
+                    response.body.len()
+                ));
+                let _ = stream.write_all(head.as_bytes()).await;
+                let _ = stream.write_all(&response.body).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+    format!("http://{}", addr)
+}
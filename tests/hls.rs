@@ -0,0 +1,487 @@
+mod common;
+
+use common::{serve, Request, Response};
+use remu_audio::loader::downloader::DownloadStatus;
+use remu_audio::loader::hls::mpegts::{strip_id3, TsDemuxer};
+use remu_audio::loader::hls::playlist::Playlist;
+use remu_audio::loader::hls::HlsLoader;
+use remu_audio::loader::{LoaderEvent, RetryPolicy};
+use remu_audio::reader::AppendableDataWrapper;
+use remu_audio::Error;
+use reqwest::Url;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+fn base() -> Url {
+    Url::parse("https://example.com/live/audio/master.m3u8").unwrap()
+}
+
+#[test]
+fn parse_master_playlist() {
+    let text = "#EXTM3U
+#EXT-X-VERSION:3
+#EXT-X-STREAM-INF:BANDWIDTH=64000,CODECS=\"mp4a.40.5\"
+low/index.m3u8
+#EXT-X-STREAM-INF:CODECS=\"mp4a.40.2,avc1.4d401e\",BANDWIDTH=256000
+/high/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=128000
+https://cdn.example.com/mid/index.m3u8
+";
+    let Some(Playlist::Master(master)) = Playlist::parse(text, &base()) else {
+        panic!("not a master playlist");
+    };
+    let uris: Vec<&str> = master.variants.iter().map(|v| v.uri.as_str()).collect();
+    assert_eq!(
+        uris,
+        [
+            "https://example.com/live/audio/low/index.m3u8",
+            "https://example.com/high/index.m3u8",
+            "https://cdn.example.com/mid/index.m3u8",
+        ]
+    );
+    let bandwidths: Vec<u64> = master.variants.iter().map(|v| v.bandwidth).collect();
+    assert_eq!(bandwidths, [64000, 256000, 128000]);
+    // 引号内的逗号不分隔属性
+    assert_eq!(
+        master.variants[1].codecs.as_deref(),
+        Some("mp4a.40.2,avc1.4d401e")
+    );
+    assert_eq!(master.variants[2].codecs, None);
+
+    let select = |max| master.select_variant(max).unwrap().bandwidth;
+    assert_eq!(select(None), 256000);
+    assert_eq!(select(Some(200000)), 128000);
+    assert_eq!(select(Some(128000)), 128000);
+    // 均超过上限时选择码率最低的变体
+    assert_eq!(select(Some(1000)), 64000);
+}
+
+#[test]
+fn parse_media_playlist() {
+    let text = "#EXTM3U
+#EXT-X-TARGETDURATION:6
+#EXT-X-MEDIA-SEQUENCE:41
+#EXT-X-MAP:URI=\"init.mp4\"
+#EXTINF:6.0,
+seg41.m4s
+#EXTINF:5.5,title
+../other/seg42.m4s?token=a
+#EXT-X-KEY:METHOD=AES-128,URI=\"key\"
+#EXTINF:4,
+seg43.m4s
+#EXT-X-ENDLIST
+";
+    let Some(Playlist::Media(media)) = Playlist::parse(text, &base()) else {
+        panic!("not a media playlist");
+    };
+    assert_eq!(media.target_duration, 6.0);
+    assert_eq!(media.media_sequence, 41);
+    assert!(media.ended);
+    assert_eq!(media.duration(), 15.5);
+
+    let segments = &media.segments;
+    assert_eq!(segments.len(), 3);
+    assert_eq!(
+        segments[0].uri.as_str(),
+        "https://example.com/live/audio/seg41.m4s"
+    );
+    assert_eq!(
+        segments[1].uri.as_str(),
+        "https://example.com/live/other/seg42.m4s?token=a"
+    );
+    let sequences: Vec<u64> = segments.iter().map(|s| s.sequence).collect();
+    assert_eq!(sequences, [41, 42, 43]);
+    assert_eq!(segments[1].duration, 5.5);
+    let init = Url::parse("https://example.com/live/audio/init.mp4").unwrap();
+    assert!(segments.iter().all(|s| s.map.as_ref() == Some(&init)));
+    assert!(!segments[1].encrypted);
+    assert!(segments[2].encrypted);
+}
+
+#[test]
+fn live_playlist_without_endlist() {
+    let text = "#EXTM3U\n#EXT-X-TARGETDURATION:2\n#EXTINF:2,\na.aac\n";
+    let Some(Playlist::Media(media)) = Playlist::parse(text, &base()) else {
+        panic!("not a media playlist");
+    };
+    assert!(!media.ended);
+    assert_eq!(media.media_sequence, 0);
+}
+
+#[test]
+fn reject_non_playlist() {
+    assert_eq!(Playlist::parse("<html></html>", &base()), None);
+    assert_eq!(Playlist::parse("", &base()), None);
+}
+
+/// 生成 TS 包，负载不足一个包时以自适应字段填充
+fn ts_packet(pid: u16, payload_start: bool, payload: &[u8]) -> Vec<u8> {
+    assert!(payload.len() <= 184);
+    let mut packet = vec![
+        0x47,
+        (payload_start as u8) << 6 | (pid >> 8) as u8,
+        pid as u8,
+    ];
+    let stuffing = 184 - payload.len();
+    if stuffing == 0 {
+        packet.push(0x10);
+    } else {
+        // 自适应字段及负载
+        packet.push(0x30);
+        packet.push(stuffing as u8 - 1);
+        if stuffing > 1 {
+            packet.push(0);
+            packet.resize(packet.len() + stuffing - 2, 0xff);
+        }
+    }
+    packet.extend_from_slice(payload);
+    assert_eq!(packet.len(), 188);
+    packet
+}
+
+/// 生成 PSI 表的负载（pointer field 及末尾的 CRC，CRC 不校验）
+fn psi(table_id: u8, body: &[u8]) -> Vec<u8> {
+    let length = body.len() + 5 + 4;
+    let mut section = vec![
+        0,
+        table_id,
+        0xb0 | (length >> 8) as u8,
+        length as u8,
+        0,
+        1,
+        0xc1,
+        0,
+        0,
+    ];
+    section.extend_from_slice(body);
+    section.extend_from_slice(&[0; 4]);
+    section
+}
+
+/// 将 ADTS 数据封装为 PAT、PMT 及音频 PES 的 TS 流
+fn ts_stream(adts: &[u8]) -> Vec<u8> {
+    const PMT_PID: u16 = 0x1000;
+    const AUDIO_PID: u16 = 0x101;
+    let mut stream = Vec::new();
+    // 节目 1 的 PMT
+    stream.extend(ts_packet(
+        0,
+        true,
+        &psi(0x00, &[0, 1, 0xe0 | (PMT_PID >> 8) as u8, PMT_PID as u8]),
+    ));
+    // PCR PID、节目信息长度 0，视频流在前、ADTS 音频流在后
+    stream.extend(ts_packet(
+        PMT_PID,
+        true,
+        &psi(
+            0x02,
+            &[
+                0xe1, 0x00, 0xf0, 0x00, // PCR PID、节目信息长度
+                0x1b, 0xe1, 0x00, 0xf0, 0x00, // H.264
+                0x0f, 0xe1, 0x01, 0xf0, 0x00, // ADTS AAC
+            ],
+        ),
+    ));
+
+    // 每个 PES 包携带一半数据，PES 头中包含 PTS
+    for half in adts.chunks(adts.len().div_ceil(2)) {
+        let mut pes = vec![0, 0, 1, 0xc0, 0, 0, 0x80, 0x80, 5, 0x21, 0, 1, 0, 1];
+        pes.extend_from_slice(half);
+        for (i, payload) in pes.chunks(184).enumerate() {
+            stream.extend(ts_packet(AUDIO_PID, i == 0, payload));
+        }
+        // 其他流的数据应被忽略
+        stream.extend(ts_packet(0x100, true, &[0xaa; 184]));
+    }
+    stream
+}
+
+fn adts_payload() -> Vec<u8> {
+    // ADTS 帧头后跟任意数据
+    let mut adts = vec![0xff, 0xf1, 0x50, 0x80, 0x2e, 0x7f, 0xfc];
+    adts.extend((0..500u32).map(|i| (i * 7) as u8));
+    adts
+}
+
+#[test]
+fn demux_adts_from_ts() {
+    let adts = adts_payload();
+    let stream = ts_stream(&adts);
+    assert!(remu_audio::loader::hls::mpegts::is_mpegts(&stream));
+
+    let mut demuxer = TsDemuxer::new();
+    let mut out = Vec::new();
+    demuxer.push(&stream, &mut out);
+    demuxer.flush(&mut out);
+    assert_eq!(out, adts);
+}
+
+#[test]
+fn demux_ts_split_at_arbitrary_boundaries() {
+    let adts = adts_payload();
+    let stream = ts_stream(&adts);
+
+    let mut demuxer = TsDemuxer::new();
+    let mut out = Vec::new();
+    for chunk in stream.chunks(100) {
+        demuxer.push(chunk, &mut out);
+    }
+    demuxer.flush(&mut out);
+    assert_eq!(out, adts);
+}
+
+#[test]
+fn strip_id3_before_segment() {
+    let mut data = b"ID3\x04\x00\x00\x00\x00\x00\x03abc".to_vec();
+    data.extend_from_slice(b"\xff\xf1audio");
+    assert_eq!(strip_id3(&data), b"\xff\xf1audio");
+    assert_eq!(strip_id3(b"\xff\xf1audio"), b"\xff\xf1audio");
+}
+
+/// 收集加载器写入的数据
+#[derive(Clone, Default)]
+struct Collected {
+    data: Arc<Mutex<Vec<u8>>>,
+    completed: Arc<AtomicBool>,
+}
+
+impl AppendableDataWrapper for Collected {
+    fn append_data(&mut self, slice: &[u8]) {
+        self.data.lock().unwrap().extend_from_slice(slice);
+    }
+
+    fn complete(&mut self) {
+        self.completed.store(true, Ordering::SeqCst);
+    }
+
+    fn set_capacity(&mut self, _capacity: usize) {}
+}
+
+async fn wait_completed(loader: &HlsLoader, timeout: Duration) {
+    let deadline = Instant::now() + timeout;
+    while loader.status() == DownloadStatus::Downloading {
+        assert!(Instant::now() < deadline, "HLS loading timed out");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(loader.status(), DownloadStatus::Completed);
+}
+
+#[tokio::test]
+async fn load_vod_through_master_playlist() {
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let log = requests.clone();
+    let url = serve(move |request: Request| {
+        log.lock().unwrap().push(request.path.clone());
+        match request.path.as_str() {
+            "/master.m3u8" => Response::ok(
+                "#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=64000
+low.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=256000
+high.m3u8
+",
+            ),
+            "/low.m3u8" => Response::ok(
+                "#EXTM3U
+#EXT-X-TARGETDURATION:4
+#EXTINF:4,
+seg/0.aac
+#EXTINF:3.5,
+seg/1.aac
+#EXT-X-ENDLIST
+",
+            ),
+            "/seg/0.aac" => Response::ok("first,"),
+            // ID3 时间戳标签被去掉
+            "/seg/1.aac" => Response::ok(b"ID3\x04\x00\x00\x00\x00\x00\x01xsecond".to_vec()),
+            _ => Response::status(404),
+        }
+    })
+    .await;
+
+    let collected = Collected::default();
+    let loader = HlsLoader::new(collected.clone());
+    loader.set_max_bandwidth(Some(100_000));
+    loader
+        .load(&format!("{}/master.m3u8", url), None)
+        .await
+        .unwrap();
+    // 点播的总时长在加载播放列表后即可得到
+    assert_eq!(loader.duration(), Some(Duration::from_secs_f64(7.5)));
+
+    wait_completed(&loader, Duration::from_secs(5)).await;
+    assert_eq!(*collected.data.lock().unwrap(), b"first,second");
+    assert!(collected.completed.load(Ordering::SeqCst));
+    assert_eq!(loader.buffered_duration(), Duration::from_secs_f64(7.5));
+    // 包含 EXT-X-ENDLIST 时不刷新播放列表
+    assert_eq!(
+        *requests.lock().unwrap(),
+        ["/master.m3u8", "/low.m3u8", "/seg/0.aac", "/seg/1.aac"]
+    );
+}
+
+#[tokio::test]
+async fn reload_live_playlist_until_endlist() {
+    let reloads = Arc::new(AtomicUsize::new(0));
+    let counter = reloads.clone();
+    let url = serve(move |request: Request| match request.path.as_str() {
+        "/live.m3u8" => {
+            // 第一次返回 5 个分片，刷新后新增一个分片并结束
+            let mut text =
+                String::from("#EXTM3U\n#EXT-X-TARGETDURATION:1\n#EXT-X-MEDIA-SEQUENCE:10\n");
+            for i in 10..15 {
+                text.push_str(&format!("#EXTINF:1,\n{}.aac\n", i));
+            }
+            if counter.fetch_add(1, Ordering::SeqCst) > 0 {
+                text.push_str("#EXTINF:1,\n15.aac\n#EXT-X-ENDLIST\n");
+            }
+            Response::ok(text)
+        }
+        path => match path.strip_suffix(".aac") {
+            Some(name) => Response::ok(format!("[{}]", &name[1..])),
+            None => Response::status(404),
+        },
+    })
+    .await;
+
+    let collected = Collected::default();
+    let loader = HlsLoader::new(collected.clone());
+    loader
+        .load(&format!("{}/live.m3u8", url), None)
+        .await
+        .unwrap();
+    // 直播没有总时长
+    assert_eq!(loader.duration(), None);
+
+    wait_completed(&loader, Duration::from_secs(10)).await;
+    // 从倒数第三个分片开始播放，刷新后只下载新增的分片
+    assert_eq!(*collected.data.lock().unwrap(), b"[12][13][14][15]");
+    assert_eq!(reloads.load(Ordering::SeqCst), 2);
+}
+
+/// 两个分片的点播播放列表，`/0.aac` 由 `segment` 处理
+async fn serve_vod<F>(segment: F) -> String
+where
+    F: Fn() -> Response + Send + Sync + 'static,
+{
+    serve(move |request: Request| match request.path.as_str() {
+        "/vod.m3u8" => Response::ok(
+            "#EXTM3U
+#EXT-X-TARGETDURATION:1
+#EXTINF:1,
+0.aac
+#EXTINF:1,
+1.aac
+#EXT-X-ENDLIST
+",
+        ),
+        "/0.aac" => segment(),
+        "/1.aac" => Response::ok("[1]"),
+        _ => Response::status(404),
+    })
+    .await
+}
+
+/// 创建记录事件的加载器
+fn loader_with_events(
+    collected: &Collected,
+    policy: RetryPolicy,
+) -> (HlsLoader, Arc<Mutex<Vec<LoaderEvent>>>) {
+    let loader = HlsLoader::new(collected.clone());
+    loader.set_retry_policy(policy);
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorder = events.clone();
+    loader.set_callback(move |event| {
+        if !matches!(event, LoaderEvent::Progress { .. }) {
+            recorder.lock().unwrap().push(event);
+        }
+    });
+    (loader, events)
+}
+
+#[tokio::test]
+async fn retry_failed_segment_requests() {
+    let failures = Arc::new(AtomicUsize::new(0));
+    let counter = failures.clone();
+    let url = serve_vod(move || {
+        // 前两次请求失败
+        if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+            Response::status(503)
+        } else {
+            Response::ok("[0]")
+        }
+    })
+    .await;
+
+    let collected = Collected::default();
+    let policy = RetryPolicy::new(3, Duration::from_millis(10));
+    let (loader, events) = loader_with_events(&collected, policy);
+    loader
+        .load(&format!("{}/vod.m3u8", url), None)
+        .await
+        .unwrap();
+
+    wait_completed(&loader, Duration::from_secs(5)).await;
+    assert_eq!(*collected.data.lock().unwrap(), b"[0][1]");
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            LoaderEvent::Retrying { attempt: 1 },
+            LoaderEvent::Retrying { attempt: 2 },
+            LoaderEvent::Resumed,
+            LoaderEvent::Completed,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn abort_with_error_after_retries_fail() {
+    let url = serve_vod(|| Response::status(404)).await;
+
+    let collected = Collected::default();
+    let policy = RetryPolicy::new(1, Duration::from_millis(10));
+    let (loader, events) = loader_with_events(&collected, policy);
+    loader
+        .load(&format!("{}/vod.m3u8", url), None)
+        .await
+        .unwrap();
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while loader.status() == DownloadStatus::Downloading {
+        assert!(Instant::now() < deadline, "HLS loading timed out");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(loader.status(), DownloadStatus::Aborted);
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            LoaderEvent::Retrying { attempt: 1 },
+            LoaderEvent::Aborted {
+                error: Error::HttpStatus(404)
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn invalid_playlist_is_not_retried() {
+    let requests = Arc::new(AtomicUsize::new(0));
+    let counter = requests.clone();
+    let url = serve(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        Response::ok("<html></html>")
+    })
+    .await;
+
+    let collected = Collected::default();
+    let (loader, events) = loader_with_events(&collected, RetryPolicy::default());
+    let error = loader
+        .load(&format!("{}/vod.m3u8", url), None)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::UnsupportedFormat(_)));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+    assert_eq!(*events.lock().unwrap(), [LoaderEvent::Aborted { error }]);
+}
@@ -0,0 +1,138 @@
+use remu_audio::loader::icy::IcyStripper;
+use remu_audio::AudioMetadata;
+
+const METAINT: usize = 16;
+
+/// 元数据块：长度字节及以 0 补齐到 16 字节整数倍的内容
+fn meta_block(text: &str) -> Vec<u8> {
+    let len = text.len().div_ceil(16);
+    let mut block = vec![len as u8];
+    block.extend_from_slice(text.as_bytes());
+    block.resize(1 + len * 16, 0);
+    block
+}
+
+/// 每隔 `METAINT` 字节音频插入一个元数据块
+fn stream(audio: &[u8], blocks: &[Vec<u8>]) -> Vec<u8> {
+    let mut stream = Vec::new();
+    for (i, chunk) in audio.chunks(METAINT).enumerate() {
+        stream.extend_from_slice(chunk);
+        if chunk.len() == METAINT {
+            stream.extend(blocks.get(i).cloned().unwrap_or_else(|| vec![0]));
+        }
+    }
+    stream
+}
+
+fn audio(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8).collect()
+}
+
+/// 按指定大小分段处理，返回音频数据及解析到的元数据
+fn process(stream: &[u8], chunk_size: usize) -> (Vec<u8>, Vec<AudioMetadata>) {
+    let mut stripper = IcyStripper::new(METAINT);
+    let mut audio = Vec::new();
+    let mut updates = Vec::new();
+    for chunk in stream.chunks(chunk_size) {
+        updates.extend(stripper.process(chunk, &mut audio));
+    }
+    (audio, updates)
+}
+
+fn titles(updates: &[AudioMetadata]) -> Vec<&str> {
+    updates.iter().filter_map(|m| m.title.as_deref()).collect()
+}
+
+#[test]
+fn metadata_split_across_chunk_boundaries() {
+    let audio_data = audio(METAINT * 3);
+    let blocks = [
+        meta_block("StreamTitle='First Song';"),
+        meta_block("StreamTitle='Second Song';StreamUrl='http://example.com';"),
+    ];
+    let data = stream(&audio_data, &blocks);
+
+    // 元数据块及其长度字节可能被任意分段
+    for chunk_size in [1, 3, 7, 16, 17, 33] {
+        let (audio, updates) = process(&data, chunk_size);
+        assert_eq!(audio, audio_data, "chunk size {}", chunk_size);
+        assert_eq!(titles(&updates), ["First Song", "Second Song"]);
+    }
+
+    // 一段数据中包含多个元数据块时只返回最后一个
+    let (audio, updates) = process(&data, data.len());
+    assert_eq!(audio, audio_data);
+    assert_eq!(titles(&updates), ["Second Song"]);
+}
+
+#[test]
+fn zero_length_metadata() {
+    let audio_data = audio(METAINT * 4 + 5);
+    // 长度字节为 0 表示没有元数据
+    let data = stream(
+        &audio_data,
+        &[vec![0], vec![0], meta_block("StreamTitle='Song';")],
+    );
+    assert_eq!(data.len(), audio_data.len() + 1 + 1 + 33 + 1);
+
+    for chunk_size in [1, METAINT, METAINT + 1, data.len()] {
+        let (audio, updates) = process(&data, chunk_size);
+        assert_eq!(audio, audio_data);
+        assert_eq!(titles(&updates), ["Song"]);
+    }
+}
+
+#[test]
+fn repeated_and_empty_titles_are_ignored() {
+    let audio_data = audio(METAINT * 4);
+    let blocks = [
+        meta_block("StreamTitle='Song';"),
+        meta_block("StreamTitle='Song';"),
+        meta_block("StreamTitle='';"),
+        meta_block("StreamTitle='Next';"),
+    ];
+    let (_, updates) = process(&stream(&audio_data, &blocks), 64);
+    assert_eq!(titles(&updates), ["Song", "Next"]);
+}
+
+#[test]
+fn stream_title_with_quotes_and_semicolons() {
+    let audio_data = audio(METAINT * 3);
+    let blocks = [
+        meta_block("StreamTitle='Guns N' Roses - Sweet Child O' Mine';"),
+        meta_block("StreamTitle='Artist; Feat. Other - Don't Stop; Live';StreamUrl='';"),
+        meta_block("StreamTitle='It''s Here';"),
+    ];
+    let (_, updates) = process(&stream(&audio_data, &blocks), 5);
+    assert_eq!(
+        titles(&updates),
+        [
+            "Guns N' Roses - Sweet Child O' Mine",
+            "Artist; Feat. Other - Don't Stop; Live",
+            "It''s Here",
+        ]
+    );
+
+    // 其他字段保存在标签中
+    let tags = &updates[1].tags;
+    assert_eq!(
+        tags,
+        &[
+            (
+                "StreamTitle".to_string(),
+                "Artist; Feat. Other - Don't Stop; Live".to_string()
+            ),
+            ("StreamUrl".to_string(), String::new()),
+        ]
+    );
+}
+
+#[test]
+fn from_headers_reads_metaint() {
+    let mut headers = reqwest::header::HeaderMap::new();
+    assert!(IcyStripper::from_headers(&headers).is_none());
+    headers.insert("icy-metaint", "0".parse().unwrap());
+    assert!(IcyStripper::from_headers(&headers).is_none());
+    headers.insert("icy-metaint", " 8192 ".parse().unwrap());
+    assert!(IcyStripper::from_headers(&headers).is_some());
+}
@@ -0,0 +1,170 @@
+mod common;
+
+use common::{serve, Request, Response};
+use remu_audio::loader::cache::DiskCache;
+use remu_audio::loader::downloader::{DownloadStatus, Downloader};
+use remu_audio::loader::{Auth, LoaderOptions};
+use remu_audio::player::{NullOutput, Output, Player};
+use remu_audio::reader::MVecU8Wrapper;
+use remu_audio::render::{RenderOptions, Renderer};
+use rodio::source::{SineWave, Source};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 生成短时长的 WAV 数据
+fn wav() -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sine.wav");
+    let source = SineWave::new(440.0).take_duration(Duration::from_millis(200));
+    Renderer::new(RenderOptions::default())
+        .render(source, &path)
+        .unwrap();
+    std::fs::read(path).unwrap()
+}
+
+/// 启动返回 WAV 数据的服务器，并记录收到的请求
+async fn serve_wav() -> (String, Arc<Mutex<Vec<Request>>>) {
+    let body = wav();
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let recorder = requests.clone();
+    let base = serve(move |request| {
+        recorder.lock().unwrap().push(request);
+        Response::ok(body.clone()).header("Content-Type", "audio/wav")
+    })
+    .await;
+    (base, requests)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn load_url_sends_headers_auth_and_user_agent() {
+    let (base, requests) = serve_wav().await;
+    let mut player = Player::with_output(Output::Null(NullOutput::unlimited())).unwrap();
+
+    let options = LoaderOptions {
+        headers: vec![("X-Client".to_string(), "remu".to_string())],
+        auth: Some(Auth::Basic {
+            username: "user".to_string(),
+            password: Some("secret".to_string()),
+        }),
+        user_agent: Some("remu-test/1.0".to_string()),
+        ..Default::default()
+    };
+    player
+        .load_url(&format!("{}/audio.wav", base), options)
+        .await
+        .unwrap();
+
+    let requests = requests.lock().unwrap();
+    let request = &requests[0];
+    assert_eq!(request.method, "GET");
+    assert_eq!(request.path, "/audio.wav");
+    assert_eq!(request.header("x-client"), Some("remu"));
+    // base64("user:secret")
+    assert_eq!(
+        request.header("authorization"),
+        Some("Basic dXNlcjpzZWNyZXQ=")
+    );
+    assert_eq!(request.header("user-agent"), Some("remu-test/1.0"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn enqueue_url_accepts_auth_and_headers_directly() {
+    let (base, requests) = serve_wav().await;
+    let mut player = Player::with_output(Output::Null(NullOutput::unlimited())).unwrap();
+
+    player
+        .enqueue_url(
+            &format!("{}/first.wav", base),
+            Auth::Bearer("token".to_string()),
+        )
+        .await
+        .unwrap();
+    let headers = vec![("X-Track".to_string(), "2".to_string())];
+    player
+        .load_url(&format!("{}/second.wav", base), headers)
+        .await
+        .unwrap();
+
+    let requests = requests.lock().unwrap();
+    let first = requests.iter().find(|r| r.path == "/first.wav").unwrap();
+    assert_eq!(first.header("authorization"), Some("Bearer token"));
+    assert_eq!(first.header("x-track"), None);
+    let second = requests.iter().find(|r| r.path == "/second.wav").unwrap();
+    assert_eq!(second.header("x-track"), Some("2"));
+    assert_eq!(second.header("authorization"), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn load_url_accepts_optional_headers() {
+    let (base, requests) = serve_wav().await;
+    let mut player = Player::with_output(Output::Null(NullOutput::unlimited())).unwrap();
+
+    let headers = vec![("X-Track".to_string(), "2".to_string())];
+    player
+        .enqueue_url(&format!("{}/headers.wav", base), Some(headers))
+        .await
+        .unwrap();
+    player
+        .load_url(&format!("{}/plain.wav", base), None)
+        .await
+        .unwrap();
+
+    let requests = requests.lock().unwrap();
+    let plain = requests.iter().find(|r| r.path == "/plain.wav").unwrap();
+    assert_eq!(plain.header("x-track"), None);
+    let with_headers = requests.iter().find(|r| r.path == "/headers.wav").unwrap();
+    assert_eq!(with_headers.header("x-track"), Some("2"));
+}
+
+/// 通过磁盘缓存下载，等待下载完成后返回下载的数据
+async fn download_cached(url: &str, cache: &DiskCache) -> Vec<u8> {
+    let wrapper = MVecU8Wrapper::new();
+    let loader = Downloader::new(wrapper.clone());
+    loader.set_cache(cache.clone());
+    loader.download(url, None).await.unwrap();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while loader.status() == DownloadStatus::Downloading {
+        assert!(Instant::now() < deadline, "download timed out");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(loader.status(), DownloadStatus::Completed);
+    let data = wrapper.data().lock().unwrap().clone();
+    data
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn cache_is_revalidated_with_head_requests() {
+    let body = wav();
+    let etag = Arc::new(Mutex::new("\"v1\"".to_string()));
+    let methods = Arc::new(Mutex::new(Vec::new()));
+    let (server_etag, recorder, server_body) = (etag.clone(), methods.clone(), body.clone());
+    let base = serve(move |request| {
+        recorder.lock().unwrap().push(request.method);
+        Response::ok(server_body.clone())
+            .header("Content-Type", "audio/wav")
+            .header("ETag", &server_etag.lock().unwrap())
+    })
+    .await;
+    let url = format!("{}/audio.wav", base);
+    let dir = tempfile::tempdir().unwrap();
+    let cache = DiskCache::new(dir.path(), u64::MAX).unwrap();
+
+    assert_eq!(download_cached(&url, &cache).await, body);
+    assert!(cache.lookup(&url).unwrap().complete);
+
+    // 从缓存读取，校验值未变化时保留缓存
+    assert_eq!(download_cached(&url, &cache).await, body);
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(*methods.lock().unwrap(), ["GET", "HEAD"]);
+    assert!(cache.lookup(&url).is_some());
+
+    // 校验值变化时删除缓存
+    *etag.lock().unwrap() = "\"v2\"".to_string();
+    assert_eq!(download_cached(&url, &cache).await, body);
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while cache.lookup(&url).is_some() {
+        assert!(Instant::now() < deadline, "stale cache entry was kept");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert_eq!(*methods.lock().unwrap(), ["GET", "HEAD", "HEAD"]);
+}
@@ -0,0 +1,137 @@
+use remu_audio::loudness::LoudnessAnalyzer;
+use rodio::buffer::SamplesBuffer;
+use std::f32::consts::FRAC_PI_4;
+use std::f64::consts::TAU;
+
+const SAMPLE_RATE: u32 = 48_000;
+
+/// 生成各声道相同的正弦波采样，`loudness` 为单声道时的响度
+///
+/// 满幅的 1kHz 正弦波在单声道中的响度为 -3.01 LUFS
+fn sine(freq: f32, loudness: f32, channels: u16, seconds: f32) -> Vec<f32> {
+    let amplitude = 10f32.powf((loudness + 3.01) / 20.0);
+    sine_with_phase(freq, amplitude, 0.0, channels, seconds)
+}
+
+fn sine_with_phase(freq: f32, amplitude: f32, phase: f32, channels: u16, seconds: f32) -> Vec<f32> {
+    let frames = (seconds * SAMPLE_RATE as f32) as usize;
+    (0..frames)
+        .flat_map(|i| {
+            let t = i as f64 / SAMPLE_RATE as f64;
+            let sample = amplitude * (TAU * freq as f64 * t + phase as f64).sin() as f32;
+            std::iter::repeat_n(sample, channels as usize)
+        })
+        .collect()
+}
+
+fn buffer(channels: u16, samples: Vec<f32>) -> SamplesBuffer {
+    SamplesBuffer::new(channels, SAMPLE_RATE, samples)
+}
+
+fn assert_close(value: f64, expected: f64, tolerance: f64) {
+    assert!(
+        (value - expected).abs() <= tolerance,
+        "{value} is not within {tolerance} of {expected}"
+    );
+}
+
+#[test]
+fn mono_sine_at_minus_23_lufs() {
+    let samples = sine(1000.0, -23.0, 1, 10.0);
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+
+    assert_close(analysis.integrated.unwrap(), -23.0, 0.1);
+    assert_close(analysis.replay_gain().unwrap() as f64, 5.0, 0.1);
+    // 响度稳定的音频，每个瞬时及短期测量块的响度均相同
+    assert_eq!(analysis.momentary.len(), 97);
+    assert_eq!(analysis.short_term.len(), 71);
+    for loudness in analysis.momentary.iter().chain(&analysis.short_term) {
+        assert_close(*loudness, -23.0, 0.1);
+    }
+    assert_close(analysis.loudness_range.unwrap(), 0.0, 0.1);
+}
+
+#[test]
+fn stereo_sine_at_minus_23_lufs() {
+    // 两个声道的能量相加，每个声道比单声道时低 3.01 dB
+    let samples = sine(1000.0, -26.01, 2, 10.0);
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(2, samples));
+
+    assert_close(analysis.integrated.unwrap(), -23.0, 0.1);
+    assert_close(analysis.duration.as_secs_f64(), 10.0, 1e-6);
+}
+
+#[test]
+fn silence_and_quiet_audio_are_below_absolute_gate() {
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, vec![0.0; SAMPLE_RATE as usize * 5]));
+    assert_eq!(analysis.integrated, None);
+    assert_eq!(analysis.loudness_range, None);
+    assert!(analysis.momentary.iter().all(|l| *l == f64::NEG_INFINITY));
+
+    let samples = sine(1000.0, -75.0, 1, 5.0);
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+    assert_eq!(analysis.integrated, None);
+}
+
+#[test]
+fn too_short_for_a_block() {
+    let samples = sine(1000.0, -23.0, 1, 0.3);
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+    assert_eq!(analysis.integrated, None);
+    assert!(analysis.momentary.is_empty());
+}
+
+#[test]
+fn absolute_gate_ignores_silence() {
+    let mut samples = sine(1000.0, -23.0, 1, 10.0);
+    samples.extend(std::iter::repeat_n(0.0, SAMPLE_RATE as usize * 10));
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+
+    // 静音部分低于绝对门限，不计入综合响度
+    assert_close(analysis.integrated.unwrap(), -23.0, 0.1);
+}
+
+#[test]
+fn relative_gate_ignores_quiet_passages() {
+    // -40 LUFS 的部分低于相对门限（-23 - 10 LU），不计入综合响度
+    let mut samples = sine(1000.0, -23.0, 1, 10.0);
+    samples.extend(sine(1000.0, -40.0, 1, 10.0));
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+    assert_close(analysis.integrated.unwrap(), -23.0, 0.2);
+
+    // -30 LUFS 的部分高于相对门限，按能量平均
+    let mut samples = sine(1000.0, -23.0, 1, 10.0);
+    samples.extend(sine(1000.0, -30.0, 1, 10.0));
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+    let expected = 10.0 * ((10f64.powf(-2.3) + 10f64.powf(-3.0)) / 2.0).log10();
+    assert_close(analysis.integrated.unwrap(), expected, 0.2);
+}
+
+#[test]
+fn loudness_range_of_two_levels() {
+    // 与 EBU Tech 3342 的测试信号相同：-20 LUFS 及 -30 LUFS 两段，响度范围为 10 LU
+    let mut samples = sine(1000.0, -23.01, 2, 10.0);
+    samples.extend(sine(1000.0, -33.01, 2, 10.0));
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(2, samples));
+    assert_close(analysis.loudness_range.unwrap(), 10.0, 1.0);
+}
+
+#[test]
+fn true_peak_exceeds_sample_peak_between_samples() {
+    // 采样率四分之一处的正弦波，采样点落在 45° 相位上，波峰位于两个采样之间
+    let samples = sine_with_phase(SAMPLE_RATE as f32 / 4.0, 1.0, FRAC_PI_4, 1, 1.0);
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(1, samples));
+
+    assert!((analysis.sample_peak - FRAC_PI_4.sin()).abs() < 1e-3);
+    assert!(analysis.true_peak > 0.95, "{}", analysis.true_peak);
+    assert!(analysis.true_peak_db() > -0.5);
+}
+
+#[test]
+fn true_peak_matches_sample_peak_at_low_frequency() {
+    let samples = sine_with_phase(100.0, 0.5, 0.0, 2, 1.0);
+    let analysis = LoudnessAnalyzer::new().analyze(buffer(2, samples));
+
+    assert!((analysis.sample_peak - 0.5).abs() < 1e-3);
+    assert!((analysis.true_peak - analysis.sample_peak).abs() < 0.01);
+}
@@ -0,0 +1,304 @@
+use remu_audio::player::{
+    AudioEffect, Crossfade, FadeCurve, NullOutput, Output, PlaybackControl, Player,
+};
+use remu_audio::render::{RenderOptions, Renderer};
+use remu_audio::PlayerEvent;
+use rodio::source::{SineWave, Source};
+use rodio::{ChannelCount, SampleRate};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 生成指定时长的正弦波 WAV 文件
+fn write_sine(path: &Path, duration: Duration) {
+    let source = SineWave::new(440.0).take_duration(duration).amplify(0.5);
+    Renderer::new(RenderOptions::default())
+        .render(source, path)
+        .unwrap();
+}
+
+/// 记录播放器事件
+fn record(player: &Player) -> Arc<Mutex<Vec<PlayerEvent>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let recorder = events.clone();
+    player.set_callback(move |event| recorder.lock().unwrap().push(event));
+    events
+}
+
+/// 等待指定事件出现，超时返回 `false`
+fn wait_for(events: &Mutex<Vec<PlayerEvent>>, event: &PlayerEvent) -> bool {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while Instant::now() < deadline {
+        if events.lock().unwrap().contains(event) {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[tokio::test]
+async fn play_pause_seek_and_end_on_null_output() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sine.wav");
+    write_sine(&path, Duration::from_millis(600));
+
+    let mut player = Player::with_output(Output::Null(NullOutput::real_time())).unwrap();
+    let events = record(&player);
+
+    player.load_file(path.to_str().unwrap()).await.unwrap();
+    let duration = player.duration().unwrap();
+    assert!(duration.abs_diff(Duration::from_millis(600)) < Duration::from_millis(5));
+    assert!(player.paused());
+
+    player.play();
+    std::thread::sleep(Duration::from_millis(100));
+    player.pause();
+    assert!(player.paused());
+    // 暂停在输出线程的下一次读取时生效，之后播放位置不再前进
+    std::thread::sleep(Duration::from_millis(50));
+    let paused_at = player.position();
+    assert!(paused_at > Duration::ZERO);
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(player.position(), paused_at);
+
+    player.seek(Duration::from_millis(300)).unwrap();
+    assert!(player.position().abs_diff(Duration::from_millis(300)) < Duration::from_millis(20));
+    player.play();
+
+    assert!(wait_for(&events, &PlayerEvent::Ended));
+    assert!(player.ended());
+
+    let events = events.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        vec![
+            PlayerEvent::LoadStart,
+            PlayerEvent::TrackChange { index: 0 },
+            PlayerEvent::DurationChange,
+            PlayerEvent::LoadedMetadata,
+            PlayerEvent::LoadedData,
+            PlayerEvent::Play,
+            PlayerEvent::Playing,
+            PlayerEvent::Pause,
+            PlayerEvent::Seeking,
+            PlayerEvent::Seeked,
+            PlayerEvent::Play,
+            PlayerEvent::Playing,
+            PlayerEvent::Ended,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn queue_advances_to_next_track() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("first.wav");
+    let second = dir.path().join("second.wav");
+    write_sine(&first, Duration::from_millis(200));
+    write_sine(&second, Duration::from_millis(300));
+
+    let mut player = Player::with_output(Output::Null(NullOutput::unlimited())).unwrap();
+    let events = record(&player);
+
+    player.enqueue_file(first.to_str().unwrap()).await.unwrap();
+    player.enqueue_file(second.to_str().unwrap()).await.unwrap();
+    assert_eq!(player.queue_len(), 2);
+    player.play();
+
+    assert!(wait_for(&events, &PlayerEvent::Ended));
+    assert_eq!(player.current_index(), Some(1));
+
+    let events = events.lock().unwrap().clone();
+    let track_changes: Vec<_> = events
+        .iter()
+        .filter(|e| matches!(e, PlayerEvent::TrackChange { .. }))
+        .cloned()
+        .collect();
+    assert_eq!(
+        track_changes,
+        vec![
+            PlayerEvent::TrackChange { index: 0 },
+            PlayerEvent::TrackChange { index: 1 },
+        ]
+    );
+    assert_eq!(events.last(), Some(&PlayerEvent::Ended));
+}
+
+#[tokio::test]
+async fn crossfade_to_next_track() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = dir.path().join("first.wav");
+    let second = dir.path().join("second.wav");
+    write_sine(&first, Duration::from_millis(500));
+    write_sine(&second, Duration::from_millis(500));
+
+    let mut player = Player::with_output(Output::Null(NullOutput::real_time())).unwrap();
+    player.set_crossfade(Some(Crossfade::new(
+        Duration::from_millis(200),
+        FadeCurve::EqualPower,
+    )));
+    let events = record(&player);
+
+    player.enqueue_file(first.to_str().unwrap()).await.unwrap();
+    player.enqueue_file(second.to_str().unwrap()).await.unwrap();
+    let started = Instant::now();
+    player.play();
+
+    assert!(wait_for(&events, &PlayerEvent::TrackChange { index: 1 }));
+    // 第二个音轨在第一个音轨结束前开始淡入
+    let switched = started.elapsed();
+    assert!(switched < Duration::from_millis(450), "{:?}", switched);
+    assert!(wait_for(&events, &PlayerEvent::Ended));
+    let elapsed = started.elapsed();
+    assert!(elapsed < Duration::from_millis(950), "{:?}", elapsed);
+}
+
+/// 前半段为 8kHz 单声道、后半段为 16kHz 立体声的音频，各 1 秒
+struct FormatChange {
+    pos: usize,
+}
+
+impl FormatChange {
+    const FIRST: usize = 8000;
+    const SECOND: usize = 32000;
+}
+
+impl Iterator for FormatChange {
+    type Item = f32;
+
+    fn next(&mut self) -> Option<f32> {
+        if self.pos >= Self::FIRST + Self::SECOND {
+            return None;
+        }
+        self.pos += 1;
+        Some(0.1)
+    }
+}
+
+impl Source for FormatChange {
+    fn current_span_len(&self) -> Option<usize> {
+        if self.pos < Self::FIRST {
+            Some(Self::FIRST - self.pos)
+        } else {
+            Some(Self::FIRST + Self::SECOND - self.pos)
+        }
+    }
+
+    fn channels(&self) -> u16 {
+        if self.pos < Self::FIRST {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn sample_rate(&self) -> u32 {
+        if self.pos < Self::FIRST {
+            8000
+        } else {
+            16000
+        }
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        Some(Duration::from_secs(2))
+    }
+}
+
+#[tokio::test]
+async fn position_follows_format_changes() {
+    // 原速时直接输出，变速时按 WSOLA 伸缩，播放位置均按输入的格式计算
+    for rate in [1.0, 1.5] {
+        let mut player = Player::with_output(Output::Null(NullOutput::unlimited())).unwrap();
+        player.set_playback_rate(rate);
+        let events = record(&player);
+        player.load_source(FormatChange { pos: 0 }).unwrap();
+        player.play();
+
+        assert!(wait_for(&events, &PlayerEvent::Ended));
+        let position = player.position();
+        assert!(
+            position.abs_diff(Duration::from_secs(2)) < Duration::from_millis(50),
+            "rate {}: {:?}",
+            rate,
+            position
+        );
+    }
+}
+
+#[tokio::test]
+async fn render_at_normal_rate_is_bit_exact() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("input.wav");
+    let output = dir.path().join("output.wav");
+    write_sine(&input, Duration::from_millis(300));
+
+    let mut player = Player::with_output(Output::Null(NullOutput::unlimited())).unwrap();
+    player.enqueue_file(input.to_str().unwrap()).await.unwrap();
+    player
+        .render_to_file(&output, Renderer::new(RenderOptions::default()))
+        .await
+        .unwrap();
+
+    // 原速时不经过 WSOLA，采样与输入完全一致
+    assert_eq!(
+        std::fs::read(&output).unwrap(),
+        std::fs::read(&input).unwrap()
+    );
+}
+
+/// 记录处理次数的效果节点
+struct CountBlocks(Arc<AtomicUsize>);
+
+impl AudioEffect for CountBlocks {
+    fn process(&mut self, _: &mut [f32], _: ChannelCount, _: SampleRate) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn effects_are_created_on_the_calling_thread() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("sine.wav");
+    write_sine(&path, Duration::from_secs(2));
+
+    let mut player = Player::with_output(Output::Null(NullOutput::real_time())).unwrap();
+    player.load_file(path.to_str().unwrap()).await.unwrap();
+    player.play();
+
+    let created = Arc::new(Mutex::new(Vec::new()));
+    let processed = Arc::new(AtomicUsize::new(0));
+    let factory = {
+        let created = created.clone();
+        let processed = processed.clone();
+        move || {
+            created.lock().unwrap().push(std::thread::current().id());
+            CountBlocks(processed.clone())
+        }
+    };
+    let effects = player.effects();
+    let id = effects.push(factory.clone());
+    // 正在播放的音轨的节点实例在添加时创建，音频线程只切换效果链
+    assert_eq!(*created.lock().unwrap(), vec![std::thread::current().id()]);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while processed.load(Ordering::SeqCst) == 0 {
+        assert!(Instant::now() < deadline, "effect was not applied");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // 在其他线程中修改时，实例在该线程中创建
+    let handle = std::thread::spawn(move || {
+        effects.set_bypassed(id, true);
+        effects.push(factory);
+        std::thread::current().id()
+    });
+    let other = handle.join().unwrap();
+    assert_eq!(
+        *created.lock().unwrap(),
+        vec![std::thread::current().id(), other]
+    );
+    player.stop();
+}
@@ -0,0 +1,177 @@
+use remu_audio::player::PrefetchSource;
+use rodio::buffer::SamplesBuffer;
+use rodio::source::{SeekError, Source};
+use rodio::{ChannelCount, SampleRate};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+use tokio::runtime::{Handle, Runtime};
+
+const SAMPLE_RATE: u32 = 1000;
+
+/// 采样值各不相同的单声道音频，时长 1 秒
+fn ramp() -> SamplesBuffer {
+    ramp_of(SAMPLE_RATE as usize)
+}
+
+fn ramp_of(len: usize) -> SamplesBuffer {
+    let samples = (0..len).map(|i| i as f32 / SAMPLE_RATE as f32);
+    SamplesBuffer::new(1, SAMPLE_RATE, samples.collect::<Vec<_>>())
+}
+
+/// 等待缓冲区中至少有 `samples` 个采样
+fn wait_buffered(source: &PrefetchSource, samples: usize) {
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while source.buffered_samples() < samples {
+        assert!(Instant::now() < deadline, "prefetch timed out");
+        std::thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// 跳转缓慢的音频源
+struct SlowSeek(SamplesBuffer);
+
+impl Iterator for SlowSeek {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+impl Source for SlowSeek {
+    fn current_span_len(&self) -> Option<usize> {
+        self.0.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.0.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.0.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.0.total_duration()
+    }
+
+    fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+        std::thread::sleep(Duration::from_millis(300));
+        self.0.try_seek(pos)
+    }
+}
+
+/// 记录解码所在线程的音频源
+struct RecordThreads(SamplesBuffer, Arc<Mutex<HashSet<ThreadId>>>);
+
+impl Iterator for RecordThreads {
+    type Item = rodio::Sample;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.1.lock().unwrap().insert(std::thread::current().id());
+        self.0.next()
+    }
+}
+
+impl Source for RecordThreads {
+    fn current_span_len(&self) -> Option<usize> {
+        self.0.current_span_len()
+    }
+
+    fn channels(&self) -> ChannelCount {
+        self.0.channels()
+    }
+
+    fn sample_rate(&self) -> SampleRate {
+        self.0.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.0.total_duration()
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn outputs_same_samples_as_inner_source() {
+    let mut source = PrefetchSource::new(ramp(), Duration::from_secs(2), &Handle::current());
+    assert_eq!(source.channels(), 1);
+    assert_eq!(source.sample_rate(), SAMPLE_RATE);
+    assert_eq!(source.total_duration(), ramp().total_duration());
+
+    wait_buffered(&source, SAMPLE_RATE as usize);
+    let samples: Vec<f32> = source.by_ref().collect();
+    // 创建时缓冲区为空，先输出一帧静音
+    assert_eq!(samples[0], 0.0);
+    assert_eq!(samples[1..], ramp().collect::<Vec<_>>());
+    assert_eq!(source.next(), None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn small_buffer_outputs_every_sample() {
+    // 缓冲区远小于音频长度时，解码任务需等待播放线程读取
+    let len = 64 * 1024;
+    let mut source =
+        PrefetchSource::new(ramp_of(len), Duration::from_millis(10), &Handle::current());
+    let mut samples = Vec::new();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    for sample in source.by_ref() {
+        assert!(Instant::now() < deadline, "prefetch timed out");
+        samples.push(sample);
+    }
+    // 缓冲区为空时输出的静音只会出现在开头或解码追赶期间，去掉静音后顺序不变
+    let expected: Vec<f32> = ramp_of(len).collect();
+    let decoded: Vec<f32> = samples.into_iter().filter(|s| *s != 0.0).collect();
+    assert_eq!(decoded, expected[1..]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn seek_returns_immediately_and_outputs_silence_until_applied() {
+    let mut source =
+        PrefetchSource::new(SlowSeek(ramp()), Duration::from_secs(2), &Handle::current());
+    wait_buffered(&source, SAMPLE_RATE as usize);
+
+    let started = Instant::now();
+    source.try_seek(Duration::from_millis(500)).unwrap();
+    assert!(started.elapsed() < Duration::from_millis(100));
+    // 跳转前解码的采样已丢弃
+    assert_eq!(source.next(), Some(0.0));
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let first = loop {
+        assert!(Instant::now() < deadline, "seek timed out");
+        match source.next() {
+            Some(0.0) => std::thread::sleep(Duration::from_millis(1)),
+            sample => break sample,
+        }
+    };
+    assert_eq!(first, Some(0.5));
+    let rest: Vec<f32> = source.collect();
+    assert_eq!(rest.len(), SAMPLE_RATE as usize / 2 - 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn decodes_on_one_thread() {
+    // 缓冲区远小于音频长度，解码线程多次休眠后被唤醒
+    let threads = Arc::new(Mutex::new(HashSet::new()));
+    let inner = RecordThreads(ramp_of(64 * 1024), threads.clone());
+    let mut source = PrefetchSource::new(inner, Duration::from_millis(10), &Handle::current());
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let mut decoded = 0;
+    for sample in source.by_ref() {
+        assert!(Instant::now() < deadline, "prefetch timed out");
+        decoded += (sample != 0.0) as usize;
+    }
+    assert_eq!(decoded, 64 * 1024 - 1);
+    assert_eq!(threads.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn created_outside_of_runtime() {
+    let runtime = Runtime::new().unwrap();
+    let source = PrefetchSource::new(ramp(), Duration::from_secs(2), runtime.handle());
+    wait_buffered(&source, SAMPLE_RATE as usize);
+    let samples: Vec<f32> = source.collect();
+    assert_eq!(samples[1..], ramp().collect::<Vec<_>>());
+}
@@ -0,0 +1,88 @@
+use remu_audio::reader::{AppendableDataWrapper, MVecU8AsyncReader, MVecU8Wrapper};
+use std::io::SeekFrom;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+/// 读取尚未完成时返回 `None`
+async fn try_read(reader: &mut MVecU8AsyncReader, buf: &mut [u8]) -> Option<usize> {
+    tokio::time::timeout(Duration::from_millis(50), reader.read(buf))
+        .await
+        .ok()
+        .map(|result| result.unwrap())
+}
+
+#[tokio::test]
+async fn read_waits_for_data() {
+    let mut wrapper = MVecU8Wrapper::new();
+    let mut reader = MVecU8AsyncReader::new(wrapper.clone());
+
+    let read = tokio::spawn(async move {
+        let mut buf = [0u8; 8];
+        let len = reader.read(&mut buf).await.unwrap();
+        (reader, buf[..len].to_vec())
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!read.is_finished());
+
+    // 写入数据后唤醒等待中的读取
+    wrapper.append_data(&[1, 2, 3]);
+    let (mut reader, data) = read.await.unwrap();
+    assert_eq!(data, vec![1, 2, 3]);
+
+    // 下载完成后返回 EOF
+    let mut buf = [0u8; 8];
+    assert_eq!(try_read(&mut reader, &mut buf).await, None);
+    wrapper.complete();
+    assert_eq!(try_read(&mut reader, &mut buf).await, Some(0));
+}
+
+#[tokio::test]
+async fn seek_past_buffered_end_waits_for_data() {
+    let mut wrapper = MVecU8Wrapper::new();
+    let mut reader = MVecU8AsyncReader::new(wrapper.clone());
+    wrapper.append_data(&(0..10).collect::<Vec<u8>>());
+
+    assert_eq!(reader.seek(SeekFrom::Start(20)).await.unwrap(), 20);
+    let mut buf = [0u8; 8];
+    assert_eq!(try_read(&mut reader, &mut buf).await, None);
+
+    // 数据未写入到跳转位置前继续等待
+    wrapper.append_data(&(10..20).collect::<Vec<u8>>());
+    assert_eq!(try_read(&mut reader, &mut buf).await, None);
+
+    wrapper.append_data(&(20..30).collect::<Vec<u8>>());
+    assert_eq!(try_read(&mut reader, &mut buf).await, Some(8));
+    assert_eq!(buf, [20, 21, 22, 23, 24, 25, 26, 27]);
+
+    assert_eq!(reader.seek(SeekFrom::Current(-4)).await.unwrap(), 24);
+    assert_eq!(try_read(&mut reader, &mut buf).await, Some(6));
+    assert_eq!(buf[..6], [24, 25, 26, 27, 28, 29]);
+
+    // 下载完成后，超出数据末尾的读取返回 EOF
+    reader.seek(SeekFrom::Start(100)).await.unwrap();
+    wrapper.complete();
+    assert_eq!(try_read(&mut reader, &mut buf).await, Some(0));
+    assert!(reader.seek(SeekFrom::End(0)).await.is_err());
+}
+
+#[tokio::test]
+async fn cancel_wakes_pending_read() {
+    let wrapper = MVecU8Wrapper::new();
+    let mut reader = MVecU8AsyncReader::new(wrapper.clone());
+    let token = reader.cancellation_token();
+
+    let read = tokio::spawn(async move {
+        let mut buf = [0u8; 8];
+        reader.read(&mut buf).await.unwrap()
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!read.is_finished());
+
+    // 取消后等待中的读取返回 EOF，无需写入数据
+    token.cancel();
+    let len = tokio::time::timeout(Duration::from_secs(1), read)
+        .await
+        .expect("cancelled read did not wake")
+        .unwrap();
+    assert_eq!(len, 0);
+}
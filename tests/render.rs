@@ -0,0 +1,192 @@
+use remu_audio::decoder::Decoder;
+use remu_audio::render::{RenderFormat, RenderOptions, Renderer, SampleFormat};
+use rodio::buffer::SamplesBuffer;
+use rodio::Source;
+use std::fs::File;
+use std::path::Path;
+
+const SAMPLE_RATE: u32 = 44_100;
+
+/// 覆盖各种子帧的测试信号：正弦波、噪声、静音及削波，长度不是帧长的整数倍
+fn test_signal(channels: u16) -> Vec<f32> {
+    let mut seed: u32 = 1;
+    let mut noise = move || {
+        seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (seed >> 8) as f32 / (1 << 24) as f32 * 2.0 - 1.0
+    };
+    let frames = 10_000;
+    let mut samples = Vec::with_capacity(frames * channels as usize);
+    for i in 0..frames {
+        for channel in 0..channels {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let sample = match i {
+                0..2000 => 0.5 * (std::f32::consts::TAU * 440.0 * (channel + 1) as f32 * t).sin(),
+                2000..5000 => 0.0,
+                5000..8000 => 0.3 * noise(),
+                // 超出满幅的部分被截断
+                _ => {
+                    if channel == 0 {
+                        1.5
+                    } else {
+                        -1.5
+                    }
+                }
+            };
+            samples.push(sample);
+        }
+    }
+    samples
+}
+
+fn render(
+    path: &Path,
+    format: RenderFormat,
+    sample_format: SampleFormat,
+    channels: u16,
+    samples: Vec<f32>,
+) {
+    let options = RenderOptions {
+        format,
+        sample_format,
+        ..Default::default()
+    };
+    let source = SamplesBuffer::new(channels, SAMPLE_RATE, samples);
+    Renderer::new(options).render(source, path).unwrap();
+}
+
+fn decode(path: &Path) -> (u16, u32, Vec<f32>) {
+    let decoder = Decoder::try_from(File::open(path).unwrap()).unwrap();
+    (decoder.channels(), decoder.sample_rate(), decoder.collect())
+}
+
+fn flac_round_trip(sample_format: SampleFormat, channels: u16, tolerance: f32) {
+    let dir = tempfile::tempdir().unwrap();
+    let flac = dir.path().join("out.flac");
+    let wav = dir.path().join("out.wav");
+    let samples = test_signal(channels);
+    render(
+        &flac,
+        RenderFormat::Flac,
+        sample_format,
+        channels,
+        samples.clone(),
+    );
+    render(
+        &wav,
+        RenderFormat::Wav,
+        sample_format,
+        channels,
+        samples.clone(),
+    );
+
+    let (decoded_channels, sample_rate, decoded) = decode(&flac);
+    assert_eq!(decoded_channels, channels);
+    assert_eq!(sample_rate, SAMPLE_RATE);
+    assert_eq!(decoded.len(), samples.len());
+    for (decoded, original) in decoded.iter().zip(&samples) {
+        let original = original.clamp(-1.0, 1.0);
+        assert!(
+            (decoded - original).abs() <= tolerance,
+            "{decoded} != {original}"
+        );
+    }
+
+    // 无损压缩，解码结果与相同位深的 WAV 完全一致
+    let (_, _, pcm) = decode(&wav);
+    assert_eq!(decoded, pcm);
+}
+
+#[test]
+fn flac_16_bit_stereo_round_trip() {
+    flac_round_trip(SampleFormat::I16, 2, 1e-4);
+}
+
+#[test]
+fn flac_24_bit_mono_round_trip() {
+    flac_round_trip(SampleFormat::I24, 1, 1e-6);
+}
+
+#[test]
+fn flac_rejects_float_samples() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = SamplesBuffer::new(1, SAMPLE_RATE, vec![0.0; 16]);
+    let options = RenderOptions {
+        format: RenderFormat::Flac,
+        sample_format: SampleFormat::F32,
+        ..Default::default()
+    };
+    let result = Renderer::new(options).render(source, dir.path().join("out.flac"));
+    assert!(result.is_err());
+}
+
+fn u16_at(bytes: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes(bytes[pos..pos + 2].try_into().unwrap())
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+
+fn render_wav(sample_format: SampleFormat, channels: u16, samples: Vec<f32>) -> Vec<u8> {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("out.wav");
+    render(&path, RenderFormat::Wav, sample_format, channels, samples);
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn wav_16_bit_header_and_data() {
+    let bytes = render_wav(SampleFormat::I16, 2, vec![0.0, 1.0, -1.0, 0.5]);
+
+    assert_eq!(bytes.len(), 44 + 8);
+    assert_eq!(&bytes[0..4], b"RIFF");
+    assert_eq!(u32_at(&bytes, 4), 36 + 8);
+    assert_eq!(&bytes[8..12], b"WAVE");
+    assert_eq!(&bytes[12..16], b"fmt ");
+    assert_eq!(u32_at(&bytes, 16), 16);
+    // PCM
+    assert_eq!(u16_at(&bytes, 20), 1);
+    assert_eq!(u16_at(&bytes, 22), 2);
+    assert_eq!(u32_at(&bytes, 24), SAMPLE_RATE);
+    assert_eq!(u32_at(&bytes, 28), SAMPLE_RATE * 4);
+    assert_eq!(u16_at(&bytes, 32), 4);
+    assert_eq!(u16_at(&bytes, 34), 16);
+    assert_eq!(&bytes[36..40], b"data");
+    assert_eq!(u32_at(&bytes, 40), 8);
+
+    let data: Vec<i16> = (bytes[44..].chunks(2))
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    assert_eq!(data, vec![0, i16::MAX, -i16::MAX, 16384]);
+}
+
+#[test]
+fn wav_float_header() {
+    let bytes = render_wav(SampleFormat::F32, 1, vec![0.25, -2.0]);
+
+    // IEEE float
+    assert_eq!(u16_at(&bytes, 20), 3);
+    assert_eq!(u16_at(&bytes, 34), 32);
+    assert_eq!(u16_at(&bytes, 32), 4);
+    assert_eq!(u32_at(&bytes, 40), 8);
+    // 浮点采样不截断
+    assert_eq!(&bytes[44..48], &0.25f32.to_le_bytes());
+    assert_eq!(&bytes[48..52], &(-2.0f32).to_le_bytes());
+}
+
+#[test]
+fn wav_24_bit_odd_length_is_padded() {
+    let bytes = render_wav(SampleFormat::I24, 1, vec![1.0, -1.0, 0.0]);
+
+    // 数据块长度为 9 字节，补齐一个字节，补齐的字节计入 RIFF 长度但不计入 data 长度
+    assert_eq!(u16_at(&bytes, 32), 3);
+    assert_eq!(u16_at(&bytes, 34), 24);
+    assert_eq!(u32_at(&bytes, 40), 9);
+    assert_eq!(u32_at(&bytes, 4), 36 + 9 + 1);
+    assert_eq!(bytes.len(), 44 + 9 + 1);
+    assert_eq!(
+        &bytes[44..53],
+        &[0xff, 0xff, 0x7f, 0x01, 0x00, 0x80, 0, 0, 0]
+    );
+    assert_eq!(bytes[53], 0);
+}
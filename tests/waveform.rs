@@ -0,0 +1,107 @@
+use remu_audio::waveform::{DatBits, Waveform, WaveformBuilder};
+
+fn i32_at(bytes: &[u8], index: usize) -> i32 {
+    i32::from_le_bytes(bytes[index * 4..index * 4 + 4].try_into().unwrap())
+}
+
+/// 每 4 帧一个数据点，最后一个数据点未满
+fn mono() -> Waveform {
+    let mut builder = WaveformBuilder::new(1, 8000, 4);
+    for sample in [0.5, -0.25, 0.1, 0.0, 1.0, -1.0, 0.0, 0.0, 0.2] {
+        builder.push(sample);
+    }
+    builder.waveform()
+}
+
+#[test]
+fn mono_16_bit_dat() {
+    let waveform = mono();
+    assert_eq!(waveform.len(), 3);
+    let dat = waveform.to_dat(DatBits::Sixteen);
+
+    // 版本、标志、采样率、每个数据点的帧数、数据点数
+    assert_eq!(dat.len(), 20 + 3 * 2 * 2);
+    assert_eq!(i32_at(&dat, 0), 1);
+    assert_eq!(i32_at(&dat, 1), 0);
+    assert_eq!(i32_at(&dat, 2), 8000);
+    assert_eq!(i32_at(&dat, 3), 4);
+    assert_eq!(i32_at(&dat, 4), 3);
+
+    let values: Vec<i16> = (dat[20..].chunks(2))
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    // 每个数据点依次为最小值、最大值，满幅的正值截断到 32767
+    assert_eq!(values, [-8192, 16384, -32768, 32767, 6554, 6554]);
+}
+
+#[test]
+fn mono_8_bit_dat() {
+    let dat = mono().to_dat(DatBits::Eight);
+
+    assert_eq!(dat.len(), 20 + 3 * 2);
+    assert_eq!(i32_at(&dat, 0), 1);
+    assert_eq!(i32_at(&dat, 1), 1);
+    assert_eq!(i32_at(&dat, 3), 4);
+    assert_eq!(i32_at(&dat, 4), 3);
+    let values: Vec<i8> = dat[20..].iter().map(|b| *b as i8).collect();
+    assert_eq!(values, [-32, 64, -128, 127, 26, 26]);
+}
+
+#[test]
+fn stereo_dat_is_version_2_with_interleaved_channels() {
+    let mut builder = WaveformBuilder::new(2, 44100, 2);
+    // 左声道为正、右声道为负
+    for sample in [0.5, -0.5, 0.25, -0.25, 0.125, -1.0] {
+        builder.push(sample);
+    }
+    let waveform = builder.waveform();
+    let dat = waveform.to_dat(DatBits::Sixteen);
+
+    assert_eq!(i32_at(&dat, 0), 2);
+    assert_eq!(i32_at(&dat, 1), 0);
+    assert_eq!(i32_at(&dat, 2), 44100);
+    assert_eq!(i32_at(&dat, 3), 2);
+    assert_eq!(i32_at(&dat, 4), 2);
+    // 声道数
+    assert_eq!(i32_at(&dat, 5), 2);
+    assert_eq!(dat.len(), 24 + 2 * 2 * 2 * 2);
+
+    let values: Vec<i16> = (dat[24..].chunks(2))
+        .map(|b| i16::from_le_bytes([b[0], b[1]]))
+        .collect();
+    assert_eq!(
+        values,
+        [8192, 16384, -16384, -8192, 4096, 4096, -32768, -32768]
+    );
+}
+
+#[test]
+fn read_dat_round_trip() {
+    for bits in [DatBits::Eight, DatBits::Sixteen] {
+        let waveform = mono();
+        let read = Waveform::read_dat(waveform.to_dat(bits).as_slice()).unwrap();
+        assert_eq!(read.sample_rate, 8000);
+        assert_eq!(read.samples_per_bucket, 4);
+        assert_eq!(read.len(), 3);
+        let tolerance = if bits == DatBits::Eight {
+            1.0 / 128.0
+        } else {
+            1.0 / 32768.0
+        };
+        for (read, original) in read.channels[0].iter().zip(&waveform.channels[0]) {
+            assert!((read.min - original.min).abs() <= tolerance);
+            assert!((read.max - original.max).abs() <= tolerance);
+            assert_eq!(read.rms, None);
+        }
+    }
+}
+
+#[test]
+fn read_dat_rejects_invalid_data() {
+    let mut dat = mono().to_dat(DatBits::Sixteen);
+    dat[0] = 3;
+    assert!(Waveform::read_dat(dat.as_slice()).is_err());
+
+    let dat = mono().to_dat(DatBits::Sixteen);
+    assert!(Waveform::read_dat(&dat[..dat.len() - 1]).is_err());
+}